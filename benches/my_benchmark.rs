@@ -1,18 +1,29 @@
-#[macro_use]
-extern crate criterion;
-extern crate pathfinder;
-use pathfinder::*;
-
-use criterion::Criterion;
-
-fn criterion_benchmark(c: &mut Criterion) {
-    let j = Point::from_radians(8f64, -2f64, 0f32);
-    let k = Point::from_radians(16f64, 6f64, 0f32);
-    let ob = Obstacle::from_radians(15f64, 0f64, 5f32, 20f32);
-    c.bench_function("perpendicular method", move |b| {
-        b.iter(|| Pathfinder::valid_path_obs(&j, &k, &ob))
-    });
-}
-
-criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
+#[macro_use]
+extern crate criterion;
+extern crate pathfinder;
+use pathfinder::*;
+
+use criterion::Criterion;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let flyzones = vec![vec![
+        Location::from_degrees(30.28f64, -97.73f64, 0f32),
+        Location::from_degrees(30.29f64, -97.73f64, 0f32),
+        Location::from_degrees(30.29f64, -97.72f64, 0f32),
+        Location::from_degrees(30.28f64, -97.72f64, 0f32),
+    ]];
+    let ob = Obstacle::new(
+        Location::from_degrees(30.285f64, -97.725f64, 0f32),
+        50f32,
+        20f32,
+    );
+    let j = Location::from_degrees(30.282f64, -97.728f64, 10f32);
+    let k = Location::from_degrees(30.288f64, -97.722f64, 10f32);
+    let mut pathfinder = Tanstar::create(5f32, flyzones, vec![ob]);
+    c.bench_function("path around obstacle", move |b| {
+        b.iter(|| pathfinder.find_shortest_path::<()>(j, k).unwrap())
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+#[macro_use]
+extern crate criterion;
+extern crate pathfinder;
+use pathfinder::*;
+
+use criterion::Criterion;
+
+// offset from the origin of the flyzone, in degrees
+fn location(lat: f64, lon: f64, alt: f32) -> Location {
+    Location::from_degrees(30.28 + lat, -97.73 + lon, alt)
+}
+
+// square flyzone crossed by a diagonal of obstacles the path has to wind through
+fn create_pathfinder() -> Tanstar {
+    let flyzones = vec![vec![
+        location(0f64, 0f64, 0f32),
+        location(0.004f64, 0f64, 0f32),
+        location(0.004f64, 0.004f64, 0f32),
+        location(0f64, 0.004f64, 0f32),
+    ]];
+    let obstacles = [
+        (0.001f64, 0.001f64),
+        (0.002f64, 0.002f64),
+        (0.003f64, 0.003f64),
+        (0.001f64, 0.003f64),
+        (0.003f64, 0.001f64),
+    ]
+    .iter()
+    .map(|&(lat, lon)| Obstacle::new(location(lat, lon, 0f32), 30f32, 50f32))
+    .collect();
+    Tanstar::create(5f32, flyzones, obstacles)
+}
+
+fn search(pathfinder: &mut Tanstar) -> usize {
+    let start = location(0.0003f64, 0.0003f64, 20f32);
+    let goal = location(0.0037f64, 0.0037f64, 20f32);
+    pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+    pathfinder.count_explored_vertices_last_search()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    for &(name, ring_heuristic) in &[("straight line heuristic", false), ("ring heuristic", true)] {
+        let mut pathfinder = create_pathfinder();
+        pathfinder.set_ring_heuristic(ring_heuristic);
+        println!("{}: {} vertices explored", name, search(&mut pathfinder));
+        c.bench_function(name, move |b| b.iter(|| search(&mut pathfinder)));
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,109 @@
+// error.rs
+// error type returned by fallible pathfinder queries
+
+use obj::Location;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathfinderError {
+    // flyzone index does not refer to an existing flyzone
+    InvalidFlyzoneIndex { index: usize },
+    // obstacle index does not refer to an existing obstacle
+    InvalidObstacleIndex { index: usize },
+    // node index does not refer to a node of the graph
+    InvalidNodeIndex { index: usize },
+    // no path could be found between the requested locations
+    NoPathFound,
+    // none of the goals could be reached
+    AllGoalsUnreachable,
+    // no path could be found without climbing above the altitude limit
+    NoPathUnderAltitudeLimit { limit: f32 },
+    // no path could be found without flying over an obstacle
+    NoPathWithoutFlyover,
+    // best path found turns more often than allowed
+    TooManyTurns { turns: usize },
+    // shortest path found is longer than the range of the aircraft
+    PathExceedsFuelRange { best_distance: f32 },
+    // search exceeded the planning timeout, with the best partial path explored
+    PlanningTimeout { partial_path: Option<Vec<Location>> },
+    // vertex ring of a node does not close back on itself
+    BrokenRing { node: usize },
+    // some nodes of the graph cannot reach each other
+    DisconnectedGraph,
+    // flyzones do not overlap, so they cannot be merged into one boundary
+    DisjointFlyzones,
+    // csv input could not be parsed
+    InvalidCsv { line: usize },
+    // generated path leaves the flyzone
+    ExitsFlyzone,
+    // generated path crosses an obstacle
+    IntersectsObstacle,
+    // location has a latitude, longitude or altitude that is not a finite number
+    InvalidCoordinates,
+    // location is not inside any flyzone
+    OutsideFlyzone,
+    // location is inside an obstacle
+    InsideObstacle { index: usize },
+    // location altitude is outside of the flight envelope
+    AltitudeOutOfRange { altitude: f32 },
+    // polygon has too few vertices, no area, or edges that are empty or fold back
+    DegeneratePolygon,
+    // standard deviation of an obstacle is negative or not a finite number
+    InvalidUncertainty,
+}
+
+impl fmt::Display for PathfinderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathfinderError::InvalidFlyzoneIndex { index } => {
+                write!(f, "flyzone index {} is out of range", index)
+            }
+            PathfinderError::InvalidObstacleIndex { index } => {
+                write!(f, "obstacle index {} is out of range", index)
+            }
+            PathfinderError::InvalidNodeIndex { index } => {
+                write!(f, "node index {} is out of range", index)
+            }
+            PathfinderError::NoPathFound => write!(f, "no path found"),
+            PathfinderError::AllGoalsUnreachable => write!(f, "no goal can be reached"),
+            PathfinderError::NoPathUnderAltitudeLimit { limit } => {
+                write!(f, "no path found under altitude {}", limit)
+            }
+            PathfinderError::NoPathWithoutFlyover => {
+                write!(f, "no path found without flying over obstacles")
+            }
+            PathfinderError::TooManyTurns { turns } => {
+                write!(f, "best path found has too many turns ({})", turns)
+            }
+            PathfinderError::PathExceedsFuelRange { best_distance } => {
+                write!(
+                    f,
+                    "shortest path of {}m exceeds the fuel range",
+                    best_distance
+                )
+            }
+            PathfinderError::PlanningTimeout { .. } => write!(f, "planning timed out"),
+            PathfinderError::BrokenRing { node } => write!(f, "node {} has a broken ring", node),
+            PathfinderError::DisconnectedGraph => write!(f, "graph is not fully connected"),
+            PathfinderError::DisjointFlyzones => write!(f, "flyzones do not overlap"),
+            PathfinderError::InvalidCsv { line } => write!(f, "invalid csv on line {}", line),
+            PathfinderError::ExitsFlyzone => write!(f, "path exits the flyzone"),
+            PathfinderError::IntersectsObstacle => write!(f, "path intersects an obstacle"),
+            PathfinderError::InvalidCoordinates => write!(f, "location is not finite"),
+            PathfinderError::OutsideFlyzone => write!(f, "location is outside of the flyzones"),
+            PathfinderError::InsideObstacle { index } => {
+                write!(f, "location is inside obstacle {}", index)
+            }
+            PathfinderError::AltitudeOutOfRange { altitude } => {
+                write!(f, "altitude {} is outside of the flight envelope", altitude)
+            }
+            PathfinderError::DegeneratePolygon => write!(f, "polygon is degenerate"),
+            PathfinderError::InvalidUncertainty => {
+                write!(f, "obstacle uncertainty is negative or not finite")
+            }
+        }
+    }
+}
+
+impl Error for PathfinderError {}
@@ -7,7 +7,9 @@ pub mod obj;
 pub mod tanstar;
 
 mod algorithm;
+mod error;
 
+pub use error::PathfinderError;
 pub use obj::*;
 pub use tanstar::{TConfig, Tanstar};
 
@@ -82,6 +84,51 @@ impl<A: Algorithm> Pathfinder<A> {
     }
 }
 
+// Builder to assemble the inputs of a pathfinder before initializing the algorithm
+pub struct PathfinderBuilder<A: Algorithm> {
+    algo: A,
+    config: A::Config,
+    flyzones: Vec<Vec<Location>>,
+    obstacles: Vec<Obstacle>,
+}
+
+impl<A: Algorithm> PathfinderBuilder<A> {
+    pub fn new(algo: A, config: A::Config) -> Self {
+        Self {
+            algo,
+            config,
+            flyzones: Vec::new(),
+            obstacles: Vec::new(),
+        }
+    }
+
+    pub fn with_flyzones(mut self, flyzones: Vec<Vec<Location>>) -> Self {
+        self.flyzones = flyzones;
+        self
+    }
+
+    pub fn with_obstacles(self, obstacles: Vec<Obstacle>) -> Self {
+        self.with_obstacles_from_iterator(obstacles.into_iter())
+    }
+
+    // Validate and store obstacles one at a time without collecting them first
+    pub fn with_obstacles_from_iterator(mut self, iter: impl Iterator<Item = Obstacle>) -> Self {
+        self.obstacles.reserve(iter.size_hint().0);
+        for obstacle in iter {
+            assert!(
+                obstacle.radius > 0f32 && obstacle.height >= 0f32,
+                "Obstacle requires a positive radius and height."
+            );
+            self.obstacles.push(obstacle);
+        }
+        self
+    }
+
+    pub fn build(self) -> Pathfinder<A> {
+        Pathfinder::new(self.algo, self.config, self.flyzones, self.obstacles)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,4 +144,41 @@ mod tests {
     fn tanstar_invalid_flyzone_test() {
         Pathfinder::new(Tanstar::new(), TConfig::default(), vec![vec![]], Vec::new());
     }
+
+    #[test]
+    fn builder_obstacles_from_iterator_test() {
+        let mut count = 0;
+        let obstacles = std::iter::repeat_with(|| {
+            count += 1;
+            Obstacle::from_degrees(30.32, 65.25, count as f32, 10f32)
+        })
+        .take(1000);
+        let builder = PathfinderBuilder::new(Tanstar::new(), TConfig::default())
+            .with_obstacles_from_iterator(obstacles);
+        assert_eq!(builder.obstacles.len(), 1000);
+        assert_eq!(builder.obstacles.capacity(), 1000);
+        assert_eq!(builder.obstacles[999].radius, 1000f32);
+    }
+
+    #[test]
+    fn builder_test() {
+        let flyzones = vec![vec![
+            Location::from_degrees(30.32469, -97.60466, 0f32),
+            Location::from_degrees(30.32437, -97.60367, 0f32),
+            Location::from_degrees(30.32356, -97.60410, 0f32),
+        ]];
+        let obstacles = vec![Obstacle::from_degrees(-97.60410, 30.32420, 5f32, 20f32)];
+        let pathfinder = PathfinderBuilder::new(Tanstar::new(), TConfig::default())
+            .with_flyzones(flyzones)
+            .with_obstacles(obstacles)
+            .build();
+        assert_eq!(pathfinder.get_obstacle().len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn builder_invalid_obstacle_test() {
+        PathfinderBuilder::new(Tanstar::new(), TConfig::default())
+            .with_obstacles(vec![Obstacle::from_degrees(30.32, 65.25, 0f32, 10f32)]);
+    }
 }
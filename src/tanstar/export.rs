@@ -0,0 +1,730 @@
+// export.rs
+// Contains serializers to hand paths over to ground control and autopilot software
+
+use super::*;
+
+use std::fmt::Write;
+
+// MAVLink v1 framing
+const MAVLINK_STX: u8 = 0xFE;
+const MAVLINK_SYSTEM_ID: u8 = 255; // ground control station
+const MAVLINK_COMPONENT_ID: u8 = 190; // mission planner
+const MAVLINK_TARGET_SYSTEM: u8 = 1;
+const MAVLINK_TARGET_COMPONENT: u8 = 1;
+// MISSION_ITEM_INT message
+const MISSION_ITEM_INT_ID: u8 = 73;
+const MISSION_ITEM_INT_LEN: u8 = 37;
+const MISSION_ITEM_INT_CRC_EXTRA: u8 = 38;
+// MAV_CMD and MAV_FRAME values
+const MAV_CMD_NAV_WAYPOINT: u16 = 16;
+const MAV_CMD_NAV_RETURN_TO_LAUNCH: u16 = 20;
+const MAV_CMD_NAV_LAND: u16 = 21;
+const MAV_CMD_NAV_TAKEOFF: u16 = 22;
+const MAV_FRAME_GLOBAL: u8 = 0;
+const MAV_FRAME_GLOBAL_RELATIVE_ALT_INT: u8 = 6;
+const MAV_FRAME_MISSION: u8 = 2;
+const MAV_FRAME_GLOBAL_RELATIVE_ALT: u8 = 3;
+// QGroundControl plan values
+const QGC_FIRMWARE_ARDUPILOT: u8 = 3;
+const QGC_VEHICLE_FIXED_WING: u8 = 1;
+// first line of ArduPilot waypoint files
+const WPL_HEADER: &str = "QGC WPL 110";
+
+const CSV_HEADER: &str = "lat,lon,alt,heading";
+// header of csv exported before headings were added
+const CSV_HEADER_NO_HEADING: &str = "lat,lon,alt";
+const KML_HEADER: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n";
+
+// Accumulate one byte into a CRC-16/MCRF4XX checksum, as used by MAVLink
+fn crc_accumulate(byte: u8, crc: u16) -> u16 {
+    let mut tmp = byte ^ (crc & 0xFF) as u8;
+    tmp ^= tmp << 4;
+    let tmp = u16::from(tmp);
+    (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4)
+}
+
+fn crc_calculate(bytes: &[u8]) -> u16 {
+    bytes
+        .iter()
+        .fold(0xFFFF, |crc, &byte| crc_accumulate(byte, crc))
+}
+
+impl Tanstar {
+    // Serialize waypoints into a sequence of MAVLink v1 MISSION_ITEM_INT messages
+    // The first waypoint is sent as a takeoff, the last as a landing and the rest as waypoints
+    pub fn waypoint_mission_to_mavlink(&self, waypoints: &[Location]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (i, waypoint) in waypoints.iter().enumerate() {
+            let command = if i == 0 {
+                MAV_CMD_NAV_TAKEOFF
+            } else if i == waypoints.len() - 1 {
+                MAV_CMD_NAV_LAND
+            } else {
+                MAV_CMD_NAV_WAYPOINT
+            };
+
+            // payload fields are ordered by size as required by MAVLink
+            let mut payload = Vec::with_capacity(MISSION_ITEM_INT_LEN as usize);
+            for param in &[0f32; 4] {
+                payload.extend_from_slice(&param.to_bits().to_le_bytes());
+            }
+            let x = (waypoint.lat_degree() * 1e7).round() as i32;
+            let y = (waypoint.lon_degree() * 1e7).round() as i32;
+            payload.extend_from_slice(&x.to_le_bytes());
+            payload.extend_from_slice(&y.to_le_bytes());
+            payload.extend_from_slice(&waypoint.alt().to_bits().to_le_bytes());
+            payload.extend_from_slice(&(i as u16).to_le_bytes());
+            payload.extend_from_slice(&command.to_le_bytes());
+            payload.push(MAVLINK_TARGET_SYSTEM);
+            payload.push(MAVLINK_TARGET_COMPONENT);
+            payload.push(MAV_FRAME_GLOBAL_RELATIVE_ALT_INT);
+            payload.push(if i == 0 { 1 } else { 0 }); // current
+            payload.push(1); // autocontinue
+
+            let mut frame = vec![
+                MAVLINK_STX,
+                MISSION_ITEM_INT_LEN,
+                (i % 256) as u8,
+                MAVLINK_SYSTEM_ID,
+                MAVLINK_COMPONENT_ID,
+                MISSION_ITEM_INT_ID,
+            ];
+            frame.extend_from_slice(&payload);
+            // checksum covers everything after the start byte, followed by the crc extra
+            let crc = crc_accumulate(MISSION_ITEM_INT_CRC_EXTRA, crc_calculate(&frame[1..]));
+            frame.extend_from_slice(&crc.to_le_bytes());
+            bytes.append(&mut frame);
+        }
+        bytes
+    }
+
+    // Serialize waypoints as a QGroundControl .plan mission
+    // The first waypoint is a takeoff, the last a return to launch and the rest are waypoints.
+    // The takeoff location is used as the planned home position
+    pub fn waypoints_to_qgc_plan(&self, waypoints: &[Location]) -> String {
+        let mut items = Vec::new();
+        for (i, waypoint) in waypoints.iter().enumerate() {
+            let (command, frame) = if i == 0 {
+                (MAV_CMD_NAV_TAKEOFF, MAV_FRAME_GLOBAL_RELATIVE_ALT)
+            } else if i == waypoints.len() - 1 {
+                (MAV_CMD_NAV_RETURN_TO_LAUNCH, MAV_FRAME_MISSION)
+            } else {
+                (MAV_CMD_NAV_WAYPOINT, MAV_FRAME_GLOBAL_RELATIVE_ALT)
+            };
+            // return to launch flies home, so its location is left empty
+            let (lat, lon, alt) = if command == MAV_CMD_NAV_RETURN_TO_LAUNCH {
+                (0f64, 0f64, 0f32)
+            } else {
+                (waypoint.lat_degree(), waypoint.lon_degree(), waypoint.alt())
+            };
+            let mut item = String::new();
+            write!(
+                item,
+                "{{\"autoContinue\":true,\"command\":{},\"doJumpId\":{},\"frame\":{},\
+                 \"params\":[0,0,0,null,{},{},{}],\"type\":\"SimpleItem\"}}",
+                command,
+                i + 1,
+                frame,
+                lat,
+                lon,
+                alt
+            )
+            .expect("error in writing plan");
+            items.push(item);
+        }
+        let home = match waypoints.first() {
+            Some(&home) => home,
+            None => self.home_location(),
+        };
+        format!(
+            "{{\"fileType\":\"Plan\",\"geoFence\":{{\"circles\":[],\"polygons\":[],\"version\":2}},\
+             \"groundStation\":\"QGroundControl\",\"mission\":{{\"cruiseSpeed\":15,\
+             \"firmwareType\":{},\"hoverSpeed\":5,\"items\":[{}],\
+             \"plannedHomePosition\":[{},{},{}],\"vehicleType\":{},\"version\":2}},\
+             \"rallyPoints\":{{\"points\":[],\"version\":2}},\"version\":1}}",
+            QGC_FIRMWARE_ARDUPILOT,
+            items.join(","),
+            home.lat_degree(),
+            home.lon_degree(),
+            home.alt(),
+            QGC_VEHICLE_FIXED_WING
+        )
+    }
+
+    // Serialize waypoints as an ArduPilot waypoint file, one tab separated row of
+    // index, current, frame, command, p1-p4, lat, lon, alt and autocontinue each
+    // The first waypoint is the home position, flagged as current with absolute altitude, and
+    // the rest are waypoints with altitude relative to home
+    pub fn waypoint_mission_to_ardupilot_txt(&self, waypoints: &[Location]) -> String {
+        let mut txt = String::from(WPL_HEADER);
+        txt.push('\n');
+        for (i, waypoint) in waypoints.iter().enumerate() {
+            let (current, frame) = if i == 0 {
+                (1, MAV_FRAME_GLOBAL)
+            } else {
+                (0, MAV_FRAME_GLOBAL_RELATIVE_ALT)
+            };
+            writeln!(
+                txt,
+                "{}\t{}\t{}\t{}\t0\t0\t0\t0\t{:.8}\t{:.8}\t{:.6}\t1",
+                i,
+                current,
+                frame,
+                MAV_CMD_NAV_WAYPOINT,
+                waypoint.lat_degree(),
+                waypoint.lon_degree(),
+                waypoint.alt()
+            )
+            .expect("error in writing waypoint file");
+        }
+        txt
+    }
+
+    // Serialize waypoints as csv with a header row, one row of lat,lon (degrees),alt (meters) each
+    // heading is the compass heading in degrees towards the next waypoint, the last waypoint
+    // keeps the heading it was reached with
+    pub fn export_path_csv(&self, waypoints: &[Location]) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        csv.push('\n');
+        let points: Vec<Point> = waypoints
+            .iter()
+            .map(|waypoint| Point::from((waypoint, &self.origin)))
+            .collect();
+        let mut heading = 0f32;
+        for (i, waypoint) in waypoints.iter().enumerate() {
+            if i + 1 < points.len() {
+                heading = heading_degrees(&points[i], &points[i + 1]);
+            }
+            writeln!(
+                csv,
+                "{},{},{},{}",
+                waypoint.lat_degree(),
+                waypoint.lon_degree(),
+                waypoint.alt(),
+                heading
+            )
+            .expect("error in writing csv");
+        }
+        csv
+    }
+
+    // (lon, lat) in degrees of each waypoint, for drawing the path on a map
+    pub fn path_ground_track(&self, waypoints: &[Location]) -> Vec<(f64, f64)> {
+        waypoints
+            .iter()
+            .map(|waypoint| (waypoint.lon_degree(), waypoint.lat_degree()))
+            .collect()
+    }
+
+    // Altitude in meters of each waypoint
+    pub fn path_altitudes(&self, waypoints: &[Location]) -> Vec<f32> {
+        waypoints.iter().map(|waypoint| waypoint.alt()).collect()
+    }
+
+    // Parse waypoints from csv generated by export_path_csv, headings are ignored
+    // blank lines are skipped, and line numbers in errors start at 1 for the header
+    pub fn import_waypoints_from_csv(csv: &str) -> Result<Vec<Location>, PathfinderError> {
+        let mut lines = csv.lines().enumerate();
+        let columns = match lines.next() {
+            Some((_, header)) if header.trim() == CSV_HEADER => 4,
+            Some((_, header)) if header.trim() == CSV_HEADER_NO_HEADING => 3,
+            _ => return Err(PathfinderError::InvalidCsv { line: 1 }),
+        };
+        let mut waypoints = Vec::new();
+        for (i, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = PathfinderError::InvalidCsv { line: i + 1 };
+            let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+            if fields.len() != columns {
+                return Err(error);
+            }
+            match (
+                fields[0].parse::<f64>(),
+                fields[1].parse::<f64>(),
+                fields[2].parse::<f32>(),
+            ) {
+                (Ok(lat), Ok(lon), Ok(alt)) => {
+                    waypoints.push(Location::from_degrees(lat, lon, alt))
+                }
+                _ => return Err(error),
+            }
+        }
+        Ok(waypoints)
+    }
+
+    // Serialize the graph as a json adjacency list
+    // nodes are the vertices of the graph, tagged with the index of the node they belong to,
+    // and edges are the connections between vertices
+    pub fn export_graph_json(&self) -> Result<String, PathfinderError> {
+        self.validate_graph()?;
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            for vertex in node.borrow().vertices() {
+                let vertex = vertex.borrow();
+                let mut entry = String::new();
+                write!(
+                    entry,
+                    "{{\"id\":{},\"node\":{},\"x\":{},\"y\":{},\"sentinel\":{}}}",
+                    vertex.index, i, vertex.location.x, vertex.location.y, vertex.sentinel
+                )
+                .expect("error in writing json");
+                nodes.push(entry);
+                for edge in &vertex.connection {
+                    let mut entry = String::new();
+                    write!(
+                        entry,
+                        "{{\"from\":{},\"to\":{},\"distance\":{},\"threshold\":{}}}",
+                        vertex.index,
+                        edge.neighbor.borrow().index,
+                        edge.distance,
+                        edge.threshold
+                    )
+                    .expect("error in writing json");
+                    edges.push(entry);
+                }
+            }
+        }
+        Ok(format!(
+            "{{\"nodes\":[{}],\"edges\":[{}]}}",
+            nodes.join(","),
+            edges.join(",")
+        ))
+    }
+
+    // Serialize waypoints and obstacles as kml for Google Earth
+    // each waypoint is a placemark and the path is a line string through all of them, with
+    // coordinates as lon,lat (degrees),alt (meters)
+    pub fn export_kml(&self, waypoints: &[Location]) -> String {
+        let mut kml = String::from(KML_HEADER);
+        kml.push_str("<Document>\n<Folder><name>Waypoints</name>\n");
+        for (i, waypoint) in waypoints.iter().enumerate() {
+            writeln!(
+                kml,
+                "<Placemark><name>Waypoint {}</name><Point><altitudeMode>relativeToGround</altitudeMode><coordinates>{},{},{}</coordinates></Point></Placemark>",
+                i,
+                waypoint.lon_degree(),
+                waypoint.lat_degree(),
+                waypoint.alt()
+            )
+            .expect("error in writing kml");
+        }
+        kml.push_str("</Folder>\n<Folder><name>Path</name>\n");
+        kml.push_str("<Placemark><name>Path</name><LineString>");
+        kml.push_str("<altitudeMode>relativeToGround</altitudeMode><coordinates>");
+        let coordinates: Vec<String> = waypoints
+            .iter()
+            .map(|waypoint| {
+                format!(
+                    "{},{},{}",
+                    waypoint.lon_degree(),
+                    waypoint.lat_degree(),
+                    waypoint.alt()
+                )
+            })
+            .collect();
+        kml.push_str(&coordinates.join(" "));
+        kml.push_str("</coordinates></LineString></Placemark>\n</Folder>\n");
+        kml.push_str("<Folder><name>Obstacles</name>\n");
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            writeln!(
+                kml,
+                "<Placemark><name>Obstacle {}</name><description>radius {}m, height {}m</description><Point><coordinates>{},{},0</coordinates></Point></Placemark>",
+                i,
+                obstacle.radius,
+                obstacle.height,
+                obstacle.location.lon_degree(),
+                obstacle.location.lat_degree()
+            )
+            .expect("error in writing kml");
+        }
+        kml.push_str("</Folder>\n</Document>\n</kml>\n");
+        kml
+    }
+
+    // Serialize the visibility graph between nodes in graphviz dot format
+    pub fn export_dot(&self) -> String {
+        let mut dot = String::from("graph visibility {\n");
+        for i in 0..self.nodes.len() {
+            writeln!(dot, "    {};", i).expect("error in writing dot");
+        }
+        for (a, b) in self.find_visibility_graph() {
+            writeln!(dot, "    {} -- {};", a, b).expect("error in writing dot");
+        }
+        dot.push('}');
+        dot.push('\n');
+        dot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate roxmltree;
+    extern crate serde_json;
+
+    use super::*;
+
+    fn dummy_pathfinder() -> Tanstar {
+        let flyzone = vec![vec![
+            Location::from_degrees(30.32469, -97.60466, 0f32),
+            Location::from_degrees(30.32437, -97.60367, 0f32),
+            Location::from_degrees(30.32356, -97.60333, 0f32),
+        ]];
+        Tanstar::create(1f32, flyzone, Vec::new())
+    }
+
+    // Minimal MAVLink v1 parser returning (seq, command, lat, lon, alt) of each mission item
+    fn parse_mission_items(mut bytes: &[u8]) -> Vec<(u16, u16, f64, f64, f32)> {
+        let u16_at = |b: &[u8], i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+        let u32_at = |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+        let mut items = Vec::new();
+        while !bytes.is_empty() {
+            assert_eq!(bytes[0], MAVLINK_STX);
+            let len = bytes[1] as usize;
+            assert_eq!(bytes[5], MISSION_ITEM_INT_ID);
+            let frame = &bytes[..len + 8];
+            let crc = crc_accumulate(
+                MISSION_ITEM_INT_CRC_EXTRA,
+                crc_calculate(&frame[1..len + 6]),
+            );
+            assert_eq!(crc, u16_at(frame, len + 6));
+
+            let payload = &frame[6..len + 6];
+            let lat = f64::from(u32_at(payload, 16) as i32) / 1e7;
+            let lon = f64::from(u32_at(payload, 20) as i32) / 1e7;
+            let alt = f32::from_bits(u32_at(payload, 24));
+            items.push((u16_at(payload, 28), u16_at(payload, 30), lat, lon, alt));
+            bytes = &bytes[len + 8..];
+        }
+        items
+    }
+
+    #[test]
+    fn crc_test() {
+        // check value for CRC-16/MCRF4XX
+        assert_eq!(crc_calculate(b"123456789"), 0x6F91);
+    }
+
+    #[test]
+    fn mavlink_mission_test() {
+        let pathfinder = dummy_pathfinder();
+        let waypoints = vec![
+            Location::from_degrees(30.32469, -97.60466, 10f32),
+            Location::from_degrees(30.32437, -97.60367, 50f32),
+            Location::from_degrees(30.32356, -97.60333, 50f32),
+            Location::from_degrees(30.32400, -97.60400, 0f32),
+        ];
+        let bytes = pathfinder.waypoint_mission_to_mavlink(&waypoints);
+        assert_eq!(
+            bytes.len(),
+            waypoints.len() * (MISSION_ITEM_INT_LEN as usize + 8)
+        );
+
+        // takeoff frame as encoded by the independent rust-mavlink 0.13 implementation
+        let takeoff: [u8; 45] = [
+            0xFE, 0x25, 0x00, 0xFF, 0xBE, 0x49, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x34, 0x2E, 0x13, 0x12, 0xF8, 0xB5,
+            0xD2, 0xC5, 0x00, 0x00, 0x20, 0x41, 0x00, 0x00, 0x16, 0x00, 0x01, 0x01, 0x06, 0x01,
+            0x01, 0x16, 0xBB,
+        ];
+        assert_eq!(&bytes[..takeoff.len()], &takeoff[..]);
+
+        let items = parse_mission_items(&bytes);
+        assert_eq!(items.len(), waypoints.len());
+        let commands = [
+            MAV_CMD_NAV_TAKEOFF,
+            MAV_CMD_NAV_WAYPOINT,
+            MAV_CMD_NAV_WAYPOINT,
+            MAV_CMD_NAV_LAND,
+        ];
+        for (i, (item, waypoint)) in items.iter().zip(&waypoints).enumerate() {
+            let &(seq, command, lat, lon, alt) = item;
+            assert_eq!(seq as usize, i);
+            assert_eq!(command, commands[i]);
+            assert!((lat - waypoint.lat_degree()).abs() < 1e-6);
+            assert!((lon - waypoint.lon_degree()).abs() < 1e-6);
+            assert_eq!(alt, waypoint.alt());
+        }
+    }
+
+    // Reference parser for ArduPilot waypoint files returning the 12 columns of each row
+    fn parse_wpl(txt: &str) -> Vec<Vec<f64>> {
+        let mut lines = txt.lines();
+        assert_eq!(lines.next(), Some(WPL_HEADER));
+        lines
+            .map(|line| {
+                let columns: Vec<f64> = line.split('\t').map(|c| c.parse().unwrap()).collect();
+                assert_eq!(columns.len(), 12);
+                columns
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ardupilot_txt_test() {
+        let pathfinder = dummy_pathfinder();
+        let waypoints = vec![
+            Location::from_degrees(30.32469, -97.60466, 0f32),
+            Location::from_degrees(30.32437, -97.60367, 50f32),
+            Location::from_degrees(30.32356, -97.60333, 50f32),
+            Location::from_degrees(30.32400, -97.60400, 75.5f32),
+            Location::from_degrees(30.32420, -97.60420, 20f32),
+        ];
+        let txt = pathfinder.waypoint_mission_to_ardupilot_txt(&waypoints);
+        let rows = parse_wpl(&txt);
+        assert_eq!(rows.len(), waypoints.len());
+        for (i, (row, waypoint)) in rows.iter().zip(&waypoints).enumerate() {
+            assert_eq!(row[0] as usize, i);
+            assert_eq!(row[1], if i == 0 { 1f64 } else { 0f64 });
+            let frame = if i == 0 {
+                MAV_FRAME_GLOBAL
+            } else {
+                MAV_FRAME_GLOBAL_RELATIVE_ALT
+            };
+            assert_eq!(row[2], f64::from(frame));
+            assert_eq!(row[3], f64::from(MAV_CMD_NAV_WAYPOINT));
+            assert!((row[8] - waypoint.lat_degree()).abs() < 1e-7);
+            assert!((row[9] - waypoint.lon_degree()).abs() < 1e-7);
+            assert!((row[10] - f64::from(waypoint.alt())).abs() < 1e-4);
+            assert_eq!(row[11], 1f64);
+        }
+        assert_eq!(
+            pathfinder.waypoint_mission_to_ardupilot_txt(&[]),
+            format!("{}\n", WPL_HEADER)
+        );
+    }
+
+    // Check the kml is well formed and return the text inside every coordinates element
+    fn parse_kml(kml: &str) -> Vec<String> {
+        let document = roxmltree::Document::parse(kml).unwrap();
+        let root = document.root_element();
+        assert_eq!(root.tag_name().name(), "kml");
+        assert_eq!(
+            root.tag_name().namespace(),
+            Some("http://www.opengis.net/kml/2.2")
+        );
+        root.descendants()
+            .filter(|node| node.tag_name().name() == "coordinates")
+            .map(|node| node.text().unwrap_or("").to_string())
+            .collect()
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_kml_rejects_malformed_test() {
+        let kml = format!(
+            "{}<Document><name>a & b</name></Document></kml>",
+            KML_HEADER
+        );
+        parse_kml(&kml);
+    }
+
+    #[test]
+    fn export_kml_test() {
+        let flyzone = vec![vec![
+            Location::from_degrees(30.32469, -97.60466, 0f32),
+            Location::from_degrees(30.32437, -97.60367, 0f32),
+            Location::from_degrees(30.32356, -97.60333, 0f32),
+        ]];
+        let obstacles = vec![Obstacle::new(
+            Location::from_degrees(30.32420, -97.60380, 20f32),
+            5f32,
+            20f32,
+        )];
+        let pathfinder = Tanstar::create(1f32, flyzone, obstacles);
+        let waypoints = vec![
+            Location::from_degrees(30.32450, -97.60420, 10f32),
+            Location::from_degrees(30.32400, -97.60360, 30.5f32),
+        ];
+        let kml = pathfinder.export_kml(&waypoints);
+        assert!(kml.starts_with("<?xml"));
+        let coordinates = parse_kml(&kml);
+        // two waypoints, the path and the obstacle
+        assert_eq!(coordinates.len(), 4);
+        let lon_lat_alt = |text: &str| -> Vec<f64> {
+            text.split(',')
+                .map(|field| field.parse().unwrap())
+                .collect()
+        };
+        for (text, waypoint) in coordinates.iter().zip(waypoints.iter()) {
+            let fields = lon_lat_alt(text);
+            assert!((fields[0] - waypoint.lon_degree()).abs() < 1e-9);
+            assert!((fields[1] - waypoint.lat_degree()).abs() < 1e-9);
+            assert_eq!(fields[2] as f32, waypoint.alt());
+        }
+        assert_eq!(coordinates[2].split(' ').count(), 2);
+        assert!((lon_lat_alt(&coordinates[3])[0] - -97.60380).abs() < 1e-9);
+        assert!(kml.contains("<description>radius 5m, height 20m</description>"));
+    }
+
+    #[test]
+    fn export_dot_test() {
+        let pathfinder = dummy_pathfinder();
+        let dot = pathfinder.export_dot();
+        let lines: Vec<&str> = dot.lines().collect();
+        assert_eq!(lines[0], "graph visibility {");
+        assert_eq!(lines[lines.len() - 1], "}");
+        let edges: Vec<&str> = lines.iter().filter(|l| l.contains("--")).cloned().collect();
+        assert_eq!(edges.len(), pathfinder.find_visibility_graph().len());
+        for (edge, &(a, b)) in edges.iter().zip(pathfinder.find_visibility_graph().iter()) {
+            assert_eq!(*edge, format!("    {} -- {};", a, b));
+        }
+        assert_eq!(lines.len(), pathfinder.nodes.len() + edges.len() + 2);
+    }
+
+    #[test]
+    fn graph_json_test() {
+        let pathfinder = dummy_pathfinder();
+        assert_eq!(pathfinder.nodes.len(), 3);
+        let json: serde_json::Value =
+            serde_json::from_str(&pathfinder.export_graph_json().unwrap()).unwrap();
+
+        let mut vertices = Vec::new();
+        for node in &pathfinder.nodes {
+            vertices.extend(node.borrow().vertices());
+        }
+        assert_eq!(json["nodes"].as_array().unwrap().len(), vertices.len());
+
+        let edges = json["edges"].as_array().unwrap();
+        let expected: Vec<(i64, i64, f64)> = vertices
+            .iter()
+            .flat_map(|v| {
+                let index = i64::from(v.borrow().index);
+                v.borrow()
+                    .connection
+                    .iter()
+                    .map(|edge| {
+                        let to = i64::from(edge.neighbor.borrow().index);
+                        (index, to, f64::from(edge.distance))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(edges.len(), expected.len());
+        for (edge, &(from, to, distance)) in edges.iter().zip(expected.iter()) {
+            assert_eq!(edge["from"].as_i64().unwrap(), from);
+            assert_eq!(edge["to"].as_i64().unwrap(), to);
+            assert!((edge["distance"].as_f64().unwrap() - distance).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn ground_track_test() {
+        let pathfinder = dummy_pathfinder();
+        let waypoints = vec![
+            Location::from_degrees(30.32469, -97.60466, 0f32),
+            Location::from_degrees(30.32437, -97.60367, 50.5f32),
+            Location::from_degrees(30.32356, -97.60333, 100f32),
+        ];
+        let track = pathfinder.path_ground_track(&waypoints);
+        assert_eq!(track.len(), waypoints.len());
+        for (&(lon, lat), waypoint) in track.iter().zip(waypoints.iter()) {
+            assert_eq!(lon, waypoint.lon_degree());
+            assert_eq!(lat, waypoint.lat_degree());
+        }
+        assert!((track[0].0 + 97.60466).abs() < 1e-9);
+        assert!((track[0].1 - 30.32469).abs() < 1e-9);
+        assert_eq!(
+            pathfinder.path_altitudes(&waypoints),
+            [0f32, 50.5f32, 100f32]
+        );
+
+        assert!(pathfinder.path_ground_track(&[]).is_empty());
+        assert!(pathfinder.path_altitudes(&[]).is_empty());
+    }
+
+    #[test]
+    fn path_csv_test() {
+        let pathfinder = dummy_pathfinder();
+        let waypoints = vec![
+            Location::from_degrees(30.32469, -97.60466, 0f32),
+            Location::from_degrees(30.32437, -97.60367, 50.5f32),
+            Location::from_degrees(30.32356, -97.60333, 100f32),
+        ];
+        let csv = pathfinder.export_path_csv(&waypoints);
+        assert_eq!(csv.lines().count(), 4);
+        assert_eq!(csv.lines().next(), Some(CSV_HEADER));
+        let headings: Vec<f32> = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.rsplit(',').next().unwrap().parse().unwrap())
+            .collect();
+        let points: Vec<Point> = waypoints
+            .iter()
+            .map(|waypoint| Point::from((waypoint, &pathfinder.origin)))
+            .collect();
+        let first = heading_degrees(&points[0], &points[1]);
+        let second = heading_degrees(&points[1], &points[2]);
+        assert!((headings[0] - first).abs() < 1e-3);
+        assert!((headings[1] - second).abs() < 1e-3);
+        assert!((headings[2] - second).abs() < 1e-3);
+
+        let imported = Tanstar::import_waypoints_from_csv(&csv).unwrap();
+        assert_eq!(imported.len(), waypoints.len());
+        for (a, b) in imported.iter().zip(waypoints.iter()) {
+            assert!((a.lat_degree() - b.lat_degree()).abs() < 1e-9);
+            assert!((a.lon_degree() - b.lon_degree()).abs() < 1e-9);
+            assert_eq!(a.alt(), b.alt());
+        }
+
+        // csv without headings can still be imported
+        assert_eq!(
+            Tanstar::import_waypoints_from_csv("lat,lon,alt\n30.1,-97.2,10\n")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            Tanstar::import_waypoints_from_csv("lat,lon\n"),
+            Err(PathfinderError::InvalidCsv { line: 1 })
+        );
+        assert_eq!(
+            Tanstar::import_waypoints_from_csv("lat,lon,alt\n30.1,-97.2,10\n30.1,north,10\n"),
+            Err(PathfinderError::InvalidCsv { line: 3 })
+        );
+    }
+
+    #[test]
+    fn qgc_plan_test() {
+        let pathfinder = dummy_pathfinder();
+        let waypoints = vec![
+            Location::from_degrees(30.32469, -97.60466, 10f32),
+            Location::from_degrees(30.32437, -97.60367, 50f32),
+            Location::from_degrees(30.32356, -97.60333, 50.5f32),
+            Location::from_degrees(30.32400, -97.60400, 0f32),
+        ];
+        let plan: serde_json::Value =
+            serde_json::from_str(&pathfinder.waypoints_to_qgc_plan(&waypoints)).unwrap();
+        assert_eq!(plan["fileType"], "Plan");
+        assert_eq!(plan["groundStation"], "QGroundControl");
+        assert_eq!(plan["version"], 1);
+
+        let mission = &plan["mission"];
+        let items = mission["items"].as_array().unwrap();
+        assert_eq!(items.len(), waypoints.len());
+        let commands: Vec<u64> = items
+            .iter()
+            .map(|item| item["command"].as_u64().unwrap())
+            .collect();
+        assert_eq!(commands, [22, 16, 16, 20]);
+        for (i, item) in items.iter().enumerate() {
+            assert_eq!(item["type"], "SimpleItem");
+            assert_eq!(item["doJumpId"], i as u64 + 1);
+        }
+        let params = items[2]["params"].as_array().unwrap();
+        assert_eq!(params.len(), 7);
+        assert!((params[4].as_f64().unwrap() - 30.32356).abs() < 1e-9);
+        assert!((params[5].as_f64().unwrap() - -97.60333).abs() < 1e-9);
+        assert_eq!(params[6].as_f64(), Some(50.5));
+        let home = mission["plannedHomePosition"].as_array().unwrap();
+        assert!((home[0].as_f64().unwrap() - 30.32469).abs() < 1e-9);
+
+        let plan: serde_json::Value =
+            serde_json::from_str(&pathfinder.waypoints_to_qgc_plan(&[])).unwrap();
+        assert!(plan["mission"]["items"].as_array().unwrap().is_empty());
+    }
+}
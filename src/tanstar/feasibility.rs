@@ -0,0 +1,362 @@
+// feasibility.rs
+// Contains the pre-flight checks of locations and complete paths
+
+use super::*;
+
+// Flight envelope used for the feasibility check, altitudes are set with set_altitude_limits
+pub const MAX_FLIGHT_ANGLE: f32 = PI / 3f32; // In radians, from horizontal
+
+// Problem found on a segment, segments are numbered from the first waypoint
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeasibilityViolation {
+    SegmentIntersectsObstacle { segment: usize, obstacle: usize },
+    SegmentExitsFlyzone { segment: usize },
+    AltitudeTooLow { segment: usize, min: f32 },
+    AltitudeTooHigh { segment: usize, max: f32 },
+    SegmentTooSteep { segment: usize, angle: f32 },
+    PathCrossesItself { first: usize, second: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeasibilityReport {
+    pub valid: bool, // True only if there are no violations
+    pub violations: Vec<FeasibilityViolation>,
+}
+
+impl Tanstar {
+    // Check that a location can be used as the start or end of a path
+    // returns the first problem found out of non finite coordinates, being outside every
+    // flyzone, being inside an obstacle and flying outside of the flight envelope
+    pub fn validate_location(&self, loc: &Location) -> Result<(), PathfinderError> {
+        if !loc.lat().is_finite() || !loc.lon().is_finite() || !loc.alt().is_finite() {
+            return Err(PathfinderError::InvalidCoordinates);
+        }
+        let point = Point::from((loc, &self.origin));
+        let inside = self.flyzones.iter().any(|flyzone| {
+            let zone: Vec<Point> = flyzone
+                .iter()
+                .map(|location| Point::from((location, &self.origin)))
+                .collect();
+            point_in_polygon(&point, &zone)
+        });
+        if !inside {
+            return Err(PathfinderError::OutsideFlyzone);
+        }
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            let center = Point::from((&obstacle.location, &self.origin));
+            let distance = ((point.x - center.x).powi(2) + (point.y - center.y).powi(2)).sqrt();
+            if distance < obstacle.radius && loc.alt() < obstacle.height {
+                return Err(PathfinderError::InsideObstacle { index });
+            }
+        }
+        let (min, max) = self.altitude_limits;
+        if loc.alt() < min || loc.alt() > max {
+            return Err(PathfinderError::AltitudeOutOfRange {
+                altitude: loc.alt(),
+            });
+        }
+        Ok(())
+    }
+
+    // Whether each waypoint lies inside any of the flyzones, ignoring altitude
+    pub fn waypoints_within_flyzone(&self, waypoints: &[Location]) -> Vec<bool> {
+        waypoints
+            .iter()
+            .map(|waypoint| self.point_within_flyzone(&Point::from((waypoint, &self.origin))))
+            .collect()
+    }
+
+    // Whether a point lies inside any of the flyzones, ignoring altitude
+    pub(crate) fn point_within_flyzone(&self, point: &Point) -> bool {
+        self.flyzones.iter().any(|flyzone| {
+            let zone: Vec<Point> = flyzone
+                .iter()
+                .map(|location| Point::from((location, &self.origin)))
+                .collect();
+            winding_number(point, &zone) != 0
+        })
+    }
+
+    // Whether any two segments of the path that do not share a waypoint intersect
+    pub fn path_has_crossing(&self, waypoints: &[Location]) -> bool {
+        self.first_crossing(waypoints).is_some()
+    }
+
+    // First pair of non adjacent segments of the path that intersect in the xy plane
+    fn first_crossing(&self, waypoints: &[Location]) -> Option<(usize, usize)> {
+        let points: Vec<Point> = waypoints
+            .iter()
+            .map(|location| Point::from((location, &self.origin)))
+            .collect();
+        let segments: Vec<&[Point]> = points.windows(2).collect();
+        for (first, a) in segments.iter().enumerate() {
+            for (second, b) in segments.iter().enumerate().skip(first + 2) {
+                if intersect(&a[0], &a[1], &b[0], &b[1]) {
+                    return Some((first, second));
+                }
+            }
+        }
+        None
+    }
+
+    // Check every segment of a path against the flyzones, obstacles and flight envelope
+    pub fn path_feasibility_report(&self, waypoints: &[Location]) -> FeasibilityReport {
+        let (min, max) = self.altitude_limits;
+        let mut violations = Vec::new();
+        for (segment, leg) in waypoints.windows(2).enumerate() {
+            let a = Point::from((&leg[0], &self.origin));
+            let b = Point::from((&leg[1], &self.origin));
+
+            // a segment lying entirely outside every flyzone crosses no flyzone edge
+            let outside = !self.point_within_flyzone(&a) || !self.point_within_flyzone(&b);
+            if outside || matches!(self.valid_path(&a, &b), PathValidity::Invalid) {
+                violations.push(FeasibilityViolation::SegmentExitsFlyzone { segment });
+            }
+            for (obstacle, o) in self.obstacles.iter().enumerate() {
+                let low = a.z.min(b.z);
+                if low < o.height {
+                    if let (Some(_), Some(_)) = perpendicular_intersect(&self.origin, &a, &b, o) {
+                        violations.push(FeasibilityViolation::SegmentIntersectsObstacle {
+                            segment,
+                            obstacle,
+                        });
+                    }
+                }
+            }
+            if a.z.min(b.z) < min {
+                violations.push(FeasibilityViolation::AltitudeTooLow { segment, min });
+            }
+            if a.z.max(b.z) > max {
+                violations.push(FeasibilityViolation::AltitudeTooHigh { segment, max });
+            }
+            let angle = (b.z - a.z).abs().atan2(a.distance(&b));
+            if angle > MAX_FLIGHT_ANGLE {
+                violations.push(FeasibilityViolation::SegmentTooSteep { segment, angle });
+            }
+        }
+        if let Some((first, second)) = self.first_crossing(waypoints) {
+            violations.push(FeasibilityViolation::PathCrossesItself { first, second });
+        }
+        FeasibilityReport {
+            valid: violations.is_empty(),
+            violations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::graph::util::test_helpers::*;
+    use super::*;
+
+    #[test]
+    fn feasibility_report_test() {
+        let flyzones = square_flyzone(400f32);
+        let obstacles = vec![obstacle_from_meters(200f32, 100f32, 20f32, 50f32)];
+        let mut pathfinder = Tanstar::create(1f32, flyzones, obstacles);
+        pathfinder.set_altitude_limits(0f32, 121.92f32);
+        let location = |x, y, z| Location::from_meters(x, y, z, &origin());
+
+        let path = vec![
+            location(50f32, 50f32, 30f32),
+            location(50f32, 300f32, 30f32),
+            location(300f32, 300f32, 30f32),
+        ];
+        let report = pathfinder.path_feasibility_report(&path);
+        assert!(report.valid);
+        assert!(report.violations.is_empty());
+
+        let path = vec![
+            location(100f32, 100f32, 30f32),
+            // through the obstacle
+            location(300f32, 100f32, 30f32),
+            // climbs too steeply and too high
+            location(300f32, 150f32, 200f32),
+            // out of the flyzone
+            location(500f32, 150f32, 100f32),
+        ];
+        let report = pathfinder.path_feasibility_report(&path);
+        assert!(!report.valid);
+        assert_eq!(
+            report.violations[0],
+            FeasibilityViolation::SegmentIntersectsObstacle {
+                segment: 0,
+                obstacle: 0
+            }
+        );
+        assert_eq!(
+            report.violations[1],
+            FeasibilityViolation::AltitudeTooHigh {
+                segment: 1,
+                max: 121.92f32
+            }
+        );
+        match report.violations[2] {
+            FeasibilityViolation::SegmentTooSteep { segment, angle } => {
+                assert_eq!(segment, 1);
+                assert!((angle - (170f32).atan2(50f32)).abs() < 0.001);
+            }
+            _ => panic!(),
+        }
+        assert_eq!(
+            report.violations[3],
+            FeasibilityViolation::SegmentExitsFlyzone { segment: 2 }
+        );
+        assert_eq!(
+            report.violations[4],
+            FeasibilityViolation::AltitudeTooHigh {
+                segment: 2,
+                max: 121.92f32
+            }
+        );
+        assert_eq!(report.violations.len(), 5);
+
+        // every leg outside of the flyzone
+        let path = vec![
+            location(500f32, 50f32, 30f32),
+            location(500f32, 300f32, 30f32),
+            location(600f32, 300f32, 30f32),
+        ];
+        let report = pathfinder.path_feasibility_report(&path);
+        assert!(!report.valid);
+        assert_eq!(
+            report.violations,
+            vec![
+                FeasibilityViolation::SegmentExitsFlyzone { segment: 0 },
+                FeasibilityViolation::SegmentExitsFlyzone { segment: 1 },
+            ]
+        );
+
+        let path = vec![
+            location(50f32, 50f32, -5f32),
+            location(50f32, 300f32, 30f32),
+        ];
+        assert_eq!(
+            pathfinder.path_feasibility_report(&path).violations,
+            vec![FeasibilityViolation::AltitudeTooLow {
+                segment: 0,
+                min: 0f32
+            }]
+        );
+    }
+
+    #[test]
+    fn validate_location_test() {
+        let flyzones = square_flyzone(400f32);
+        let obstacles = vec![obstacle_from_meters(200f32, 100f32, 20f32, 50f32)];
+        let mut pathfinder = Tanstar::create(1f32, flyzones, obstacles);
+        let location = |x, y, z| Location::from_meters(x, y, z, &origin());
+        // altitudes are not limited by default
+        assert_eq!(
+            pathfinder.validate_location(&location(50f32, 50f32, 200f32)),
+            Ok(())
+        );
+        pathfinder.set_altitude_limits(0f32, 121.92f32);
+
+        assert_eq!(
+            pathfinder.validate_location(&location(50f32, 50f32, 30f32)),
+            Ok(())
+        );
+        // above the obstacle
+        assert_eq!(
+            pathfinder.validate_location(&location(200f32, 100f32, 60f32)),
+            Ok(())
+        );
+        assert_eq!(
+            pathfinder.validate_location(&Location::from_degrees(f64::NAN, 0f64, 30f32)),
+            Err(PathfinderError::InvalidCoordinates)
+        );
+        assert_eq!(
+            pathfinder.validate_location(&location(50f32, 50f32, f32::INFINITY)),
+            Err(PathfinderError::InvalidCoordinates)
+        );
+        assert_eq!(
+            pathfinder.validate_location(&location(500f32, 50f32, 30f32)),
+            Err(PathfinderError::OutsideFlyzone)
+        );
+        assert_eq!(
+            pathfinder.validate_location(&location(205f32, 95f32, 30f32)),
+            Err(PathfinderError::InsideObstacle { index: 0 })
+        );
+        assert_eq!(
+            pathfinder.validate_location(&location(50f32, 50f32, 200f32)),
+            Err(PathfinderError::AltitudeOutOfRange { altitude: 200f32 })
+        );
+        // first problem found is returned
+        assert_eq!(
+            pathfinder.validate_location(&location(500f32, 50f32, 200f32)),
+            Err(PathfinderError::OutsideFlyzone)
+        );
+
+        // invalid locations are rejected before searching
+        let start = location(50f32, 50f32, 30f32);
+        assert!(pathfinder
+            .adjust_path::<()>(start, location(50f32, 50f32, 200f32))
+            .is_none());
+        assert!(pathfinder
+            .adjust_path::<()>(location(500f32, 50f32, 30f32), start)
+            .is_none());
+    }
+
+    #[test]
+    fn waypoints_within_flyzone_test() {
+        // u shaped flyzone open to the north
+        let flyzones = vec![vec![
+            Location::from_meters(0f32, 0f32, 0f32, &origin()),
+            Location::from_meters(300f32, 0f32, 0f32, &origin()),
+            Location::from_meters(300f32, 300f32, 0f32, &origin()),
+            Location::from_meters(200f32, 300f32, 0f32, &origin()),
+            Location::from_meters(200f32, 100f32, 0f32, &origin()),
+            Location::from_meters(100f32, 100f32, 0f32, &origin()),
+            Location::from_meters(100f32, 300f32, 0f32, &origin()),
+            Location::from_meters(0f32, 300f32, 0f32, &origin()),
+        ]];
+        let pathfinder = Tanstar::create(1f32, flyzones, Vec::new());
+        let location = |x, y| Location::from_meters(x, y, 30f32, &origin());
+        let waypoints = [
+            location(50f32, 250f32),
+            location(150f32, 250f32),
+            location(250f32, 250f32),
+            location(150f32, 50f32),
+            location(350f32, 50f32),
+        ];
+        assert_eq!(
+            pathfinder.waypoints_within_flyzone(&waypoints),
+            vec![true, false, true, true, false]
+        );
+        assert!(pathfinder.waypoints_within_flyzone(&[]).is_empty());
+    }
+
+    #[test]
+    fn path_crossing_test() {
+        let flyzones = square_flyzone(400f32);
+        let pathfinder = Tanstar::create(1f32, flyzones, Vec::new());
+        let location = |x, y| Location::from_meters(x, y, 30f32, &origin());
+
+        let path = vec![
+            location(50f32, 50f32),
+            location(300f32, 50f32),
+            location(300f32, 300f32),
+        ];
+        assert!(!pathfinder.path_has_crossing(&path));
+        assert!(!pathfinder.path_has_crossing(&path[..1]));
+
+        // figure eight
+        let path = vec![
+            location(50f32, 50f32),
+            location(300f32, 300f32),
+            location(300f32, 50f32),
+            location(50f32, 300f32),
+        ];
+        assert!(pathfinder.path_has_crossing(&path));
+        let report = pathfinder.path_feasibility_report(&path);
+        assert!(!report.valid);
+        assert_eq!(
+            report.violations,
+            vec![FeasibilityViolation::PathCrossesItself {
+                first: 0,
+                second: 2
+            }]
+        );
+    }
+}
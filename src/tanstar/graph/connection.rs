@@ -18,4 +18,28 @@ impl Connection {
             threshold,
         }
     }
+
+    // Create the reverse of this connection, leading back to source
+    // distance and threshold are shared with the original connection
+    pub fn reciprocal(&self, source: &Rc<RefCell<Vertex>>) -> Self {
+        Self::new(source.clone(), self.distance, self.threshold)
+    }
+
+    // Check the connection from start against the current obstacles
+    // true if it passes through an obstacle taller than the threshold it was built with
+    pub fn crosses_any_obstacle(
+        &self,
+        start: &Point,
+        obstacles: &[Obstacle],
+        origin: &Location,
+    ) -> bool {
+        let end = self.neighbor.borrow().location;
+        obstacles.iter().any(|obstacle| {
+            if obstacle.height <= self.threshold {
+                return false;
+            }
+            let (entry, exit) = perpendicular_intersect(origin, start, &end, obstacle);
+            entry.is_some() && exit.is_some()
+        })
+    }
 }
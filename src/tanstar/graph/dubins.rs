@@ -0,0 +1,280 @@
+// dubins.rs
+// Contains the Dubins curves, shortest paths between two poses for a fixed turning radius
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Turn {
+    Left,
+    Straight,
+    Right,
+}
+
+// The six kinds of Dubins paths, each made of three segments
+const WORDS: [[Turn; 3]; 6] = [
+    [Turn::Left, Turn::Straight, Turn::Left],
+    [Turn::Right, Turn::Straight, Turn::Right],
+    [Turn::Left, Turn::Straight, Turn::Right],
+    [Turn::Right, Turn::Straight, Turn::Left],
+    [Turn::Right, Turn::Left, Turn::Right],
+    [Turn::Left, Turn::Right, Turn::Left],
+];
+
+fn mod2pi(angle: f32) -> f32 {
+    add_angles(angle, 0f32)
+}
+
+// Segment lengths of a word, in multiples of the radius, if the word can join the poses
+// alpha and beta are the start and end headings relative to the line between the poses, and d
+// is the distance between the poses in multiples of the radius
+fn word_lengths(word: usize, alpha: f32, beta: f32, d: f32) -> Option<[f32; 3]> {
+    let (sa, sb, ca, cb) = (alpha.sin(), beta.sin(), alpha.cos(), beta.cos());
+    let c_ab = (alpha - beta).cos();
+    match word {
+        // LSL
+        0 => {
+            let p2 = 2f32 + d * d - 2f32 * c_ab + 2f32 * d * (sa - sb);
+            if p2 < 0f32 {
+                return None;
+            }
+            let tmp = (cb - ca).atan2(d + sa - sb);
+            Some([mod2pi(tmp - alpha), p2.sqrt(), mod2pi(beta - tmp)])
+        }
+        // RSR
+        1 => {
+            let p2 = 2f32 + d * d - 2f32 * c_ab + 2f32 * d * (sb - sa);
+            if p2 < 0f32 {
+                return None;
+            }
+            let tmp = (ca - cb).atan2(d - sa + sb);
+            Some([mod2pi(alpha - tmp), p2.sqrt(), mod2pi(tmp - beta)])
+        }
+        // LSR
+        2 => {
+            let p2 = -2f32 + d * d + 2f32 * c_ab + 2f32 * d * (sa + sb);
+            if p2 < 0f32 {
+                return None;
+            }
+            let p = p2.sqrt();
+            let tmp = (-ca - cb).atan2(d + sa + sb) - (-2f32).atan2(p);
+            Some([mod2pi(tmp - alpha), p, mod2pi(tmp - mod2pi(beta))])
+        }
+        // RSL
+        3 => {
+            let p2 = d * d - 2f32 + 2f32 * c_ab - 2f32 * d * (sa + sb);
+            if p2 < 0f32 {
+                return None;
+            }
+            let p = p2.sqrt();
+            let tmp = (ca + cb).atan2(d - sa - sb) - 2f32.atan2(p);
+            Some([mod2pi(alpha - tmp), p, mod2pi(beta - tmp)])
+        }
+        // RLR
+        4 => {
+            let tmp = (6f32 - d * d + 2f32 * c_ab + 2f32 * d * (sa - sb)) / 8f32;
+            if tmp.abs() > 1f32 {
+                return None;
+            }
+            let p = mod2pi(2f32 * PI - tmp.acos());
+            let t = mod2pi(alpha - (ca - cb).atan2(d - sa + sb) + p / 2f32);
+            Some([t, p, mod2pi(alpha - beta - t + p)])
+        }
+        // LRL
+        _ => {
+            let tmp = (6f32 - d * d + 2f32 * c_ab + 2f32 * d * (sb - sa)) / 8f32;
+            if tmp.abs() > 1f32 {
+                return None;
+            }
+            let p = mod2pi(2f32 * PI - tmp.acos());
+            let t = mod2pi(-alpha - (ca - cb).atan2(d + sa - sb) + p / 2f32);
+            Some([t, p, mod2pi(mod2pi(beta) - alpha - t + p)])
+        }
+    }
+}
+
+// Shortest word joining the poses, with its segment lengths in multiples of the radius
+fn shortest_word(
+    start: &Point,
+    start_angle: f32,
+    end: &Point,
+    end_angle: f32,
+    radius: f32,
+) -> ([Turn; 3], [f32; 3]) {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let d = (dx * dx + dy * dy).sqrt() / radius;
+    let theta = if d == 0f32 { 0f32 } else { dy.atan2(dx) };
+    let alpha = mod2pi(start_angle - theta);
+    let beta = mod2pi(end_angle - theta);
+    let mut best = (WORDS[0], [f32::INFINITY; 3]);
+    for (i, word) in WORDS.iter().enumerate() {
+        if let Some(lengths) = word_lengths(i, alpha, beta, d) {
+            if lengths.iter().sum::<f32>() < best.1.iter().sum::<f32>() {
+                best = (*word, lengths);
+            }
+        }
+    }
+    best
+}
+
+// Pose reached from (x, y, angle) after flying length along a segment of the given turn
+fn advance(pose: (f32, f32, f32), turn: Turn, length: f32, radius: f32) -> (f32, f32, f32) {
+    let (x, y, angle) = pose;
+    match turn {
+        Turn::Straight => (x + length * angle.cos(), y + length * angle.sin(), angle),
+        Turn::Left => {
+            let next = angle + length / radius;
+            (
+                x + radius * (next.sin() - angle.sin()),
+                y - radius * (next.cos() - angle.cos()),
+                next,
+            )
+        }
+        Turn::Right => {
+            let next = angle - length / radius;
+            (
+                x - radius * (next.sin() - angle.sin()),
+                y + radius * (next.cos() - angle.cos()),
+                next,
+            )
+        }
+    }
+}
+
+// Length of the shortest Dubins path between two poses
+// angles are headings in radians counter-clockwise from the x axis
+pub fn dubins_length(
+    start: &Point,
+    start_angle: f32,
+    end: &Point,
+    end_angle: f32,
+    radius: f32,
+) -> f32 {
+    let (_, lengths) = shortest_word(start, start_angle, end, end_angle, radius);
+    lengths.iter().sum::<f32>() * radius
+}
+
+// Points along the shortest Dubins path between two poses, at most step apart
+// angles are headings in radians counter-clockwise from the x axis. The first and last points
+// are start and end, and z changes linearly with the distance flown
+pub fn dubins_path(
+    start: &Point,
+    start_angle: f32,
+    end: &Point,
+    end_angle: f32,
+    radius: f32,
+    step: f32,
+) -> Vec<Point> {
+    assert!(radius > 0f32 && step > 0f32);
+    let (word, lengths) = shortest_word(start, start_angle, end, end_angle, radius);
+    let total = lengths.iter().sum::<f32>() * radius;
+    let mut points = vec![*start];
+    let mut pose = (start.x, start.y, start_angle);
+    let mut flown = 0f32;
+    for (&turn, &length) in word.iter().zip(lengths.iter()) {
+        let length = length * radius;
+        let samples = (length / step).ceil().max(1f32) as usize;
+        for i in 1..=samples {
+            let (x, y, _) = advance(pose, turn, length * i as f32 / samples as f32, radius);
+            let z = if total > 0f32 {
+                start.z + (end.z - start.z) * (flown + length * i as f32 / samples as f32) / total
+            } else {
+                end.z
+            };
+            points.push(Point::new(x, y, z));
+        }
+        pose = advance(pose, turn, length, radius);
+        flown += length;
+    }
+    // land exactly on end despite rounding
+    let last = points.len() - 1;
+    points[last] = *end;
+    points
+}
+
+#[cfg(test)]
+mod test {
+    extern crate rand;
+
+    use self::rand::{thread_rng, Rng};
+    use super::*;
+
+    // end pose of flying the word from the start pose, in meters and radians
+    fn fly(
+        start: (f32, f32, f32),
+        word: &[Turn; 3],
+        lengths: &[f32; 3],
+        radius: f32,
+    ) -> (f32, f32, f32) {
+        word.iter()
+            .zip(lengths.iter())
+            .fold(start, |pose, (&turn, &length)| {
+                advance(pose, turn, length * radius, radius)
+            })
+    }
+
+    #[test]
+    fn dubins_words_random() {
+        let mut rng = thread_rng();
+        for _ in 0..500 {
+            let radius = rng.gen_range(5f32, 30f32);
+            let start = (
+                rng.gen_range(-100f32, 100f32),
+                rng.gen_range(-100f32, 100f32),
+            );
+            let end = (
+                rng.gen_range(-100f32, 100f32),
+                rng.gen_range(-100f32, 100f32),
+            );
+            let (a, b) = (
+                rng.gen_range(0f32, 2f32 * PI),
+                rng.gen_range(0f32, 2f32 * PI),
+            );
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let theta = dy.atan2(dx);
+            let d = (dx * dx + dy * dy).sqrt() / radius;
+            let mut found = false;
+            for (i, word) in WORDS.iter().enumerate() {
+                let lengths = match word_lengths(i, mod2pi(a - theta), mod2pi(b - theta), d) {
+                    Some(lengths) => lengths,
+                    None => continue,
+                };
+                found = true;
+                let (x, y, angle) = fly((start.0, start.1, a), word, &lengths, radius);
+                assert!((x - end.0).abs() < 0.05, "{:?} misses x", word);
+                assert!((y - end.1).abs() < 0.05, "{:?} misses y", word);
+                assert!(angle_difference(mod2pi(angle), b).abs() < 1e-3);
+            }
+            assert!(found);
+        }
+    }
+
+    #[test]
+    fn dubins_path_test() {
+        let start = Point::new(0f32, 0f32, 10f32);
+        let end = Point::new(100f32, 0f32, 30f32);
+        // same heading along the line between the poses is a straight line
+        assert!((dubins_length(&start, 0f32, &end, 0f32, 10f32) - 100f32).abs() < 1e-3);
+        // turning around needs at least a half circle
+        let back = dubins_length(&start, 0f32, &end, PI, 10f32);
+        assert!(back >= 100f32 + PI * 10f32 - 1e-3);
+
+        let points = dubins_path(&start, PI / 2f32, &end, -PI / 2f32, 10f32, 2f32);
+        assert_eq!(points[0].distance3d(&start), 0f32);
+        assert_eq!(points[points.len() - 1].distance3d(&end), 0f32);
+        let mut previous = points[1].z;
+        for pair in points.windows(2) {
+            assert!(pair[0].distance(&pair[1]) <= 2f32 + 1e-3);
+            assert!(pair[1].z >= previous - 1e-3);
+            previous = pair[1].z;
+        }
+        // heading never changes faster than the turning radius allows
+        for triple in points.windows(3) {
+            let first = (triple[1].y - triple[0].y).atan2(triple[1].x - triple[0].x);
+            let second = (triple[2].y - triple[1].y).atan2(triple[2].x - triple[1].x);
+            let step = triple[0]
+                .distance(&triple[1])
+                .max(triple[1].distance(&triple[2]));
+            assert!(angle_difference(first, second).abs() <= step / 10f32 + 1e-2);
+        }
+    }
+}
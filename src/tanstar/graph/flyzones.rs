@@ -66,6 +66,146 @@ impl Tanstar {
         Location::from_radians(min_lat, lon, 0f32)
     }
 
+    // Convert a flyzone into points with respect to the origin
+    fn flyzone_points(&self, zone_index: usize) -> Result<Vec<Point>, PathfinderError> {
+        match self.flyzones.get(zone_index) {
+            Some(flyzone) => Ok(flyzone
+                .iter()
+                .map(|location| Point::from((location, &self.origin)))
+                .collect()),
+            None => Err(PathfinderError::InvalidFlyzoneIndex { index: zone_index }),
+        }
+    }
+
+    // Total length of a flyzone boundary, including the edge closing the polygon
+    pub fn flyzone_perimeter(&self, zone_index: usize) -> Result<f32, PathfinderError> {
+        let points = self.flyzone_points(zone_index)?;
+        let size = points.len();
+        let mut perimeter = 0f32;
+        for i in 0..size {
+            perimeter += points[i].distance(&points[(i + 1) % size]);
+        }
+        Ok(perimeter)
+    }
+
+    // Corners of the box holding every flyzone and the full cylinder of every obstacle, the first
+    // with the minimum latitude, longitude and altitude and the second with the maximums
+    pub fn bounding_box(&self) -> (Location, Location) {
+        let mut locations: Vec<Location> = self.flyzones.iter().flatten().cloned().collect();
+        for obstacle in &self.obstacles {
+            let center = Point::from((&obstacle.location, &self.origin));
+            let r = obstacle.radius;
+            for &(dx, dy) in &[(r, 0f32), (-r, 0f32), (0f32, r), (0f32, -r)] {
+                let edge = Point::new(center.x + dx, center.y + dy, 0f32);
+                locations.push(Location::from((&edge, &self.origin)));
+            }
+            let top = Point::new(center.x, center.y, obstacle.height);
+            locations.push(Location::from((&top, &self.origin)));
+        }
+        let first = *locations.first().expect("Require at least one flyzone");
+        let (mut min, mut max) = (
+            (first.lat(), first.lon(), first.alt()),
+            (first.lat(), first.lon(), first.alt()),
+        );
+        for location in &locations {
+            min = (
+                min.0.min(location.lat()),
+                min.1.min(location.lon()),
+                min.2.min(location.alt()),
+            );
+            max = (
+                max.0.max(location.lat()),
+                max.1.max(location.lon()),
+                max.2.max(location.alt()),
+            );
+        }
+        (
+            Location::from_radians(min.0, min.1, min.2),
+            Location::from_radians(max.0, max.1, max.2),
+        )
+    }
+
+    // Closest point to loc on the boundary of any flyzone, keeping the altitude of loc
+    // works the same for locations inside and outside of the flyzones
+    pub fn find_nearest_flyzone_boundary_point(&self, loc: &Location) -> Location {
+        let point = Point::from((loc, &self.origin));
+        let mut nearest = None;
+        let mut min_distance = f32::MAX;
+        for flyzone in &self.flyzones {
+            let zone: Vec<Point> = flyzone
+                .iter()
+                .map(|location| Point::from((location, &self.origin)))
+                .collect();
+            for (i, a) in zone.iter().enumerate() {
+                let b = &zone[(i + 1) % zone.len()];
+                let candidate = closest_point_on_segment(&point, a, b);
+                let distance = point.distance(&candidate);
+                if distance < min_distance {
+                    min_distance = distance;
+                    nearest = Some(candidate);
+                }
+            }
+        }
+        let nearest = nearest.expect("Require at least one flyzone");
+        Location::from((&nearest, &self.origin))
+    }
+
+    // Area weighted centroid of a flyzone with respect to the origin
+    // falls back to the average of the vertices if the flyzone has no area
+    pub fn compute_flyzone_centroid(&self, zone_index: usize) -> Result<Point, PathfinderError> {
+        let points = self.flyzone_points(zone_index)?;
+        let size = points.len();
+        // centroid sums are scaled by twice the signed area of each triangle fanned from the origin
+        let center = Point::new(0f32, 0f32, 0f32);
+        let (mut cx, mut cy) = (0f32, 0f32);
+        for i in 0..size {
+            let a = points[i];
+            let b = points[(i + 1) % size];
+            let cross = 2f32 * triangle_area(&center, &a, &b);
+            cx += (a.x + b.x) * cross;
+            cy += (a.y + b.y) * cross;
+        }
+        let area = 2f32 * polygon_area(&points);
+        if area == 0f32 {
+            let n = size as f32;
+            let x = points.iter().map(|p| p.x).sum::<f32>() / n;
+            let y = points.iter().map(|p| p.y).sum::<f32>() / n;
+            return Ok(Point::new(x, y, 0f32));
+        }
+        Ok(Point::new(cx / (3f32 * area), cy / (3f32 * area), 0f32))
+    }
+
+    // Merge all flyzones into a single boundary
+    // every flyzone must overlap the union of the others, otherwise the union is ambiguous
+    pub fn flyzone_union(&self) -> Result<Vec<Location>, PathfinderError> {
+        let mut remaining = Vec::new();
+        for i in 0..self.flyzones.len() {
+            remaining.push(self.flyzone_points(i)?);
+        }
+        if remaining.is_empty() {
+            return Err(PathfinderError::InvalidFlyzoneIndex { index: 0 });
+        }
+        let mut union = remaining.remove(0);
+        while !remaining.is_empty() {
+            let merged = remaining
+                .iter()
+                .enumerate()
+                .filter_map(|(i, zone)| polygon_union(&union, zone).map(|merged| (i, merged)))
+                .next();
+            match merged {
+                Some((i, merged)) => {
+                    remaining.remove(i);
+                    union = merged;
+                }
+                None => return Err(PathfinderError::DisjointFlyzones),
+            }
+        }
+        Ok(union
+            .iter()
+            .map(|point| Location::from((point, &self.origin)))
+            .collect())
+    }
+
     // Convert flyzone into virtual nodes
     pub fn virtualize_flyzone(&mut self, index: usize) {
         let flyzone = &self.flyzones[index];
@@ -75,8 +215,11 @@ impl Tanstar {
             let point = Point::from((location, &self.origin));
             flyzone_points.push(point);
         }
+        // keep virtual nodes clear of the boundary by the buffer
+        let flyzone_points =
+            offset_polygon(&flyzone_points, self.config.buffer_size).unwrap_or(flyzone_points);
         // determine flyzone directions
-        let size = flyzone.len() as isize - 1;
+        let size = flyzone_points.len() as isize - 1;
         let (clockwise, _) = vertex_direction(&flyzone_points);
         let (direction, mut iter): (isize, isize) = if clockwise { (1, 0) } else { (-1, size) };
 
@@ -134,6 +277,42 @@ impl Tanstar {
         }
     }
 
+    // Convert the convex corners of a keep out zone into virtual nodes
+    // each node sits outside the corner, tangent to both edges, so paths can turn around it
+    pub fn virtualize_keep_out_zone(&mut self, index: usize) {
+        let points: Vec<Point> = self.keep_out_zones[index]
+            .iter()
+            .map(|location| Point::from((location, &self.origin)))
+            .collect();
+        let size = points.len();
+        let winding = polygon_area(&points);
+        for i in 0..size {
+            let a = points[(i + size - 1) % size];
+            let vertex = points[i];
+            let b = points[(i + 1) % size];
+            // concave and straight corners can not be turned around from outside
+            if triangle_area(&a, &vertex, &b) * winding <= 0f32 {
+                continue;
+            }
+            let mag_a = a.distance(&vertex);
+            let mag_b = b.distance(&vertex);
+            let unit_a = ((a.x - vertex.x) / mag_a, (a.y - vertex.y) / mag_a);
+            let unit_b = ((b.x - vertex.x) / mag_b, (b.y - vertex.y) / mag_b);
+            // bisector pointing away from the inside of the zone
+            let bisect = (-(unit_a.0 + unit_b.0), -(unit_a.1 + unit_b.1));
+            let mag_bisection = ((bisect.0).powi(2) + (bisect.1).powi(2)).sqrt();
+            let theta = (unit_a.0 * unit_b.0 + unit_a.1 * unit_b.1).acos();
+            let d = self.config.turning_radius / (theta / 2f32).sin();
+            let center = Point::new(
+                d * bisect.0 / mag_bisection + vertex.x,
+                d * bisect.1 / mag_bisection + vertex.y,
+                0f32,
+            );
+            let virt_ob = Node::new(center, self.config.turning_radius, 0f32);
+            self.nodes.push(Rc::new(RefCell::new(virt_ob)));
+        }
+    }
+
     // determines vertices of node and flyzone intersection
     #[allow(clippy::many_single_char_names)]
     pub fn insert_flyzone_sentinel(&mut self, node: &mut Node) {
@@ -167,11 +346,13 @@ impl Tanstar {
                     &mut self.num_vertices,
                     node,
                     a,
+                    node.radius,
                 )));
                 let vertex_b = Rc::new(RefCell::new(Vertex::new_sentinel(
                     &mut self.num_vertices,
                     node,
                     b,
+                    node.radius,
                 )));
 
                 let a = Location::from((&vertex_a.borrow().location, &self.origin));
@@ -203,4 +384,51 @@ mod test {
         assert_eq!(vertex_direction(&anticlockwise_flyzone), (false, false));
         assert_eq!(vertex_direction(&line_flyzone), (false, true));
     }
+
+    #[test]
+    fn flyzone_union_test() {
+        let origin = Location::from_radians(0f64, 0f64, 0f32);
+        let rectangle = |x1, y1, x2, y2| {
+            vec![
+                Location::from_meters(x1, y1, 0f32, &origin),
+                Location::from_meters(x1, y2, 0f32, &origin),
+                Location::from_meters(x2, y2, 0f32, &origin),
+                Location::from_meters(x2, y1, 0f32, &origin),
+            ]
+        };
+        // two rectangles overlapping in a corner form an eight sided polygon
+        let flyzones = vec![
+            rectangle(0f32, 0f32, 200f32, 100f32),
+            rectangle(100f32, 50f32, 300f32, 250f32),
+        ];
+        let pathfinder = Tanstar::create(1f32, flyzones, vec![]);
+        let union: Vec<Point> = pathfinder
+            .flyzone_union()
+            .unwrap()
+            .iter()
+            .map(|location| Point::from((location, &pathfinder.origin)))
+            .collect();
+        assert_eq!(union.len(), 8);
+        // 20000 + 40000 - 5000 overlap
+        assert!((polygon_area(&union) - 55000f32).abs() < 1f32);
+
+        // rectangle inside another
+        let flyzones = vec![
+            rectangle(0f32, 0f32, 200f32, 200f32),
+            rectangle(50f32, 50f32, 100f32, 100f32),
+        ];
+        let pathfinder = Tanstar::create(1f32, flyzones, vec![]);
+        let union = pathfinder.flyzone_union().unwrap();
+        assert_eq!(union.len(), 4);
+
+        let flyzones = vec![
+            rectangle(0f32, 0f32, 100f32, 100f32),
+            rectangle(200f32, 200f32, 300f32, 300f32),
+        ];
+        let pathfinder = Tanstar::create(1f32, flyzones, vec![]);
+        assert_eq!(
+            pathfinder.flyzone_union(),
+            Err(PathfinderError::DisjointFlyzones)
+        );
+    }
 }
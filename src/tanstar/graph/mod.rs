@@ -1,7 +1,9 @@
 // Visibility related code for modularity
 use super::*;
 
+mod dubins;
 mod flyzones;
+mod rrt;
 #[cfg(test)]
 mod test;
 
@@ -12,12 +14,14 @@ pub mod util;
 pub mod vertex;
 
 pub use self::connection::Connection;
+pub use self::dubins::dubins_path;
 pub use self::node::Node;
 pub use self::point::Point;
 pub use self::util::*;
 pub use self::vertex::Vertex;
 
 use obj::{Location, Obstacle};
+use std::cmp::Ordering;
 
 pub enum PathValidity {
     Valid,
@@ -37,83 +41,813 @@ impl From<PathValidity> for bool {
 type Path = Vec<(f32, f32, f32, f32)>;
 type Sentinel = Vec<(f32, f32)>;
 
+// Largest graph for which diameter uses Floyd-Warshall rather than repeated Dijkstra
+const FLOYD_WARSHALL_LIMIT: usize = 64;
+// Slack in meters for segments touching the cone of an obstacle
+const CONE_TOLERANCE: f32 = 0.01;
+// Number of sides of the polygons approximating obstacle shadows
+pub const SHADOW_SIDES: usize = 32;
+
 impl Tanstar {
+    // Insert edge from node i to node j and its reciprocal from node j back to node i
+    // the reciprocal leaves from the reverse side of both nodes
     fn insert_edge(
         &mut self,
         i: usize,
         j: usize,
         (alpha, beta, distance, threshold): (f32, f32, f32, f32),
     ) {
+        let (merge_i, merge_j) = (self.merge_threshold(i), self.merge_threshold(j));
+        let (r_i, r_j) = self.pair_radii(&self.nodes[i].borrow(), &self.nodes[j].borrow());
         // Insert edge from u -> v
-        let v = self.nodes[j].borrow().get_vertex(
-            &mut self.num_vertices,
-            beta,
-            self.config.vertex_merge_threshold,
-        );
+        let v = self.nodes[j]
+            .borrow()
+            .get_vertex_at(&mut self.num_vertices, beta, r_j, merge_j);
         let edge = Connection::new(v.clone(), distance, threshold);
-        let u = self.nodes[i].borrow().get_vertex(
+        let u = self.nodes[i]
+            .borrow()
+            .get_vertex_at(&mut self.num_vertices, alpha, r_i, merge_i);
+
+        // Insert reciprocal edge from v' -> u'
+        let u_r = self.nodes[i].borrow().get_vertex_at(
             &mut self.num_vertices,
-            alpha,
-            self.config.vertex_merge_threshold,
+            reverse_polarity(alpha),
+            r_i,
+            merge_i,
         );
+        let reciprocal = edge.reciprocal(&u_r);
         u.borrow_mut().connection.push(edge);
+        let v_r = self.nodes[j].borrow().get_vertex_at(
+            &mut self.num_vertices,
+            reverse_polarity(beta),
+            r_j,
+            merge_j,
+        );
+        v_r.borrow_mut().connection.push(reciprocal);
+    }
+
+    // Arc length on node within which a new vertex is merged into an existing one
+    fn merge_threshold(&self, node: usize) -> f32 {
+        let separation = self.min_vertex_separation * self.nodes[node].borrow().radius;
+        self.config.vertex_merge_threshold.max(separation)
     }
 
     pub fn build_graph(&mut self) {
         self.populate_nodes();
+        self.dirty_nodes.clear();
+        self.obstacle_sentinels.clear();
         for i in 0..self.nodes.len() {
             let node = self.nodes[i].clone();
             self.insert_flyzone_sentinel(&mut node.borrow_mut());
 
             for j in i + 1..self.nodes.len() {
-                let (paths, obs_sentinels) =
-                    self.find_path(&self.nodes[i].borrow(), &self.nodes[j].borrow());
-                println!("[{} {}]: path count -> {}", i, j, paths.len());
-
-                // Inserting edge
-                for mut path in paths {
-                    // Edge from i to j
-                    self.insert_edge(i, j, path);
-                    // Reciprocal edge from j to i
-                    let (beta, alpha) = (reverse_polarity(path.0), reverse_polarity(path.1));
-                    path.0 = alpha;
-                    path.1 = beta;
-                    self.insert_edge(j, i, path);
+                self.connect_nodes(i, j);
+            }
+        }
+
+        // output_graph(&self);
+    }
+
+    // Upper bound on the (nodes, edges) build_graph would generate, without building the graph
+    // every flyzone vertex may become a virtual node and every pair of nodes may be connected
+    // by up to 4 tangents, each inserted in both directions
+    pub fn estimate_graph_size(&self) -> (usize, usize) {
+        let mut nodes = self.obstacles.len();
+        if self.config.virtualize_flyzone {
+            nodes += self
+                .flyzones
+                .iter()
+                .map(|flyzone| flyzone.len())
+                .sum::<usize>();
+        }
+        let pairs = nodes * nodes.saturating_sub(1) / 2;
+        (nodes, pairs * 4 * 2)
+    }
+
+    // Insert edges and sentinels between node i and node j
+    fn connect_nodes(&mut self, i: usize, j: usize) {
+        let (paths, obs_sentinels) =
+            self.find_path(&self.nodes[i].borrow(), &self.nodes[j].borrow());
+        println!("[{} {}]: path count -> {}", i, j, paths.len());
+
+        // Inserting edge
+        for path in paths {
+            self.insert_edge(i, j, path);
+        }
+
+        // Inserting sentinels
+        if let Some(obs_sentinels) = obs_sentinels {
+            println!("inserting sentinels");
+            let mut inserted = LinkedList::new();
+            let (r_i, r_j) = self.pair_radii(&self.nodes[i].borrow(), &self.nodes[j].borrow());
+            for (alpha_s, beta_s) in obs_sentinels {
+                let a = Vertex::new_sentinel(
+                    &mut self.num_vertices,
+                    &self.nodes[i].borrow(),
+                    alpha_s,
+                    r_i,
+                );
+                let b = Vertex::new_sentinel(
+                    &mut self.num_vertices,
+                    &self.nodes[j].borrow(),
+                    beta_s,
+                    r_j,
+                );
+                let s_a = Rc::new(RefCell::new(a));
+                let s_b = Rc::new(RefCell::new(b));
+                self.nodes[i].borrow_mut().insert_vertex(s_a.clone());
+                self.nodes[j].borrow_mut().insert_vertex(s_b.clone());
+                inserted.push_back(s_a);
+                inserted.push_back(s_b);
+            }
+            self.obstacle_sentinels.insert((i, j), inserted);
+        }
+    }
+
+    // Move an obstacle to a new location given in degrees
+    // Edges of the obstacle node are recomputed the next time a path is requested
+    pub fn set_obstacle_position(
+        &mut self,
+        index: usize,
+        lat: f64,
+        lon: f64,
+        alt: f32,
+    ) -> Result<(), PathfinderError> {
+        if index >= self.obstacles.len() {
+            return Err(PathfinderError::InvalidObstacleIndex { index });
+        }
+        let location = Location::from_degrees(lat, lon, alt);
+        self.obstacles[index].location = location;
+        if index < self.nodes.len() {
+            self.nodes[index].borrow_mut().origin = Point::from((&location, &self.origin));
+            self.dirty_nodes.insert(index);
+        }
+        Ok(())
+    }
+
+    // Let paths fly through an obstacle as if it were not there, or restore it
+    // the graph is not rebuilt, edges already in the graph keep the height they were built with
+    pub fn set_obstacle_passable(
+        &mut self,
+        index: usize,
+        passable: bool,
+    ) -> Result<(), PathfinderError> {
+        if index >= self.obstacles.len() {
+            return Err(PathfinderError::InvalidObstacleIndex { index });
+        }
+        if passable {
+            self.passable_obstacles.insert(index);
+        } else {
+            self.passable_obstacles.remove(&index);
+        }
+        Ok(())
+    }
+
+    // Make obstacle index move at a constant velocity in m/s towards (east, north, up)
+    // a zero velocity makes the obstacle static again. Edges of the graph ignore moving
+    // obstacles, which are instead checked at the time each edge is reached during the search
+    pub fn set_obstacle_dynamic(
+        &mut self,
+        index: usize,
+        velocity_m_s: (f32, f32, f32),
+    ) -> Result<(), PathfinderError> {
+        if index >= self.obstacles.len() {
+            return Err(PathfinderError::InvalidObstacleIndex { index });
+        }
+        if velocity_m_s == (0f32, 0f32, 0f32) {
+            self.obstacle_velocities.remove(&index);
+        } else {
+            self.obstacle_velocities.insert(index, velocity_m_s);
+        }
+        Ok(())
+    }
+
+    // Gaussian uncertainty of obstacle index, the standard deviations of its position and radius
+    // in meters. The obstacle buffer grows by risk sigma times the combined deviation the next
+    // time the graph is built
+    pub fn set_obstacle_uncertainty(
+        &mut self,
+        index: usize,
+        pos_std_m: f32,
+        radius_std_m: f32,
+    ) -> Result<(), PathfinderError> {
+        if index >= self.obstacles.len() {
+            return Err(PathfinderError::InvalidObstacleIndex { index });
+        }
+        let valid = |std: f32| std.is_finite() && std >= 0f32;
+        if !valid(pos_std_m) || !valid(radius_std_m) {
+            return Err(PathfinderError::InvalidUncertainty);
+        }
+        if pos_std_m == 0f32 && radius_std_m == 0f32 {
+            self.obstacle_uncertainty.remove(&index);
+        } else {
+            self.obstacle_uncertainty
+                .insert(index, (pos_std_m, radius_std_m));
+        }
+        Ok(())
+    }
+
+    // Buffer around obstacle index, inflated by its uncertainty
+    fn obstacle_buffer(&self, index: usize) -> f32 {
+        let inflation = match self.obstacle_uncertainty.get(&index) {
+            Some(&(pos_std, radius_std)) => self.risk_sigma * pos_std.hypot(radius_std),
+            None => 0f32,
+        };
+        self.config.buffer_size + inflation
+    }
+
+    // Location of obstacle index dt_s seconds from now
+    pub fn predict_obstacle_position(&self, index: usize, dt_s: f32) -> Location {
+        let obstacle = &self.obstacles[index];
+        let (vx, vy, vz) = self
+            .obstacle_velocities
+            .get(&index)
+            .cloned()
+            .unwrap_or((0f32, 0f32, 0f32));
+        let center = Point::from((&obstacle.location, &self.origin));
+        let moved = Point::new(
+            center.x + vx * dt_s,
+            center.y + vy * dt_s,
+            center.z + vz * dt_s,
+        );
+        Location::from((&moved, &self.origin))
+    }
+
+    // Add an obstacle that blocks the edges between node a and node b
+    // only the edges between a and b are removed, and the new obstacle is connected to a and b
+    // alone. Virtual nodes after the obstacles are shifted up by one index
+    pub fn insert_obstacle_between(
+        &mut self,
+        a: usize,
+        b: usize,
+        obstacle: Obstacle,
+    ) -> Result<(), PathfinderError> {
+        for &index in &[a, b] {
+            if index >= self.nodes.len() {
+                return Err(PathfinderError::InvalidNodeIndex { index });
+            }
+        }
+        // node index of the new obstacle, which goes right after the existing obstacles
+        let new = self.obstacles.len();
+        let shift = |node: usize| if node >= new { node + 1 } else { node };
+        let (a, b) = (shift(a), shift(b));
+        self.obstacle_sentinels = self
+            .obstacle_sentinels
+            .drain()
+            .map(|((i, j), sentinels)| ((shift(i), shift(j)), sentinels))
+            .collect();
+        self.dirty_nodes = self.dirty_nodes.drain().map(shift).collect();
+        self.forbidden_nodes = self.forbidden_nodes.drain().map(shift).collect();
+        self.forbidden_edges = self
+            .forbidden_edges
+            .drain()
+            .map(|(i, j)| (shift(i), shift(j)))
+            .collect();
+
+        let mut node = Node::from((&obstacle, &self.origin, self.config.buffer_size));
+        self.insert_flyzone_sentinel(&mut node);
+        self.obstacles.push(obstacle);
+        self.nodes.insert(new, Rc::new(RefCell::new(node)));
+
+        if a != b {
+            self.disconnect_pair(a, b);
+        }
+        self.connect_nodes(a.min(new), a.max(new));
+        if a != b {
+            self.connect_nodes(b.min(new), b.max(new));
+        }
+        Ok(())
+    }
+
+    // [x, y] of each obstacle center in meters from the origin, in obstacle order
+    pub fn obstacle_centroids(&self) -> Vec<[f32; 2]> {
+        self.obstacles
+            .iter()
+            .map(|obstacle| {
+                let center = Point::from((&obstacle.location, &self.origin));
+                [center.x, center.y]
+            })
+            .collect()
+    }
+
+    // Label of each obstacle, matching the order of obstacle_centroids
+    pub fn obstacle_labels(&self) -> Vec<String> {
+        (0..self.obstacles.len())
+            .map(|i| format!("obs_{}", i))
+            .collect()
+    }
+
+    // Indices of obstacles whose boundary comes within radius meters of center
+    // linear scan over every obstacle, distances are measured in the xy plane
+    pub fn obstacles_within_radius(&self, center: &Location, radius: f32) -> Vec<usize> {
+        let center = Point::from((center, &self.origin));
+        self.obstacles
+            .iter()
+            .enumerate()
+            .filter(|(_, obstacle)| {
+                let origin = Point::from((&obstacle.location, &self.origin));
+                origin.distance(&center) - obstacle.radius < radius
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Shadow cast on the ground by an obstacle lit along light_direction, as a polygon of the
+    // base circle swept to the projection of the top circle. Light that does not point down
+    // casts no shadow on the ground, and an empty polygon is returned
+    pub fn obstacle_shadow(
+        &self,
+        obs_index: usize,
+        light_direction: (f32, f32, f32),
+    ) -> Result<Vec<Point>, PathfinderError> {
+        let obstacle = match self.obstacles.get(obs_index) {
+            Some(obstacle) => obstacle,
+            None => return Err(PathfinderError::InvalidObstacleIndex { index: obs_index }),
+        };
+        let (dx, dy, dz) = light_direction;
+        if dz >= 0f32 {
+            return Ok(Vec::new());
+        }
+        let node = Node::from((obstacle, &self.origin, 0f32));
+        // distance the top of the obstacle travels along the ground
+        let scale = -node.height / dz;
+        let mut outline = Vec::with_capacity(2 * SHADOW_SIDES);
+        for point in node.to_polygon(SHADOW_SIDES) {
+            outline.push(Point::new(point.x, point.y, 0f32));
+            outline.push(Point::new(point.x + dx * scale, point.y + dy * scale, 0f32));
+        }
+        Ok(convex_hull(&outline))
+    }
+
+    // Number of connections leaving node a that lead to a vertex of node b
+    pub fn connection_count_between(&self, node_a: usize, node_b: usize) -> usize {
+        let (a, b) = match (self.nodes.get(node_a), self.nodes.get(node_b)) {
+            (Some(a), Some(b)) => (a.borrow(), b.borrow()),
+            _ => return 0,
+        };
+        let targets: HashSet<i32> = b.vertices().iter().map(|v| v.borrow().index).collect();
+        a.vertices()
+            .iter()
+            .map(|v| {
+                v.borrow()
+                    .connection
+                    .iter()
+                    .filter(|edge| targets.contains(&edge.neighbor.borrow().index))
+                    .count()
+            })
+            .sum()
+    }
+
+    // Weight of the shortest connection between each pair of the first size nodes
+    // pairs are keyed with the lower node index first
+    fn node_weights(&self, size: usize) -> HashMap<(usize, usize), f32> {
+        let vertex_nodes = self.vertex_nodes();
+        let mut weights: HashMap<(usize, usize), f32> = HashMap::new();
+        for node in &self.nodes[..size] {
+            for vertex in node.borrow().vertices() {
+                let vertex = vertex.borrow();
+                let a = vertex_nodes[&vertex.index];
+                for edge in &vertex.connection {
+                    let b = match vertex_nodes.get(&edge.neighbor.borrow().index) {
+                        Some(&b) if b < size && b != a => b,
+                        _ => continue,
+                    };
+                    let weight = weights.entry((a.min(b), a.max(b))).or_insert(edge.distance);
+                    if edge.distance < *weight {
+                        *weight = edge.distance;
+                    }
+                }
+            }
+        }
+        weights
+    }
+
+    // Longest shortest path between any two nodes, with nodes weighted by their shortest
+    // connection. Floyd-Warshall is used for small graphs and repeated Dijkstra for larger ones
+    pub fn find_graph_diameter(&self) -> Result<f32, PathfinderError> {
+        let floyd_warshall = self.nodes.len() <= FLOYD_WARSHALL_LIMIT;
+        let distances = self.all_pairs_shortest_paths(floyd_warshall);
+        let mut diameter = 0f32;
+        for distance in distances.iter().flat_map(|row| row.iter()) {
+            if !distance.is_finite() {
+                return Err(PathfinderError::DisconnectedGraph);
+            }
+            diameter = diameter.max(*distance);
+        }
+        Ok(diameter)
+    }
+
+    // Pairs of nodes (a, b) with a < b that have at least one valid connection, in increasing order
+    pub fn find_visibility_graph(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = self
+            .node_weights(self.nodes.len())
+            .keys()
+            .cloned()
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    // Obstacle nodes whose removal would disconnect other nodes from each other, found with
+    // Tarjan's articulation point algorithm. Indices are in increasing order
+    pub fn find_bottleneck_nodes(&self) -> Vec<usize> {
+        let size = self.nodes.len();
+        let mut neighbors = vec![Vec::new(); size];
+        for &(a, b) in self.node_weights(size).keys() {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+        let mut discovery = vec![None; size];
+        let mut low = vec![0; size];
+        let mut cut = vec![false; size];
+        let mut time = 0;
+        for root in 0..size {
+            if discovery[root].is_none() {
+                Self::visit_articulation(
+                    root,
+                    None,
+                    &neighbors,
+                    (&mut discovery, &mut low, &mut cut),
+                    &mut time,
+                );
+            }
+        }
+        (0..self.obstacles.len().min(size))
+            .filter(|&i| cut[i])
+            .collect()
+    }
+
+    // Depth first search step of find_bottleneck_nodes, low holds the earliest discovery time
+    // reachable from the subtree of each node through at most one back edge
+    fn visit_articulation(
+        node: usize,
+        parent: Option<usize>,
+        neighbors: &[Vec<usize>],
+        (discovery, low, cut): (&mut Vec<Option<usize>>, &mut Vec<usize>, &mut Vec<bool>),
+        time: &mut usize,
+    ) {
+        discovery[node] = Some(*time);
+        low[node] = *time;
+        *time += 1;
+        let mut children = 0;
+        for &next in &neighbors[node] {
+            match discovery[next] {
+                Some(found) => {
+                    if Some(next) != parent {
+                        low[node] = low[node].min(found);
+                    }
+                }
+                None => {
+                    children += 1;
+                    Self::visit_articulation(
+                        next,
+                        Some(node),
+                        neighbors,
+                        (discovery, low, cut),
+                        time,
+                    );
+                    low[node] = low[node].min(low[next]);
+                    // no node below next can reach above this node without passing through it
+                    if parent.is_some() && low[next] >= discovery[node].unwrap() {
+                        cut[node] = true;
+                    }
                 }
+            }
+        }
+        // root is a cut vertex when it has more than one subtree
+        if parent.is_none() && children > 1 {
+            cut[node] = true;
+        }
+    }
 
-                // Inserting sentinels
-                if obs_sentinels.is_some() {
-                    println!("inserting sentinels");
-                    for (alpha_s, beta_s) in obs_sentinels.unwrap() {
-                        let mut a = Vertex::new_sentinel(
-                            &mut self.num_vertices,
-                            &self.nodes[i].borrow(),
-                            alpha_s,
-                        );
-                        //a.sentinel = true;
-                        let mut b = Vertex::new_sentinel(
-                            &mut self.num_vertices,
-                            &self.nodes[j].borrow(),
-                            beta_s,
-                        );
-                        //b.;
-                        let s_a = Rc::new(RefCell::new(a));
-                        let s_b = Rc::new(RefCell::new(b));
-                        self.nodes[i].borrow_mut().insert_vertex(s_a);
-                        self.nodes[j].borrow_mut().insert_vertex(s_b);
+    // Shortest distance between every pair of nodes, through the shortest connection of each
+    // pair of nodes. Unreachable pairs are infinite and the diagonal is 0
+    pub fn cost_matrix(&self) -> Result<Vec<Vec<f32>>, PathfinderError> {
+        self.validate_graph()?;
+        Ok(self.all_pairs_shortest_paths(false))
+    }
+
+    // Flying time in seconds from each node to every other node at the airspeed, with the wind
+    // along each connection. Row i holds the times leaving node i, unreachable pairs are infinite
+    pub fn flying_time_matrix(&self) -> Result<Vec<Vec<f32>>, PathfinderError> {
+        self.validate_graph()?;
+        Ok(self.node_flying_times())
+    }
+
+    // Indices of all nodes that can be reached from start_node through connections, in breadth
+    // first order starting with start_node. Empty if start_node does not exist
+    pub fn nodes_reachable_from(&self, start_node: usize) -> Vec<usize> {
+        if start_node >= self.nodes.len() {
+            return Vec::new();
+        }
+        let mut neighbors: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &(a, b) in self.node_weights(self.nodes.len()).keys() {
+            neighbors.entry(a).or_default().push(b);
+            neighbors.entry(b).or_default().push(a);
+        }
+        let mut visited = HashSet::new();
+        let mut reachable = vec![start_node];
+        visited.insert(start_node);
+        let mut i = 0;
+        while i < reachable.len() {
+            if let Some(next) = neighbors.get(&reachable[i]) {
+                for &node in next {
+                    if visited.insert(node) {
+                        reachable.push(node);
                     }
                 }
             }
+            i += 1;
         }
+        reachable
+    }
 
-        // output_graph(&self);
+    // Check that every node can be reached from every other node
+    pub fn is_fully_connected(&self) -> bool {
+        self.nodes_reachable_from(0).len() == self.nodes.len()
+    }
+
+    // Find the vertex with the given index in any node ring, for inspection in tests and debugging
+    #[cfg(any(test, debug_assertions))]
+    pub fn debug_vertex_by_index(&self, index: i32) -> Option<Rc<RefCell<Vertex>>> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.borrow().vertices())
+            .find(|vertex| vertex.borrow().index == index)
+    }
+
+    fn all_pairs_shortest_paths(&self, floyd_warshall: bool) -> Vec<Vec<f32>> {
+        let size = self.nodes.len();
+        let mut weights = vec![vec![f32::INFINITY; size]; size];
+        for (i, row) in weights.iter_mut().enumerate() {
+            row[i] = 0f32;
+        }
+        for ((a, b), weight) in self.node_weights(size) {
+            weights[a][b] = weight;
+            weights[b][a] = weight;
+        }
+        Self::shortest_paths(weights, floyd_warshall)
+    }
+
+    // Shortest flying time from each node to the others through its fastest connection to each
+    // neighbor. Times are directed since the wind changes the ground speed of every tangent
+    fn node_flying_times(&self) -> Vec<Vec<f32>> {
+        let size = self.nodes.len();
+        let vertex_nodes = self.vertex_nodes();
+        let mut times = vec![vec![f32::INFINITY; size]; size];
+        for (i, row) in times.iter_mut().enumerate() {
+            row[i] = 0f32;
+        }
+        for node in &self.nodes {
+            for vertex in node.borrow().vertices() {
+                let vertex = vertex.borrow();
+                let a = vertex_nodes[&vertex.index];
+                let from = Location::from((&vertex.location, &self.origin));
+                for edge in &vertex.connection {
+                    let neighbor = edge.neighbor.borrow();
+                    let b = match vertex_nodes.get(&neighbor.index) {
+                        Some(&b) if b != a => b,
+                        _ => continue,
+                    };
+                    let to = Location::from((&neighbor.location, &self.origin));
+                    let time = edge.distance / self.ground_speed(&from, &to);
+                    if time < times[a][b] {
+                        times[a][b] = time;
+                    }
+                }
+            }
+        }
+        Self::shortest_paths(times, false)
+    }
+
+    // All pairs shortest paths over a matrix of direct weights between nodes
+    fn shortest_paths(mut weights: Vec<Vec<f32>>, floyd_warshall: bool) -> Vec<Vec<f32>> {
+        let size = weights.len();
+        if floyd_warshall {
+            for k in 0..size {
+                for i in 0..size {
+                    for j in 0..size {
+                        let through = weights[i][k] + weights[k][j];
+                        if through < weights[i][j] {
+                            weights[i][j] = through;
+                        }
+                    }
+                }
+            }
+            return weights;
+        }
+
+        (0..size)
+            .map(|source| {
+                let mut distance = weights[source].clone();
+                let mut visited = vec![false; size];
+                visited[source] = true;
+                for _ in 1..size {
+                    let next = (0..size)
+                        .filter(|&i| !visited[i] && distance[i].is_finite())
+                        .min_by(|&a, &b| {
+                            distance[a]
+                                .partial_cmp(&distance[b])
+                                .unwrap_or(Ordering::Equal)
+                        });
+                    let current = match next {
+                        Some(current) => current,
+                        None => break,
+                    };
+                    visited[current] = true;
+                    for i in 0..size {
+                        let through = distance[current] + weights[current][i];
+                        if through < distance[i] {
+                            distance[i] = through;
+                        }
+                    }
+                }
+                distance
+            })
+            .collect()
+    }
+
+    // Minimum spanning tree over the obstacle nodes using kruskal's algorithm
+    // obstacles are weighted by the shortest connection between them, and the tree is returned
+    // as (node a, node b, weight) in order of increasing weight
+    pub fn min_spanning_tree(&self) -> Vec<(usize, usize, f32)> {
+        let size = self.obstacles.len().min(self.nodes.len());
+        let weights = self.node_weights(size);
+        let mut edges: Vec<(usize, usize, f32)> =
+            weights.into_iter().map(|((a, b), w)| (a, b, w)).collect();
+        edges.sort_by(|x, y| {
+            x.2.partial_cmp(&y.2)
+                .unwrap_or(Ordering::Equal)
+                .then((x.0, x.1).cmp(&(y.0, y.1)))
+        });
+
+        // union find over the obstacle nodes
+        let mut parent: Vec<usize> = (0..size).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        let mut tree = Vec::new();
+        for (a, b, weight) in edges {
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            if root_a != root_b {
+                parent[root_a] = root_b;
+                tree.push((a, b, weight));
+            }
+        }
+        tree
+    }
+
+    // Number of obstacles per square kilometer inside the region polygon
+    // obstacles are counted by their center, returns 0 for regions without area
+    pub fn obstacle_density(&self, region: &[Location]) -> f32 {
+        let region: Vec<Point> = region
+            .iter()
+            .map(|location| Point::from((location, &self.origin)))
+            .collect();
+        let area = polygon_area(&region).abs() / 1_000_000f32;
+        if area == 0f32 {
+            return 0f32;
+        }
+        let count = self
+            .obstacles
+            .iter()
+            .filter(|obstacle| {
+                point_in_polygon(&Point::from((&obstacle.location, &self.origin)), &region)
+            })
+            .count();
+        count as f32 / area
+    }
+
+    // Recompute edges of nodes marked dirty without rebuilding the rest of the graph
+    pub fn update_dirty_nodes(&mut self) {
+        let dirty: Vec<usize> = self.dirty_nodes.drain().collect();
+        for &i in &dirty {
+            self.disconnect_node(i);
+        }
+        for &i in &dirty {
+            for j in 0..self.nodes.len() {
+                // pairs of dirty nodes are connected once, from the smaller index
+                if j == i || (j < i && dirty.contains(&j)) {
+                    continue;
+                }
+                self.connect_nodes(i.min(j), i.max(j));
+            }
+        }
+    }
+
+    // Remove the sentinels inserted between a pair of nodes
+    // sentinels that became regular vertices, or that edges still point at, are kept
+    fn remove_sentinels(&mut self, pair: (usize, usize), targets: &HashSet<i32>) {
+        if let Some(sentinels) = self.obstacle_sentinels.remove(&pair) {
+            // edges may have been merged into a sentinel, keep those as regular vertices
+            let (mut kept, unused): (LinkedList<_>, LinkedList<_>) =
+                sentinels.into_iter().partition(|v| {
+                    !v.borrow().connection.is_empty() || targets.contains(&v.borrow().index)
+                });
+            for vertex in kept.iter_mut() {
+                vertex.borrow_mut().sentinel = false;
+            }
+            Node::prune_vertices(unused);
+        }
+    }
+
+    // Remove edges and sentinels between node a and node b, leaving other edges untouched
+    fn disconnect_pair(&mut self, a: usize, b: usize) {
+        let vertex_indices = |node: &Rc<RefCell<Node>>| -> HashSet<i32> {
+            node.borrow()
+                .vertices()
+                .iter()
+                .map(|v| v.borrow().index)
+                .collect()
+        };
+        let (a_vertices, b_vertices) = (
+            vertex_indices(&self.nodes[a]),
+            vertex_indices(&self.nodes[b]),
+        );
+        for &(from, to) in &[(a, &b_vertices), (b, &a_vertices)] {
+            for vertex in self.nodes[from].borrow().vertices() {
+                vertex
+                    .borrow_mut()
+                    .connection
+                    .retain(|edge| !to.contains(&edge.neighbor.borrow().index));
+            }
+        }
+        // edges still pointing at vertices of other nodes
+        let mut targets = HashSet::new();
+        for node in &self.nodes {
+            for vertex in node.borrow().vertices() {
+                for edge in &vertex.borrow().connection {
+                    targets.insert(edge.neighbor.borrow().index);
+                }
+            }
+        }
+        self.remove_sentinels((a.min(b), a.max(b)), &targets);
+    }
+
+    // Remove all edges and sentinels to and from obstacle node i, replacing it with a fresh node
+    fn disconnect_node(&mut self, i: usize) {
+        let removed: HashSet<i32> = self.nodes[i]
+            .borrow()
+            .vertices()
+            .iter()
+            .map(|v| v.borrow().index)
+            .collect();
+        // edges still pointing at vertices of other nodes
+        let mut targets = HashSet::new();
+        for (j, node) in self.nodes.iter().enumerate() {
+            if j == i {
+                continue;
+            }
+            for vertex in node.borrow().vertices() {
+                let mut vertex = vertex.borrow_mut();
+                vertex
+                    .connection
+                    .retain(|edge| !removed.contains(&edge.neighbor.borrow().index));
+                for edge in &vertex.connection {
+                    targets.insert(edge.neighbor.borrow().index);
+                }
+            }
+        }
+
+        let pairs: Vec<(usize, usize)> = self
+            .obstacle_sentinels
+            .keys()
+            .filter(|&&(a, b)| a == i || b == i)
+            .cloned()
+            .collect();
+        for pair in pairs {
+            self.remove_sentinels(pair, &targets);
+        }
+
+        let buffer = self.obstacle_buffer(i);
+        let mut node = Node::from((&self.obstacles[i], &self.origin, buffer));
+        self.insert_flyzone_sentinel(&mut node);
+        self.nodes[i] = Rc::new(RefCell::new(node));
+    }
+
+    // Check that every ring in the graph closes back on its header
+    pub fn validate_graph(&self) -> Result<(), PathfinderError> {
+        let limit = (self.num_vertices.max(0) as usize + 1) * 10;
+        for (i, node) in self.nodes.iter().enumerate() {
+            let node = node.borrow();
+            for ring in &[&node.left_ring, &node.right_ring] {
+                if ring.borrow().ring_length_within(limit).is_none() {
+                    return Err(PathfinderError::BrokenRing { node: i });
+                }
+            }
+        }
+        Ok(())
     }
 
     fn populate_nodes(&mut self) {
         self.nodes.clear();
         self.origin = Self::find_origin(&self.flyzones);
         for i in 0..self.obstacles.len() {
-            let mut node = (&self.obstacles[i], &self.origin, self.config.buffer_size).into();
+            let mut node = (&self.obstacles[i], &self.origin, self.obstacle_buffer(i)).into();
             self.nodes.push(Rc::new(RefCell::new(node)));
         }
         if self.config.virtualize_flyzone {
@@ -121,6 +855,9 @@ impl Tanstar {
                 self.virtualize_flyzone(i);
             }
         }
+        for i in 0..self.keep_out_zones.len() {
+            self.virtualize_keep_out_zone(i);
+        }
     }
 
     // Generate all valid possible path (tangent lines) between two nodes, and return the
@@ -130,101 +867,29 @@ impl Tanstar {
     pub fn find_path(&self, a: &Node, b: &Node) -> (Path, Option<Sentinel>) {
         let c1: Point = a.origin;
         let c2: Point = b.origin;
-        let r1: f32 = a.radius;
-        let r2: f32 = b.radius;
-        let dist: f32 = c1.distance(&c2);
-
-        // theta1 and theta2 represents the normalize angle
-        // normalized between 0 and 2pi
-        let theta = (c2.y - c1.y).atan2(c2.x - c1.x);
-        let (theta1, theta2) = if theta > 0f32 {
-            (theta, theta + PI)
-        } else {
-            (theta + 2f32 * PI, theta + PI)
-        };
-
+        let (r1, r2) = self.pair_radii(a, b);
+        // tapered rings only clear the obstacles modelled as cones
+        let tapered = r1 < a.radius || r2 < b.radius;
         println!(
             "x1:{}, y1:{}, r1:{}, x2:{}, y2:{}, r2:{}",
             c1.x, c1.y, r1, c2.x, c2.y, r2
         );
-
-        println!(
-            "theta: {}, theta1: {}, theta2: {}",
-            theta.to_degrees(),
-            theta1.to_degrees(),
-            theta2.to_degrees()
-        );
-
-        // gamma1 and gamma2 are the angle between reference axis and the tangents
-        // gamma1 is angle to inner tangent, gamma2 is angle to outer tangent
-        let gamma1 = ((r1 + r2).abs() / dist).acos();
-        let mut gamma2 = ((r1 - r2).abs() / dist).acos();
-
-        // we assume r1 is greater than r2 for the math to work, so find complement if otherwise
-        if r2 > r1 {
-            gamma2 = PI - gamma2;
-        }
-
-        println!(
-            "gamma1: {}, gamma2: {}",
-            gamma1.to_degrees(),
-            gamma2.to_degrees()
-        );
-
-        // Outer tangent always exists
-        let mut candidates = vec![
-            (
-                normalize_angle(true, theta1 - gamma2),
-                normalize_angle(true, theta2 + PI - gamma2),
-            ),
-            (
-                normalize_angle(false, theta1 - 2f32 * PI + gamma2),
-                normalize_angle(false, theta2 - 3f32 * PI + gamma2),
-            ),
-        ];
-
-        let mut sentinels = None;
-        if r1 != 0f32 && r2 != 0f32 && dist > r1 + r2 {
-            candidates.append(&mut vec![
-                // Inner left tangent
-                (
-                    normalize_angle(true, theta1 - gamma1),
-                    normalize_angle(false, theta2 - 2f32 * PI - gamma1),
-                ),
-                // Inner right tangent
-                (
-                    normalize_angle(false, theta1 - 2f32 * PI + gamma1),
-                    normalize_angle(true, theta2 + gamma1),
-                ),
-            ]);
-        } else {
-            println!("obstacle sentinels detected");
-            //determine angle locations of sentinels
-            let theta_s = ((r1.powi(2) + dist.powi(2) - r2.powi(2)) / (2f32 * r1 * dist)).acos();
-            let phi_s = ((r2.powi(2) + dist.powi(2) - r1.powi(2)) / (2f32 * r2 * dist)).acos();
-
-            //sentinel vertices on A
-            let a_s1 = theta_s;
-            let a_s2 = -theta_s;
-            let a_s3 = -2f32 * PI + theta_s;
-            let a_s4 = 2f32 * PI - theta_s;
-            //sentinel vertices on B
-            let b_s1 = PI - phi_s;
-            let b_s2 = PI + phi_s;
-            let b_s3 = -PI + phi_s;
-            let b_s4 = -PI - phi_s;
-            sentinels = Some(vec![(a_s1, b_s1), (a_s2, b_s2), (a_s3, b_s3), (a_s4, b_s4)]);
-        }
+        let (candidates, sentinels) = circle_circle_tangent_angles(&c1, r1, &c2, r2);
 
         let mut connections = Vec::new();
         let mut point_connections = Vec::new();
         for (i, j) in candidates {
-            let p1 = Point::from((a, i));
-            let p2 = Point::from((b, j));
+            let p1 = Point::from((a, i, r1));
+            let p2 = Point::from((b, j, r2));
             println!("angles {} -> {}", i.to_degrees(), j.to_degrees());
             println!("validating path {:?} -> {:?}", p1, p2);
 
-            match self.valid_path(&p1, &p2) {
+            let validity = if tapered {
+                self.valid_tapered_path(&p1, &p2)
+            } else {
+                self.valid_static_path(&p1, &p2)
+            };
+            match validity {
                 PathValidity::Valid => {
                     println!("This path is Valid without Flyover.");
                     connections.push((i, j, p1.distance(&p2), 0f32));
@@ -245,7 +910,55 @@ impl Tanstar {
     }
 
     // check if a path is valid (not blocked by flightzone or obstacles)
-    fn valid_path(&self, a: &Point, b: &Point) -> PathValidity {
+    pub fn valid_path(&self, a: &Point, b: &Point) -> PathValidity {
+        self.valid_path_at(a, b, 0f32)
+    }
+
+    // Check a segment flown elapsed_s seconds from now, with moving obstacles where they will be
+    // by then. The segment is assumed to be flown at the configured airspeed
+    pub fn valid_path_at(&self, a: &Point, b: &Point, elapsed_s: f32) -> PathValidity {
+        if self.obstacle_velocities.is_empty() {
+            return self.valid_static_path(a, b);
+        }
+        let moving = self.moving_obstacle_threshold(a, b, elapsed_s);
+        match self.valid_static_path(a, b) {
+            PathValidity::Invalid => PathValidity::Invalid,
+            PathValidity::Valid if moving == 0f32 => PathValidity::Valid,
+            PathValidity::Valid => PathValidity::Flyover(moving),
+            PathValidity::Flyover(threshold) => PathValidity::Flyover(threshold.max(moving)),
+        }
+    }
+
+    // Radii of the rings of nodes a and b used between them, once a flight altitude is set
+    // tapered at the altitude halfway between the altitudes the two nodes are flown around at,
+    // flat nodes at their origin and obstacles at the flight altitude
+    pub(crate) fn pair_radii(&self, a: &Node, b: &Node) -> (f32, f32) {
+        let flight_altitude = match self.flight_altitude {
+            Some(altitude) => altitude,
+            None => return (a.radius, b.radius),
+        };
+        let node_altitude = |node: &Node| {
+            if node.height == 0f32 {
+                node.origin.z
+            } else {
+                flight_altitude
+            }
+        };
+        let altitude = (node_altitude(a) + node_altitude(b)) / 2f32;
+        (a.effective_radius(altitude), b.effective_radius(altitude))
+    }
+
+    // valid_static_path with every obstacle modelled as a cone narrowing to its height, for
+    // segments between tapered rings
+    fn valid_tapered_path(&self, a: &Point, b: &Point) -> PathValidity {
+        match self.valid_static_path(a, b) {
+            PathValidity::Invalid => PathValidity::Invalid,
+            _ => PathValidity::Flyover(self.obstacle_cone_threshold(a, b)),
+        }
+    }
+
+    // Check a segment against the flyzones, keep out zones and obstacles that do not move
+    fn valid_static_path(&self, a: &Point, b: &Point) -> PathValidity {
         // let theta_o = (b.z - a.z).atan2(a.distance(b));
         // //check if angle of waypoints is valid
         // if theta_o > MAX_ANGLE_ASCENT {
@@ -277,9 +990,74 @@ impl Tanstar {
             }
         }
 
-        // test for obstacles
+        for zone in &self.keep_out_zones {
+            let points: Vec<Point> = zone
+                .iter()
+                .map(|location| Point::from((location, &self.origin)))
+                .collect();
+            let size = points.len();
+            let crosses = (0..size).any(|i| intersect(a, b, &points[i], &points[(i + 1) % size]));
+            if crosses || point_in_polygon(a, &points) || point_in_polygon(b, &points) {
+                println!("false due to keep out zone");
+                return PathValidity::Invalid;
+            }
+        }
+
+        let max_height = self.obstacle_threshold(a, b);
+        println!("path valid with threshold {}", max_height);
+        PathValidity::Flyover(max_height)
+    }
+
+    // Minimum altitude required to fly from a to b over every obstacle in the way
+    // this is the threshold the segment would be given as an edge of the graph
+    pub fn segment_flyover_altitude(&self, a: &Location, b: &Location) -> f32 {
+        let a = Point::from((a, &self.origin));
+        let b = Point::from((b, &self.origin));
+        self.obstacle_threshold(&a, &b)
+    }
+
+    // Indices of the obstacles crossed by the direct segment from start to goal, in increasing
+    // order. Only the obstacle list is used, so this works before the graph is built
+    pub fn obstacles_blocking_direct_path(&self, start: Location, goal: Location) -> Vec<usize> {
+        let a = Point::from((&start, &self.origin));
+        let b = Point::from((&goal, &self.origin));
+        self.obstacles
+            .iter()
+            .enumerate()
+            .filter(|(_, obstacle)| {
+                let intersections = perpendicular_intersect(&self.origin, &a, &b, obstacle);
+                matches!(intersections, (Some(_), Some(_)))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Convex hull of a group of obstacles, counter-clockwise, to avoid a cluster as one obstacle
+    // each obstacle contributes the 16 sided polygon of its node, so the buffer keeps the obstacle
+    // circles inside the hull
+    pub fn obstacle_group_convex_hull(
+        &self,
+        indices: &[usize],
+    ) -> Result<Vec<Point>, PathfinderError> {
+        let mut points = Vec::new();
+        for &index in indices {
+            let obstacle = self
+                .obstacles
+                .get(index)
+                .ok_or(PathfinderError::InvalidObstacleIndex { index })?;
+            let node = Node::from((obstacle, &self.origin, self.obstacle_buffer(index)));
+            points.extend(node.to_polygon(16));
+        }
+        Ok(convex_hull(&points))
+    }
+
+    // Maximum height of the obstacles intersected by the segment a to b, 0 if none
+    fn obstacle_threshold(&self, a: &Point, b: &Point) -> f32 {
         let mut max_height = 0f32;
-        for obstacle in &self.obstacles {
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            if self.passable_obstacles.contains(&i) || self.obstacle_velocities.contains_key(&i) {
+                continue;
+            }
             // catch the simple cases for now: if a or b are inside the radius of obstacle, invalid
             // check if there are two points of intersect, for flyover cases
             if let (Some(_p1), Some(_p2)) = perpendicular_intersect(&self.origin, a, b, obstacle) {
@@ -293,7 +1071,63 @@ impl Tanstar {
                 // return PathValidity::Invalid; // Temporarily disable fly over
             }
         }
-        println!("path valid with threshold {}", max_height);
-        PathValidity::Flyover(max_height)
+        max_height
+    }
+
+    // Whether b can be seen from a, with no static obstacle in between taller than the lower of
+    // the two points
+    pub fn line_of_sight(&self, a: &Point, b: &Point) -> bool {
+        self.obstacle_threshold(a, b) <= a.z.min(b.z)
+    }
+
+    // obstacle_threshold for obstacles modelled as cones, the altitude where the radius of each
+    // cone crossed by the segment a to b shrinks to the distance of the segment from its center
+    // less the buffer of the obstacle
+    fn obstacle_cone_threshold(&self, a: &Point, b: &Point) -> f32 {
+        let mut max_height = 0f32;
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            if self.passable_obstacles.contains(&i) || self.obstacle_velocities.contains_key(&i) {
+                continue;
+            }
+            let center = Point::from((&obstacle.location, &self.origin));
+            // distance to the cone, once the buffer is kept clear of it. Tangents to a tapered
+            // ring touch the cone at the ring altitude, so they are let through with some slack
+            let clearance = point_to_segment_distance_2d(&center, a, b) - self.obstacle_buffer(i)
+                + CONE_TOLERANCE;
+            if clearance < obstacle.radius {
+                let taper = if obstacle.radius > 0f32 {
+                    clearance.max(0f32) / obstacle.radius
+                } else {
+                    0f32
+                };
+                max_height = max_height.max(obstacle.height * (1f32 - taper));
+            }
+        }
+        max_height
+    }
+
+    // Minimum altitude to fly from a to b over the moving obstacles, starting elapsed_s seconds
+    // from now. Each obstacle is checked in its own frame, where the segment is shifted back by
+    // the distance the obstacle moves while the segment is flown
+    pub(crate) fn moving_obstacle_threshold(&self, a: &Point, b: &Point, elapsed_s: f32) -> f32 {
+        let duration = a.distance3d(b) / self.airspeed;
+        let mut max_height = 0f32;
+        for (&i, &(vx, vy, vz)) in &self.obstacle_velocities {
+            if self.passable_obstacles.contains(&i) {
+                continue;
+            }
+            let obstacle = Obstacle::new(
+                self.predict_obstacle_position(i, elapsed_s),
+                self.obstacles[i].radius,
+                self.obstacles[i].height + (vz * elapsed_s).max(vz * (elapsed_s + duration)),
+            );
+            let shifted = Point::new(b.x - vx * duration, b.y - vy * duration, b.z);
+            if let (Some(_), Some(_)) =
+                perpendicular_intersect(&self.origin, a, &shifted, &obstacle)
+            {
+                max_height = max_height.max(obstacle.height);
+            }
+        }
+        max_height
     }
 }
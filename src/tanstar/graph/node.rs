@@ -7,6 +7,7 @@ pub struct Node {
     pub origin: Point,
     pub radius: f32,
     pub height: f32,                     // make private later
+    pub buffer: f32,                     // part of the radius kept clear around an obstacle
     pub left_ring: Rc<RefCell<Vertex>>,  // make private later
     pub right_ring: Rc<RefCell<Vertex>>, // make private later
 }
@@ -48,11 +49,13 @@ impl fmt::Display for Node {
 impl From<(&Obstacle, &Location, f32)> for Node {
     // Generate node from obstacle
     fn from((obs, origin, buffer): (&Obstacle, &Location, f32)) -> Self {
-        Self::new(
+        let mut node = Self::new(
             Point::from((&obs.location, origin)),
             obs.radius + buffer,
             obs.height,
-        )
+        );
+        node.buffer = buffer;
+        node
     }
 }
 
@@ -86,6 +89,13 @@ impl<T> From<(&Waypoint<T>, &Location)> for Node {
 }
 
 impl Node {
+    // Generate node for a geofence point given in degrees without an obstacle
+    // the node has no height, only the horizontal area around the point is excluded
+    pub fn from_geofence_point(lat: f64, lon: f64, radius: f32, origin: &Location) -> Self {
+        let location = Location::from_degrees(lat, lon, 0f32);
+        Self::new(Point::from((&location, origin)), radius, 0f32)
+    }
+
     pub fn new(origin: Point, radius: f32, height: f32) -> Self {
         let left_head = Rc::new(RefCell::new(Vertex::new_head(
             &mut HEADER_VERTEX_INDEX,
@@ -103,6 +113,7 @@ impl Node {
             origin,
             radius,
             height,
+            buffer: 0f32,
             left_ring: left_head,
             right_ring: right_head,
         }
@@ -143,6 +154,18 @@ impl Node {
         num_vertices: &mut i32,
         angle: f32,
         threshold: f32,
+    ) -> Rc<RefCell<Vertex>> {
+        self.get_vertex_at(num_vertices, angle, self.radius, threshold)
+    }
+
+    // get_vertex on the ring of radius around the origin, for tapered nodes
+    // only vertices on the same ring are merged
+    pub fn get_vertex_at(
+        &self,
+        num_vertices: &mut i32,
+        angle: f32,
+        radius: f32,
+        threshold: f32,
     ) -> Rc<RefCell<Vertex>> {
         println!("Looking for vertex with angle {}", angle);
         let (current, next) = self.traverse_rings(angle);
@@ -166,10 +189,16 @@ impl Node {
                     };
                 }
             }
-            let arc_a = arc_length(temp_current.borrow().angle, angle, self.radius);
-            let arc_b = arc_length(angle, temp_next.borrow().angle, self.radius);
+            let arc_a = angle_difference(temp_current.borrow().angle, angle).abs() * radius;
+            let arc_b = angle_difference(angle, temp_next.borrow().angle).abs() * radius;
             let min_arc = if arc_a < arc_b { arc_a } else { arc_b };
-            if min_arc < threshold {
+            let closest = if arc_a < arc_b {
+                &temp_current
+            } else {
+                &temp_next
+            };
+            let same_ring = (closest.borrow().radius - radius).abs() < 0.01;
+            if min_arc < threshold && same_ring {
                 println!(
                     "found existing vertex {} and {} with dist {}",
                     current.borrow(),
@@ -191,7 +220,13 @@ impl Node {
             current.borrow().index,
             next.borrow().index
         );
-        let v = Rc::new(RefCell::new(Vertex::new(num_vertices, self, angle, vec![])));
+        let v = Rc::new(RefCell::new(Vertex::with_radius(
+            num_vertices,
+            self,
+            angle,
+            radius,
+            vec![],
+        )));
         next.borrow_mut().prev = Some(v.clone());
         v.borrow_mut().next = Some(next);
         v.borrow_mut().prev = Some(current.clone());
@@ -209,6 +244,48 @@ impl Node {
         current.borrow_mut().next = Some(v.clone());
     }
 
+    // Regular polygon with sides vertices on the boundary of the node, counter-clockwise
+    pub fn to_polygon(&self, sides: usize) -> Vec<Point> {
+        let edge = Point::new(self.radius, 0f32, 0f32);
+        (0..sides)
+            .map(|i| {
+                let p = rotate_point(&edge, 2f32 * PI * i as f32 / sides as f32);
+                Point::new(self.origin.x + p.x, self.origin.y + p.y, self.origin.z)
+            })
+            .collect()
+    }
+
+    // Radius of the node at altitude, modelling the obstacle as a cone narrowing from its radius
+    // at the ground to 0 at its height, with the buffer kept around it. Flat nodes keep their
+    // radius at any altitude
+    pub fn effective_radius(&self, altitude: f32) -> f32 {
+        if self.height == 0f32 {
+            return self.radius;
+        }
+        let obstacle = self.radius - self.buffer;
+        obstacle * (1f32 - altitude / self.height).max(0f32) + self.buffer
+    }
+
+    // Collect the vertices of both rings, excluding the headers
+    pub fn vertices(&self) -> Vec<Rc<RefCell<Vertex>>> {
+        let mut vertices = Vec::new();
+        for head in &[&self.left_ring, &self.right_ring] {
+            let mut current = match head.borrow().next {
+                Some(ref next) => next.clone(),
+                None => panic!("broken chain"),
+            };
+            while current.borrow().index != HEADER_VERTEX_INDEX {
+                vertices.push(current.clone());
+                let next = match current.borrow().next {
+                    Some(ref next) => next.clone(),
+                    None => panic!("broken chain"),
+                };
+                current = next;
+            }
+        }
+        vertices
+    }
+
     pub fn prune_vertices(target: LinkedList<Rc<RefCell<Vertex>>>) {
         for v in target {
             let prev = match v.borrow_mut().prev {
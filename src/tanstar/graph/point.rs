@@ -34,11 +34,16 @@ impl From<(&Location, &Location)> for Point {
 impl From<(&Node, f32)> for Point {
     // Create point from node and vertex angle
     fn from((node, angle): (&Node, f32)) -> Self {
+        Self::from((node, angle, node.radius))
+    }
+}
+
+impl From<(&Node, f32, f32)> for Point {
+    // Create point from node, vertex angle and the radius of the ring around the node origin
+    fn from((node, angle, radius): (&Node, f32, f32)) -> Self {
         let origin = node.origin;
-        let radius = node.radius;
-        let x = origin.x + radius * angle.cos();
-        let y = origin.y + radius * angle.sin();
-        Self::new(x, y, origin.z)
+        let offset = rotate_point(&Point::new(radius, 0f32, 0f32), angle);
+        Self::new(origin.x + offset.x, origin.y + offset.y, origin.z)
     }
 }
 
@@ -62,6 +67,20 @@ impl Point {
         ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2))
             .sqrt() as f32
     }
+
+    // Linear interpolation from self to other, t is clamped between 0 and 1
+    pub fn lerp(&self, other: &Point, t: f32) -> Point {
+        self.lerp_unclamped(other, t.clamp(0f32, 1f32))
+    }
+
+    // lerp without clamping t, so values outside 0 to 1 extend past either point
+    pub(crate) fn lerp_unclamped(&self, other: &Point, t: f32) -> Point {
+        Point::new(
+            self.x * (1f32 - t) + other.x * t,
+            self.y * (1f32 - t) + other.y * t,
+            self.z * (1f32 - t) + other.z * t,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -102,6 +121,26 @@ mod test {
         }
     }
 
+    #[test]
+    fn lerp_test() {
+        let a = Point::new(2f32, -4f32, 10f32);
+        let b = Point::new(12f32, 6f32, 30f32);
+        let check = |t: f32, x: f32, y: f32, z: f32| {
+            let p = a.lerp(&b, t);
+            assert!((p.x - x).abs() < 1e-5);
+            assert!((p.y - y).abs() < 1e-5);
+            assert!((p.z - z).abs() < 1e-5);
+        };
+
+        check(0f32, 2f32, -4f32, 10f32);
+        check(1f32, 12f32, 6f32, 30f32);
+        check(0.5f32, 7f32, 1f32, 20f32);
+        check(0.25f32, 4.5f32, -1.5f32, 15f32);
+        // t is clamped to the segment
+        check(2f32, 12f32, 6f32, 30f32);
+        check(-1f32, 2f32, -4f32, 10f32);
+    }
+
     #[test]
     fn random_test() {
         let flight_zone = vec![vec![
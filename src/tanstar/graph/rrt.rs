@@ -0,0 +1,137 @@
+// rrt.rs
+// Contains the sampling based planner, used without the visibility graph
+
+use super::*;
+
+// Distance the tree grows towards each sample, in meters
+const RRT_STEP_SIZE: f32 = 20f32;
+// One in this many samples is the goal itself
+const RRT_GOAL_BIAS: u32 = 10;
+// Fixed seed so that the same query always returns the same path
+const RRT_SEED: u32 = 0x2545_F491;
+
+// xorshift generator for the samples, enough for spreading points over the flyzone
+struct Xorshift(u32);
+
+impl Xorshift {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    // uniform sample in [low, high)
+    fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * (self.next() as f32 / u32::MAX as f32)
+    }
+}
+
+impl Tanstar {
+    // Find a path from start to goal with a Rapidly-exploring Random Tree
+    // the tree grows from start in the continuous space, checking every extension with
+    // valid_path at the lower altitude of start and goal, and the result is shortened with
+    // waypoint_reduction. Paths are not optimal, and NoPathFound is returned when the goal is
+    // not reached within iterations samples
+    pub fn find_path_rrt(
+        &mut self,
+        start: Location,
+        goal: Location,
+        iterations: u32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        self.validate_location(&start)?;
+        self.validate_location(&goal)?;
+        let altitude = start.alt().min(goal.alt());
+        let (min, max) = self.bounding_box();
+        let (min, max) = (
+            Point::from((&min, &self.origin)),
+            Point::from((&max, &self.origin)),
+        );
+        let end = Point::from((&goal, &self.origin));
+        let mut rng = Xorshift(RRT_SEED);
+
+        // points of the tree, with the index of their parent
+        let mut tree = vec![(Point::from((&start, &self.origin)), None)];
+        let mut reached = None;
+        for _ in 0..iterations {
+            let sample = if rng.next() < u32::MAX / RRT_GOAL_BIAS {
+                end
+            } else {
+                Point::new(rng.range(min.x, max.x), rng.range(min.y, max.y), altitude)
+            };
+            let mut nearest = 0;
+            for (i, &(point, _)) in tree.iter().enumerate() {
+                if point.distance(&sample) < tree[nearest].0.distance(&sample) {
+                    nearest = i;
+                }
+            }
+            let from = tree[nearest].0;
+            let distance = from.distance(&sample);
+            if distance == 0f32 {
+                continue;
+            }
+            let t = (RRT_STEP_SIZE / distance).min(1f32);
+            let next = from.lerp(&sample, t);
+            if !self.can_fly_between(&from, &next, altitude) {
+                continue;
+            }
+            tree.push((next, Some(nearest)));
+            if next.distance(&end) <= RRT_STEP_SIZE && self.can_fly_between(&next, &end, altitude) {
+                reached = Some(tree.len() - 1);
+                break;
+            }
+        }
+
+        let mut cur = reached.ok_or(PathfinderError::NoPathFound)?;
+        let mut points = vec![end];
+        loop {
+            let (point, parent) = tree[cur];
+            points.push(point);
+            match parent {
+                Some(parent) => cur = parent,
+                None => break,
+            }
+        }
+        points.reverse();
+        let points = self.waypoint_reduction(&points, altitude);
+
+        let mut path = vec![start];
+        for point in &points[1..points.len() - 1] {
+            let mut location = Location::from((point, &self.origin));
+            location.alt = start.alt;
+            path.push(location);
+        }
+        path.push(goal);
+        self.last_path = Some(path.clone());
+        Ok(path)
+    }
+
+    // Drop the points of a path that can be skipped by flying straight to a later point
+    // the first and last points are always kept
+    fn waypoint_reduction(&self, points: &[Point], altitude: f32) -> Vec<Point> {
+        let mut reduced = vec![points[0]];
+        let mut cur = 0;
+        while cur < points.len() - 1 {
+            let mut next = cur + 1;
+            for later in (cur + 2..points.len()).rev() {
+                if self.can_fly_between(&points[cur], &points[later], altitude) {
+                    next = later;
+                    break;
+                }
+            }
+            reduced.push(points[next]);
+            cur = next;
+        }
+        reduced
+    }
+
+    // Whether the straight segment from a to b stays in the flyzones and clears every obstacle
+    // when flown at altitude
+    pub(super) fn can_fly_between(&self, a: &Point, b: &Point, altitude: f32) -> bool {
+        match self.valid_path(a, b) {
+            PathValidity::Invalid => false,
+            PathValidity::Valid => true,
+            PathValidity::Flyover(threshold) => threshold < altitude,
+        }
+    }
+}
@@ -1,5 +1,5 @@
+use super::util::test_helpers::*;
 use super::*;
-use obj::Obstacle;
 const THRESHOLD: f64 = 0.001;
 
 //assert equal, equal practically because floating points suck for intersection
@@ -52,24 +52,11 @@ fn assert_point_eq(a: &Point, b: &Point) {
     assert_eqp!(a.y, b.y, THRESHOLD);
 }
 
-fn dummy_origin() -> Location {
-    Location::from_radians(0f64, 0f64, 0f32)
-}
-
-// Helper function to create an obstacle based on its position in transformed graph
-fn obstacle_from_meters(x: f32, y: f32, radius: f32, height: f32) -> Obstacle {
-    Obstacle::new(
-        Location::from_meters(x, y, height, &dummy_origin()),
-        radius,
-        height,
-    )
-}
-
 fn dummy_flyzones() -> Vec<Vec<Location>> {
-    let a = Point::new(0f32, 0f32, 10f32);
-    let b = Point::new(0f32, 400f32, 10f32);
-    let c = Point::new(400f32, 400f32, 10f32);
-    let d = Point::new(400f32, 0f32, 10f32);
+    let a = make_point(0f32, 0f32, 10f32);
+    let b = make_point(0f32, 400f32, 10f32);
+    let c = make_point(400f32, 400f32, 10f32);
+    let d = make_point(400f32, 0f32, 10f32);
     vec![points_to_flyzone(vec![a, b, c, d])]
 }
 
@@ -80,26 +67,26 @@ fn dummy_pathfinder() -> Tanstar {
 fn points_to_flyzone(points: Vec<Point>) -> Vec<Location> {
     let mut flyzone = Vec::new();
     for point in points {
-        flyzone.push(Location::from((&point, &dummy_origin())));
+        flyzone.push(Location::from((&point, &origin())));
     }
     flyzone
 }
 
 #[test]
 fn flyzone_pathing() {
-    let a = Point::new(40f32, 0f32, 10f32);
-    let b = Point::new(40f32, 40f32, 10f32);
-    let c = Point::new(0f32, 40f32, 10f32);
-    let d = Point::new(0f32, 0f32, 10f32);
+    let a = make_point(40f32, 0f32, 10f32);
+    let b = make_point(40f32, 40f32, 10f32);
+    let c = make_point(0f32, 40f32, 10f32);
+    let d = make_point(0f32, 0f32, 10f32);
     let flyzones = vec![points_to_flyzone(vec![a, b, c, d])];
     let pathfinder = Tanstar::create(1f32, flyzones, Vec::new());
 
-    let e = Point::new(20f32, 20f32, 10f32);
-    let f = Point::new(30f32, 30f32, 10f32);
-    let g = Point::new(20f32, 50f32, 10f32);
+    let e = make_point(20f32, 20f32, 10f32);
+    let f = make_point(30f32, 30f32, 10f32);
+    let g = make_point(20f32, 50f32, 10f32);
 
-    let h = Point::new(50f32, 50f32, 10f32);
-    let i = Point::new(50f32, 0f32, 10f32);
+    let h = make_point(50f32, 50f32, 10f32);
+    let i = make_point(50f32, 0f32, 10f32);
 
     assert_eq!(bool::from(pathfinder.valid_path(&e, &f)), true);
     assert_eq!(bool::from(pathfinder.valid_path(&e, &g)), false);
@@ -115,15 +102,15 @@ fn flyzone_pathing() {
 
 #[test]
 fn flyzones_pathing() {
-    let a = Point::new(40f32, 0f32, 10f32);
-    let b = Point::new(40f32, 40f32, 10f32);
-    let c = Point::new(0f32, 40f32, 10f32);
-    let d = Point::new(0f32, 0f32, 10f32);
+    let a = make_point(40f32, 0f32, 10f32);
+    let b = make_point(40f32, 40f32, 10f32);
+    let c = make_point(0f32, 40f32, 10f32);
+    let d = make_point(0f32, 0f32, 10f32);
 
-    let e = Point::new(30f32, 10f32, 10f32);
-    let f = Point::new(30f32, 30f32, 10f32);
-    let g = Point::new(10f32, 30f32, 10f32);
-    let h = Point::new(10f32, 10f32, 10f32);
+    let e = make_point(30f32, 10f32, 10f32);
+    let f = make_point(30f32, 30f32, 10f32);
+    let g = make_point(10f32, 30f32, 10f32);
+    let h = make_point(10f32, 10f32, 10f32);
 
     let flyzone1 = points_to_flyzone(vec![a, b, c, d]);
     let flyzone2 = points_to_flyzone(vec![e, f, g, h]);
@@ -133,11 +120,11 @@ fn flyzones_pathing() {
     let pathfinder = Tanstar::create(1f32, flyzones, Vec::new());
 
     //test breaks with multiple flyzones; must declare every flyzone from meters at (0,0)
-    /*let i = Point::new(15f32, 15f32, 10f32);
-    let j = Point::new(25f32, 25f32, 10f32);
-    let k = Point::new(35f32, 5f32, 10f32);
-    let l = Point::new(50f32, 50f32, 10f32);
-    let m = Point::new(35f32, 25f32, 10f32);
+    /*let i = make_point(15f32, 15f32, 10f32);
+    let j = make_point(25f32, 25f32, 10f32);
+    let k = make_point(35f32, 5f32, 10f32);
+    let l = make_point(50f32, 50f32, 10f32);
+    let m = make_point(35f32, 25f32, 10f32);
 
     assert_eq!(pathfinder.valid_path(&i, &j), true);
     assert_eq!(pathfinder.valid_path(&i, &k), false);
@@ -150,11 +137,11 @@ fn flyzones_pathing() {
 #[test]
 // https://www.geogebra.org/graphing/mfqccnkb
 fn obstacles_pathing() {
-    let a = Point::new(20f32, 40f32, 10f32);
-    let b = Point::new(20f32, 1f32, 10f32);
-    let c = Point::new(20f32, 60f32, 10f32);
-    let d = Point::new(60f32, 20f32, 10f32);
-    let e = Point::new(20f32, 30f32, 10f32);
+    let a = make_point(20f32, 40f32, 10f32);
+    let b = make_point(20f32, 1f32, 10f32);
+    let c = make_point(20f32, 60f32, 10f32);
+    let d = make_point(60f32, 20f32, 10f32);
+    let e = make_point(20f32, 30f32, 10f32);
 
     let ob = obstacle_from_meters(20f32, 20f32, 20f32, 20f32);
     let obstacles = vec![ob];
@@ -188,8 +175,8 @@ fn intersects_circle() {
 
     //Check intersections of line from (0,0) to (30,0) with circle of radius 5 centered at (15,0)
     //2 sol - Desmos Eq 2
-    let a = Point::new(0f32, 0f32, 0f32);
-    let b = Point::new(30f32, 0f32, 0f32);
+    let a = make_point(0f32, 0f32, 0f32);
+    let b = make_point(30f32, 0f32, 0f32);
 
     let (c1, c2) = circular_intersect(&pathfinder.origin, &a, &b, &ob);
     assert!(c1.is_some());
@@ -202,8 +189,8 @@ fn intersects_circle() {
 
     //Check intersections of line from (0,5) to (30,5) with circle of radius 5 centered at (15,0)
     //1 sol - Desmos Eq 3
-    let d = Point::new(0f32, 5f32, 0f32);
-    let e = Point::new(30f32, 5f32, 0f32);
+    let d = make_point(0f32, 5f32, 0f32);
+    let e = make_point(30f32, 5f32, 0f32);
 
     let (f1, f2) = circular_intersect(&pathfinder.origin, &d, &e, &ob);
     assert!(f1.is_some());
@@ -214,8 +201,8 @@ fn intersects_circle() {
 
     //Check intersections of line from (10,-5) to (10,5) with circle of radius 5 centered at (15,0)
     //1 sol - Desmos Eq 4
-    let g = Point::new(10f32, -5f32, 0f32);
-    let h = Point::new(10f32, 5f32, 0f32);
+    let g = make_point(10f32, -5f32, 0f32);
+    let h = make_point(10f32, 5f32, 0f32);
 
     let (i1, i2) = circular_intersect(&pathfinder.origin, &g, &h, &ob);
     assert!(i1.is_some());
@@ -226,8 +213,8 @@ fn intersects_circle() {
 
     //Check intersections of line from (10,-5) to (20,5) , y = x-15, with circle of radius 5 centered at (15,0)
     //2 sol - Desmos Eq 5
-    let j = Point::new(10f32, -5f32, 0f32);
-    let k = Point::new(20f32, 5f32, 0f32);
+    let j = make_point(10f32, -5f32, 0f32);
+    let k = make_point(20f32, 5f32, 0f32);
 
     let (l1, l2) = circular_intersect(&pathfinder.origin, &j, &k, &ob);
     assert!(l1.is_some());
@@ -240,8 +227,8 @@ fn intersects_circle() {
 
     //Check intersections of line from (10,10) to (15,-10) with circle of radius 5 centered at (15,0)
     //2 sol - Desmos Eq 6
-    let m = Point::new(10f32, 10f32, 0f32);
-    let n = Point::new(15f32, -10f32, 0f32);
+    let m = make_point(10f32, 10f32, 0f32);
+    let n = make_point(15f32, -10f32, 0f32);
 
     let (o1, o2) = circular_intersect(&pathfinder.origin, &m, &n, &ob);
     assert!(o1.is_some());
@@ -255,18 +242,18 @@ fn intersects_circle() {
 
 #[test]
 fn intersection_distance() {
-    let ax = Point::new(0f32, 0f32, 0f32);
-    let ay = Point::new(30f32, 0f32, 0f32);
+    let ax = make_point(0f32, 0f32, 0f32);
+    let ay = make_point(30f32, 0f32, 0f32);
 
-    let bx = Point::new(10f32, 0f32, 0f32);
-    let by = Point::new(20f32, 0f32, 0f32);
+    let bx = make_point(10f32, 0f32, 0f32);
+    let by = make_point(20f32, 0f32, 0f32);
 
     let ob = obstacle_from_meters(15f32, 0f32, 5f32, 20f32);
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
 
     //intercepts at (10,0), (20,0)
     assert_eq!(
-        intersect_distance(&ax, &ay, &Point::from((&ob.location, &dummy_origin()))).2,
+        intersect_distance(&ax, &ay, &Point::from((&ob.location, &origin()))).2,
         0f32
     );
     let result = perpendicular_intersect(&pathfinder.origin, &ax, &ay, &ob);
@@ -281,8 +268,8 @@ fn circular_intersection() {
 
     //Check intersections of line from (0,0) to (30,0) with circle of radius 5 centered at (15,0)
     //2 sol
-    let a = Point::new(0f32, 0f32, 0f32);
-    let b = Point::new(30f32, 0f32, 0f32);
+    let a = make_point(0f32, 0f32, 0f32);
+    let b = make_point(30f32, 0f32, 0f32);
 
     let ob = obstacle_from_meters(15f32, 0f32, 5f32, 20f32);
 
@@ -298,8 +285,8 @@ fn circular_intersection() {
 
     //Check intersections of line from (0,5) to (30,5) with circle of radius 5 centered at (15,0)
     //intersects at 1 point, should be considered valid
-    let d = Point::new(0f32, 5f32, 0f32);
-    let e = Point::new(30f32, 5f32, 0f32);
+    let d = make_point(0f32, 5f32, 0f32);
+    let e = make_point(30f32, 5f32, 0f32);
 
     let (f1, f2) = perpendicular_intersect(&pathfinder.origin, &d, &e, &ob);
     assert!(f1.is_none());
@@ -307,8 +294,8 @@ fn circular_intersection() {
 
     //Check intersections of line from (0,5) to (15,5) with circle of radius 5 centered at (15,0)
     //intersects at 1 point, should be considered valid
-    let g = Point::new(10f32, -5f32, 0f32);
-    let h = Point::new(10f32, 5f32, 0f32);
+    let g = make_point(10f32, -5f32, 0f32);
+    let h = make_point(10f32, 5f32, 0f32);
 
     let (i1, i2) = perpendicular_intersect(&pathfinder.origin, &g, &h, &ob);
     assert!(i1.is_none());
@@ -318,8 +305,8 @@ fn circular_intersection() {
     assert!(i2.is_none());
 
     //should intersect at two points
-    let j = Point::new(8f32, -2f32, 0f32);
-    let k = Point::new(16f32, 6f32, 0f32);
+    let j = make_point(8f32, -2f32, 0f32);
+    let k = make_point(16f32, 6f32, 0f32);
 
     let (l1, l2) = perpendicular_intersect(&pathfinder.origin, &j, &k, &ob);
     assert!(l1.is_some());
@@ -332,8 +319,8 @@ fn circular_intersection() {
     assert_eqp!(l2.unwrap().x, 15f32, 0.0001);
 
     //should intersect at two points
-    let m = Point::new(8f32, 4f32, 0f32);
-    let n = Point::new(30f32, -6f32, 0f32);
+    let m = make_point(8f32, 4f32, 0f32);
+    let n = make_point(30f32, -6f32, 0f32);
 
     let (o1, o2) = perpendicular_intersect(&pathfinder.origin, &m, &n, &ob);
     assert_eqp!(o1.unwrap().x, 10.807f32, 0.001);
@@ -346,14 +333,14 @@ fn circular_intersection() {
 #[test]
 fn obstacle_flyover() {
     //Graphical Visualization: https://www.geogebra.org/3d/a55hmxfy
-    let a = Point::new(10f32, 10f32, 10f32);
-    let b = Point::new(10f32, 40f32, 10f32);
-    let c = Point::new(10f32, 30f32, 30f32);
-
-    let d = Point::new(10f32, 40f32, 25f32);
-    let e = Point::new(10f32, 10f32, 25f32);
-    let f = Point::new(20f32, 40f32, 30f32);
-    let g = Point::new(10f32, 30f32, 40f32);
+    let a = make_point(10f32, 10f32, 10f32);
+    let b = make_point(10f32, 40f32, 10f32);
+    let c = make_point(10f32, 30f32, 30f32);
+
+    let d = make_point(10f32, 40f32, 25f32);
+    let e = make_point(10f32, 10f32, 25f32);
+    let f = make_point(20f32, 40f32, 30f32);
+    let g = make_point(10f32, 30f32, 40f32);
     let ob = obstacle_from_meters(10f32, 25f32, 5f32, 20f32);
     let obstacles = vec![ob];
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
@@ -373,10 +360,10 @@ fn obstacle_flyover() {
 #[test]
 // #TODO: verify node location
 fn generate_graph_test() {
-    let a = Point::new(40f32, 0f32, 0f32);
-    let b = Point::new(40f32, 40f32, 0f32);
-    let c = Point::new(0f32, 40f32, 0f32);
-    let d = Point::new(0f32, 0f32, 0f32);
+    let a = make_point(40f32, 0f32, 0f32);
+    let b = make_point(40f32, 40f32, 0f32);
+    let c = make_point(0f32, 40f32, 0f32);
+    let d = make_point(0f32, 0f32, 0f32);
     let flyzones = vec![points_to_flyzone(vec![a, b, c, d])];
     let obstacles = vec![
         obstacle_from_meters(10f32, 20f32, 10f32, 10f32),
@@ -390,8 +377,8 @@ fn generate_graph_test() {
 fn same_radius_test() {
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
 
-    let n1 = Node::new(Point::new(30_f32, 30_f32, 0_f32), 1_f32, 0_f32);
-    let n2 = Node::new(Point::new(20_f32, 30_f32, 0_f32), 1_f32, 0_f32);
+    let n1 = make_node(30_f32, 30_f32, 1_f32, 0_f32);
+    let n2 = make_node(20_f32, 30_f32, 1_f32, 0_f32);
     let a1 = Rc::new(n1);
     let b1 = Rc::new(n2);
     let expected = vec![
@@ -407,8 +394,8 @@ fn same_radius_test() {
 // https://www.geogebra.org/graphing/nkjxtwrx
 fn same_radius_offset_test() {
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
-    let n1 = Node::new(Point::new(20_f32, 20_f32, 0_f32), 5_f32, 0_f32);
-    let n2 = Node::new(Point::new(30_f32, 30_f32, 0_f32), 5_f32, 0_f32);
+    let n1 = make_node(20_f32, 20_f32, 5_f32, 0_f32);
+    let n2 = make_node(30_f32, 30_f32, 5_f32, 0_f32);
     let a1 = Rc::new(n1);
     let b1 = Rc::new(n2);
 
@@ -430,8 +417,8 @@ fn same_radius_offset_test() {
 #[test]
 fn overlap_test() {
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
-    let n3 = Node::new(Point::new(15_f32, 10_f32, 0_f32), 5_f32, 0_f32);
-    let n4 = Node::new(Point::new(20_f32, 10_f32, 0_f32), 4_f32, 0_f32);
+    let n3 = make_node(15_f32, 10_f32, 5_f32, 0_f32);
+    let n4 = make_node(20_f32, 10_f32, 4_f32, 0_f32);
     let c = Rc::new(n3);
     let d = Rc::new(n4);
     let expected = vec![
@@ -444,8 +431,8 @@ fn overlap_test() {
 #[test]
 fn sentinel_test() {
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
-    let n3 = Node::new(Point::new(15_f32, 10_f32, 0_f32), 5_f32, 0_f32);
-    let n4 = Node::new(Point::new(20_f32, 10_f32, 0_f32), 5_f32, 0_f32);
+    let n3 = make_node(15_f32, 10_f32, 5_f32, 0_f32);
+    let n4 = make_node(20_f32, 10_f32, 5_f32, 0_f32);
     let c = Rc::new(n3);
     let d = Rc::new(n4);
     let expected = vec![
@@ -461,8 +448,8 @@ fn sentinel_test() {
 #[test]
 fn different_radius_no_overlap_test() {
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
-    let n5 = Node::new(Point::new(20_f32, 10_f32, 0_f32), 2_f32, 0_f32);
-    let n6 = Node::new(Point::new(12_f32, 10_f32, 0_f32), 1_f32, 0_f32);
+    let n5 = make_node(20_f32, 10_f32, 2_f32, 0_f32);
+    let n6 = make_node(12_f32, 10_f32, 1_f32, 0_f32);
     let e = Rc::new(n5);
     let f = Rc::new(n6);
     let expected = vec![
@@ -474,6 +461,39 @@ fn different_radius_no_overlap_test() {
     assert_vec4_eqp(&pathfinder.find_path(&e, &f).0, &expected);
 }
 
+#[test]
+// tangents stay tangent to both nodes whichever quadrant the second node is in
+fn find_path_quadrants_test() {
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
+    let a = make_node(200f32, 200f32, 20f32, 0f32);
+    for step in 0..16 {
+        let theta = step as f32 * PI / 8f32;
+        let b = Node::new(
+            make_point(
+                200f32 + 100f32 * theta.cos(),
+                200f32 + 100f32 * theta.sin(),
+                0f32,
+            ),
+            10f32,
+            0f32,
+        );
+        let (paths, sentinels) = pathfinder.find_path(&a, &b);
+        assert_eq!(paths.len(), 4);
+        assert!(sentinels.is_none());
+        for (alpha, beta, dist, _) in paths {
+            assert!(alpha > -2f32 * PI && alpha < 2f32 * PI);
+            assert!(beta > -2f32 * PI && beta < 2f32 * PI);
+            let p1 = Point::from((&a, alpha));
+            let p2 = Point::from((&b, beta));
+            assert_eqp!(p1.distance(&p2), dist, 0.01);
+            // the tangent is perpendicular to the radius at both ends
+            let (dx, dy) = ((p2.x - p1.x) / dist, (p2.y - p1.y) / dist);
+            assert!(((p1.x - a.origin.x) * dx + (p1.y - a.origin.y) * dy).abs() < 0.01);
+            assert!(((p2.x - b.origin.x) * dx + (p2.y - b.origin.y) * dy).abs() < 0.01);
+        }
+    }
+}
+
 /*
 #[test]
 //all tangents are flying over an obstacle. returns threshold appropriately.
@@ -481,8 +501,8 @@ fn different_radius_no_overlap_test() {
 fn different_radius_no_overlap_all_flyover_test() {
     let obs = obstacle_from_meters(16f32, 10f32, 1.8f32, 20f32);
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), vec![obs]);
-    let n5 = Node::new(Point::new(20_f32, 10_f32, 30_f32), 2_f32, 0_f32);
-    let n6 = Node::new(Point::new(12_f32, 10_f32, 30_f32), 1_f32, 0_f32);
+    let n5 = Node::new(make_point(20_f32, 10_f32, 30_f32), 2_f32, 0_f32);
+    let n6 = Node::new(make_point(12_f32, 10_f32, 30_f32), 1_f32, 0_f32);
     let e = Rc::new(n5);
     let f = Rc::new(n6);
     let expected = vec![
@@ -521,8 +541,8 @@ fn different_radius_no_overlap_all_flyover_test() {
 fn different_radius_no_overlap_one_flyover_test() {
     let obs = obstacle_from_meters(16f32, 12f32, 1f32, 20f32);
     let pathfinder = Tanstar::create(1f32, dummy_flyzones(), vec![obs]);
-    let n5 = Node::new(Point::new(20_f32, 10_f32, 30_f32), 2_f32, 0_f32);
-    let n6 = Node::new(Point::new(12_f32, 10_f32, 30_f32), 1_f32, 0_f32);
+    let n5 = Node::new(make_point(20_f32, 10_f32, 30_f32), 2_f32, 0_f32);
+    let n6 = Node::new(make_point(12_f32, 10_f32, 30_f32), 1_f32, 0_f32);
     let e = Rc::new(n5);
     let f = Rc::new(n6);
     let expected = vec![
@@ -558,16 +578,16 @@ fn different_radius_no_overlap_one_flyover_test() {
 #[test]
 fn virtualize_flyzone_square() {
     let origin = Location::from_degrees(0f64, 0f64, 0f32);
-    let a = Location::from((&Point::new(0f32, 0f32, 10f32), &origin));
-    let b = Location::from((&Point::new(20f32, 0f32, 10f32), &origin));
-    let c = Location::from((&Point::new(20f32, 20f32, 10f32), &origin));
-    let d = Location::from((&Point::new(0f32, 20f32, 10f32), &origin));
+    let a = Location::from((&make_point(0f32, 0f32, 10f32), &origin));
+    let b = Location::from((&make_point(20f32, 0f32, 10f32), &origin));
+    let c = Location::from((&make_point(20f32, 20f32, 10f32), &origin));
+    let d = Location::from((&make_point(0f32, 20f32, 10f32), &origin));
     let test_flyzone = vec![vec![d, c, b, a]];
     let mut pathfinder = Tanstar::create(1f32, test_flyzone, Vec::new());
-    let node_a = Point::new(5f32, 5f32, 0f32);
-    let node_b = Point::new(15f32, 5f32, 0f32);
-    let node_c = Point::new(15f32, 15f32, 0f32);
-    let node_d = Point::new(5f32, 15f32, 0f32);
+    let node_a = make_point(6f32, 6f32, 0f32);
+    let node_b = make_point(14f32, 6f32, 0f32);
+    let node_c = make_point(14f32, 14f32, 0f32);
+    let node_d = make_point(6f32, 14f32, 0f32);
     let expected = vec![node_d, node_c, node_b, node_a];
     for i in 0..4 {
         assert_point_eq(&pathfinder.nodes[i].borrow().origin, &expected[i]);
@@ -582,46 +602,46 @@ fn virtualize_flyzone_square() {
 #[test]
 fn virtualize_flyzone_plus() {
     let origin = Location::from_degrees(0f64, 0f64, 0f32);
-    let a = Location::from((&Point::new(20f32, 0f32, 10f32), &origin));
-    let b = Location::from((&Point::new(40f32, 0f32, 10f32), &origin));
-    let c = Location::from((&Point::new(40f32, 20f32, 10f32), &origin));
-    let d = Location::from((&Point::new(60f32, 20f32, 10f32), &origin));
-    let e = Location::from((&Point::new(60f32, 40f32, 10f32), &origin));
-    let f = Location::from((&Point::new(40f32, 40f32, 10f32), &origin));
-    let g = Location::from((&Point::new(40f32, 60f32, 10f32), &origin));
-    let h = Location::from((&Point::new(20f32, 60f32, 10f32), &origin));
-    let i = Location::from((&Point::new(20f32, 40f32, 10f32), &origin));
-    let j = Location::from((&Point::new(0f32, 40f32, 10f32), &origin));
-    let k = Location::from((&Point::new(0f32, 20f32, 10f32), &origin));
-    let l = Location::from((&Point::new(20f32, 20f32, 10f32), &origin));
+    let a = Location::from((&make_point(20f32, 0f32, 10f32), &origin));
+    let b = Location::from((&make_point(40f32, 0f32, 10f32), &origin));
+    let c = Location::from((&make_point(40f32, 20f32, 10f32), &origin));
+    let d = Location::from((&make_point(60f32, 20f32, 10f32), &origin));
+    let e = Location::from((&make_point(60f32, 40f32, 10f32), &origin));
+    let f = Location::from((&make_point(40f32, 40f32, 10f32), &origin));
+    let g = Location::from((&make_point(40f32, 60f32, 10f32), &origin));
+    let h = Location::from((&make_point(20f32, 60f32, 10f32), &origin));
+    let i = Location::from((&make_point(20f32, 40f32, 10f32), &origin));
+    let j = Location::from((&make_point(0f32, 40f32, 10f32), &origin));
+    let k = Location::from((&make_point(0f32, 20f32, 10f32), &origin));
+    let l = Location::from((&make_point(20f32, 20f32, 10f32), &origin));
     let test_flyzone = vec![vec![l, k, j, i, h, g, f, e, d, c, b, a]];
     let mut pathfinder = Tanstar::create(1f32, test_flyzone, Vec::new());
-    let node_a = Point::new(25f32, 5f32, 0f32);
-    let node_b = Point::new(35f32, 5f32, 0f32);
-    let node_c = Point::new(
-        40f32 + (25f32 / 2f32).sqrt(),
-        20f32 - (25f32 / 2f32).sqrt(),
+    let node_a = make_point(26f32, 6f32, 0f32);
+    let node_b = make_point(34f32, 6f32, 0f32);
+    let node_c = make_point(
+        39f32 + (25f32 / 2f32).sqrt(),
+        21f32 - (25f32 / 2f32).sqrt(),
         0f32,
     );
-    let node_d = Point::new(55f32, 25f32, 0f32);
-    let node_e = Point::new(55f32, 35f32, 0f32);
-    let node_f = Point::new(
-        40f32 + (25f32 / 2f32).sqrt(),
-        40f32 + (25f32 / 2f32).sqrt(),
+    let node_d = make_point(54f32, 26f32, 0f32);
+    let node_e = make_point(54f32, 34f32, 0f32);
+    let node_f = make_point(
+        39f32 + (25f32 / 2f32).sqrt(),
+        39f32 + (25f32 / 2f32).sqrt(),
         0f32,
     );
-    let node_g = Point::new(35f32, 55f32, 0f32);
-    let node_h = Point::new(25f32, 55f32, 0f32);
-    let node_i = Point::new(
-        20f32 - (25f32 / 2f32).sqrt(),
-        40f32 + (25f32 / 2f32).sqrt(),
+    let node_g = make_point(34f32, 54f32, 0f32);
+    let node_h = make_point(26f32, 54f32, 0f32);
+    let node_i = make_point(
+        21f32 - (25f32 / 2f32).sqrt(),
+        39f32 + (25f32 / 2f32).sqrt(),
         0f32,
     );
-    let node_j = Point::new(5f32, 35f32, 0f32);
-    let node_k = Point::new(5f32, 25f32, 0f32);
-    let node_l = Point::new(
-        20f32 - (25f32 / 2f32).sqrt(),
-        20f32 - (25f32 / 2f32).sqrt(),
+    let node_j = make_point(6f32, 34f32, 0f32);
+    let node_k = make_point(6f32, 26f32, 0f32);
+    let node_l = make_point(
+        21f32 - (25f32 / 2f32).sqrt(),
+        21f32 - (25f32 / 2f32).sqrt(),
         0f32,
     );
     let expected = vec![
@@ -641,17 +661,17 @@ fn virtualize_flyzone_plus() {
 #[test]
 fn virtualize_flyzone_linear() {
     let origin = Location::from_degrees(0f64, 0f64, 0f32);
-    let a = Location::from((&Point::new(0f32, 0f32, 10f32), &origin));
-    let b = Location::from((&Point::new(20f32, 0f32, 10f32), &origin));
-    let c = Location::from((&Point::new(20f32, 10f32, 10f32), &origin));
-    let d = Location::from((&Point::new(20f32, 20f32, 10f32), &origin));
-    let e = Location::from((&Point::new(0f32, 20f32, 10f32), &origin));
+    let a = Location::from((&make_point(0f32, 0f32, 10f32), &origin));
+    let b = Location::from((&make_point(20f32, 0f32, 10f32), &origin));
+    let c = Location::from((&make_point(20f32, 10f32, 10f32), &origin));
+    let d = Location::from((&make_point(20f32, 20f32, 10f32), &origin));
+    let e = Location::from((&make_point(0f32, 20f32, 10f32), &origin));
     let test_flyzone = vec![vec![e, d, c, b, a]];
     let mut pathfinder = Tanstar::create(1f32, test_flyzone, Vec::new());
-    let node_a = Point::new(5f32, 5f32, 0f32);
-    let node_b = Point::new(15f32, 5f32, 0f32);
-    let node_c = Point::new(15f32, 15f32, 0f32);
-    let node_d = Point::new(5f32, 15f32, 0f32);
+    let node_a = make_point(6f32, 6f32, 0f32);
+    let node_b = make_point(14f32, 6f32, 0f32);
+    let node_c = make_point(14f32, 14f32, 0f32);
+    let node_d = make_point(6f32, 14f32, 0f32);
     let expected = vec![node_d, node_c, node_b, node_a];
     for i in 0..4 {
         assert_point_eq(&pathfinder.nodes[i].borrow().origin, &expected[i]);
@@ -666,40 +686,74 @@ fn virtualize_flyzone_linear() {
 #[test]
 fn virtualize_flyzone_small_angle() {
     let origin = Location::from_degrees(0f64, 0f64, 0f32);
-    let a = Location::from((&Point::new(10f32, 0f32, 10f32), &origin));
-    let b = Location::from((&Point::new(30f32, 0f32, 10f32), &origin));
-    let c = Location::from((&Point::new(30f32, 20f32, 10f32), &origin));
-    let d = Location::from((&Point::new(10f32, 20f32, 10f32), &origin));
-    let e = Location::from((&Point::new(10f32, 11f32, 10f32), &origin));
-    let f = Location::from((&Point::new(0f32, 10f32, 10f32), &origin));
-    let g = Location::from((&Point::new(10f32, 9f32, 10f32), &origin));
+    let a = Location::from((&make_point(10f32, 0f32, 10f32), &origin));
+    let b = Location::from((&make_point(30f32, 0f32, 10f32), &origin));
+    let c = Location::from((&make_point(30f32, 20f32, 10f32), &origin));
+    let d = Location::from((&make_point(10f32, 20f32, 10f32), &origin));
+    let e = Location::from((&make_point(10f32, 11f32, 10f32), &origin));
+    let f = Location::from((&make_point(0f32, 10f32, 10f32), &origin));
+    let g = Location::from((&make_point(10f32, 9f32, 10f32), &origin));
     let test_flyzone = vec![vec![g, f, e, d, c, b, a]];
     let mut pathfinder = Tanstar::create(1f32, test_flyzone, Vec::new());
-    let node_a = Point::new(15f32, 5f32, 0f32);
-    let node_b = Point::new(25f32, 5f32, 0f32);
-    let node_c = Point::new(25f32, 15f32, 0f32);
-    let node_d = Point::new(15f32, 15f32, 0f32);
-    let node_f = Point::new(6.2927, 5.6450, 0f32);
-    let node_e = Point::new(6.2927, 14.3550, 0f32);
-    let expected = vec![node_f, node_e, node_d, node_c, node_b, node_a];
-    for i in 0..6 {
+    // the notch is as narrow as twice the buffer, so its tip collapses when the boundary is
+    // inset and only the corners of the inset rectangle get virtual nodes
+    let inset = offset_polygon(
+        &[g, f, e, d, c, b, a]
+            .iter()
+            .map(|location| Point::from((location, &origin)))
+            .collect::<Vec<Point>>(),
+        1f32,
+    )
+    .unwrap();
+    assert_eq!(inset.len(), 6);
+    assert!(inset.iter().all(|point| point.x >= 11f32 - 0.001));
+    let node_a = make_point(16f32, 6f32, 0f32);
+    let node_b = make_point(24f32, 6f32, 0f32);
+    let node_c = make_point(24f32, 14f32, 0f32);
+    let node_d = make_point(16f32, 14f32, 0f32);
+    let expected = [node_d, node_c, node_b, node_a];
+    assert_eq!(pathfinder.nodes.len(), 4);
+    for i in 0..4 {
         assert_point_eq(&pathfinder.nodes[i].borrow().origin, &expected[i]);
     }
     let test_flyzone = vec![vec![a, b, c, d, e, f, g]];
     pathfinder.set_flyzone(test_flyzone);
-    for i in 0..6 {
+    assert_eq!(pathfinder.nodes.len(), 4);
+    for i in 0..4 {
         assert_point_eq(&pathfinder.nodes[i].borrow().origin, &expected[i]);
     }
+
+    // without a buffer the notch keeps its virtual nodes
+    let config = TConfig {
+        buffer_size: 0f32,
+        ..TConfig::default()
+    };
+    let mut pathfinder = Tanstar::new();
+    pathfinder.init(config, vec![vec![g, f, e, d, c, b, a]], Vec::new());
+    let node_f = make_point(6.2927, 5.6450, 0f32);
+    let node_e = make_point(6.2927, 14.3550, 0f32);
+    let expected = [
+        node_f,
+        node_e,
+        make_point(15f32, 15f32, 0f32),
+        make_point(25f32, 15f32, 0f32),
+        make_point(25f32, 5f32, 0f32),
+        make_point(15f32, 5f32, 0f32),
+    ];
+    assert_eq!(pathfinder.nodes.len(), 6);
+    for (node, point) in pathfinder.nodes.iter().zip(expected.iter()) {
+        assert_point_eq(&node.borrow().origin, point);
+    }
 }
 
 /*#[test]
 fn sentinel_vertex_test() {
-    let a = Point::new(0f32, 0f32, 0f32).to_location(&origin);
-    let b = Point::new(0f32, 5f32, 0f32).to_location(&origin);
-    let a = Point::new(5f32, 0f32, 0f32).to_location(&origin);
+    let a = make_point(0f32, 0f32, 0f32).to_location(&origin);
+    let b = make_point(0f32, 5f32, 0f32).to_location(&origin);
+    let a = make_point(5f32, 0f32, 0f32).to_location(&origin);
     let test_flyzone = vec![vec![a, b, c]];
     let mut pathfinder = Tanstar::create(1f32, test_flyzone, Vec::new());
-    let origin = Point::new(4f32, 1f32, 0f32);
+    let origin = make_point(4f32, 1f32, 0f32);
     let node = Node::new(origin, 1f32, 2f32);
 
     vertex_al = (3/2*PI, None);
@@ -707,3 +761,1108 @@ fn sentinel_vertex_test() {
     vertex.angle =
     let expected_vertices = []
 }*/
+
+#[test]
+fn flyzone_perimeter_test() {
+    let a = make_point(0f32, 0f32, 10f32);
+    let b = make_point(0f32, 100f32, 10f32);
+    let c = make_point(100f32, 100f32, 10f32);
+    let d = make_point(100f32, 0f32, 10f32);
+    let flyzones = vec![points_to_flyzone(vec![a, b, c, d])];
+    let pathfinder = Tanstar::create(1f32, flyzones, Vec::new());
+    assert_eqp!(pathfinder.flyzone_perimeter(0).unwrap(), 400f32, THRESHOLD);
+    assert_eq!(
+        pathfinder.flyzone_perimeter(1),
+        Err(PathfinderError::InvalidFlyzoneIndex { index: 1 })
+    );
+}
+
+#[test]
+fn nearest_flyzone_boundary_point_test() {
+    let pathfinder = dummy_pathfinder();
+    let nearest = |x, y| {
+        let location = Location::from((&make_point(x, y, 30f32), &pathfinder.origin));
+        let boundary = pathfinder.find_nearest_flyzone_boundary_point(&location);
+        let point = Point::from((&boundary, &pathfinder.origin));
+        assert_eq!(boundary.alt(), 30f32);
+        point
+    };
+    // interior points move to the closest edge
+    assert_point_eq(&nearest(50f32, 200f32), &make_point(0f32, 200f32, 30f32));
+    assert_point_eq(&nearest(200f32, 380f32), &make_point(200f32, 400f32, 30f32));
+    // exterior points move back onto the boundary
+    assert_point_eq(&nearest(450f32, 100f32), &make_point(400f32, 100f32, 30f32));
+    assert_point_eq(&nearest(-20f32, -30f32), &make_point(0f32, 0f32, 30f32));
+}
+
+#[test]
+fn obstacles_blocking_direct_path_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 30f32),
+        obstacle_from_meters(200f32, 240f32, 20f32, 30f32),
+        obstacle_from_meters(300f32, 300f32, 30f32, 80f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let location = |x, y| Location::from((&make_point(x, y, 10f32), &pathfinder.origin));
+    assert_eq!(
+        pathfinder.obstacles_blocking_direct_path(location(50f32, 50f32), location(350f32, 350f32)),
+        vec![0, 2]
+    );
+    assert_eq!(
+        pathfinder
+            .obstacles_blocking_direct_path(location(200f32, 20f32), location(200f32, 380f32)),
+        vec![1]
+    );
+    assert!(pathfinder
+        .obstacles_blocking_direct_path(location(20f32, 380f32), location(380f32, 380f32))
+        .is_empty());
+}
+
+#[test]
+fn obstacle_group_convex_hull_test() {
+    // diamond of four obstacles with an unrelated one far away
+    let obstacles = vec![
+        obstacle_from_meters(200f32, 150f32, 20f32, 30f32),
+        obstacle_from_meters(250f32, 200f32, 20f32, 30f32),
+        obstacle_from_meters(200f32, 250f32, 20f32, 30f32),
+        obstacle_from_meters(150f32, 200f32, 20f32, 30f32),
+        obstacle_from_meters(50f32, 50f32, 10f32, 30f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let hull = pathfinder
+        .obstacle_group_convex_hull(&[0, 1, 2, 3])
+        .unwrap();
+    assert!(polygon_area(&hull) > 0f32);
+    for obstacle in &pathfinder.obstacles[..4] {
+        let center = Point::from((&obstacle.location, &pathfinder.origin));
+        assert!(point_in_polygon(&center, &hull));
+        for i in 0..hull.len() {
+            let edge = (&hull[i], &hull[(i + 1) % hull.len()]);
+            assert!(point_to_segment_distance_2d(&center, edge.0, edge.1) >= obstacle.radius);
+        }
+    }
+    let far = Point::from((&pathfinder.obstacles[4].location, &pathfinder.origin));
+    assert!(!point_in_polygon(&far, &hull));
+
+    assert_eq!(
+        pathfinder.obstacle_group_convex_hull(&[0, 5]).err(),
+        Some(PathfinderError::InvalidObstacleIndex { index: 5 })
+    );
+}
+
+#[test]
+fn min_vertex_angle_separation_test() {
+    let obstacles = || {
+        vec![
+            obstacle_from_meters(100f32, 100f32, 20f32, 30f32),
+            obstacle_from_meters(200f32, 250f32, 30f32, 30f32),
+            obstacle_from_meters(300f32, 120f32, 25f32, 30f32),
+            obstacle_from_meters(120f32, 300f32, 20f32, 30f32),
+        ]
+    };
+    let count_vertices = |pathfinder: &Tanstar| -> usize {
+        pathfinder
+            .nodes
+            .iter()
+            .map(|node| node.borrow().vertices().len())
+            .sum()
+    };
+    let dense = Tanstar::create(1f32, dummy_flyzones(), obstacles());
+    // no merging by default
+    let mut pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles());
+    pathfinder.set_min_vertex_angle_separation(0f32);
+    pathfinder.build_graph();
+    assert_eq!(count_vertices(&pathfinder), count_vertices(&dense));
+
+    pathfinder.set_min_vertex_angle_separation(30f32);
+    pathfinder.build_graph();
+    assert!(count_vertices(&pathfinder) < count_vertices(&dense));
+    for node in &pathfinder.nodes {
+        let node = node.borrow();
+        let mut angles: Vec<f32> = node
+            .vertices()
+            .iter()
+            .filter(|vertex| !vertex.borrow().sentinel)
+            .map(|vertex| vertex.borrow().angle)
+            .collect();
+        angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in angles.windows(2) {
+            assert!(pair[1] - pair[0] >= 30f32.to_radians() - THRESHOLD as f32);
+        }
+    }
+    let start = Location::from((&make_point(30f32, 30f32, 50f32), &pathfinder.origin));
+    let end = Location::from((&make_point(370f32, 370f32, 50f32), &pathfinder.origin));
+    assert!(pathfinder.find_shortest_path::<()>(start, end).is_ok());
+}
+
+#[test]
+fn find_path_rrt_test() {
+    let obstacles = vec![
+        obstacle_from_meters(200f32, 200f32, 60f32, 100f32),
+        obstacle_from_meters(100f32, 300f32, 30f32, 100f32),
+    ];
+    let mut pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let location = |x, y| Location::from((&make_point(x, y, 50f32), &pathfinder.origin));
+    let (start, goal) = (location(50f32, 50f32), location(350f32, 350f32));
+
+    let path = pathfinder.find_path_rrt(start, goal, 5000).unwrap();
+    assert_eq!(path[0], start);
+    assert_eq!(path[path.len() - 1], goal);
+    // the direct line crosses the tall obstacle, so the path must go around it
+    assert!(path.len() > 2);
+    for leg in path.windows(2) {
+        let a = Point::from((&leg[0], &pathfinder.origin));
+        let b = Point::from((&leg[1], &pathfinder.origin));
+        assert!(pathfinder.can_fly_between(&a, &b, 50f32));
+    }
+    // same query gives the same path
+    assert_eq!(pathfinder.find_path_rrt(start, goal, 5000).unwrap(), path);
+
+    assert_eq!(
+        pathfinder.find_path_rrt(start, goal, 0),
+        Err(PathfinderError::NoPathFound)
+    );
+}
+
+#[test]
+fn bounding_box_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 30f32),
+        obstacle_from_meters(390f32, 250f32, 30f32, 80f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let (min, max) = pathfinder.bounding_box();
+    let contains = |location: &Location| {
+        location.lat() >= min.lat() - 1e-12
+            && location.lat() <= max.lat() + 1e-12
+            && location.lon() >= min.lon() - 1e-12
+            && location.lon() <= max.lon() + 1e-12
+            && location.alt() >= min.alt()
+            && location.alt() <= max.alt()
+    };
+    for location in &pathfinder.flyzones[0] {
+        assert!(contains(location));
+    }
+    for obstacle in &pathfinder.obstacles {
+        let node = Node::from((obstacle, &pathfinder.origin, 0f32));
+        for point in node.to_polygon(64) {
+            assert!(contains(&Location::from((&point, &pathfinder.origin))));
+        }
+    }
+    // the second obstacle reaches past the east edge of the flyzone
+    let east = Location::from((&make_point(420f32, 250f32, 0f32), &pathfinder.origin));
+    assert!((max.lon() - east.lon()).abs() < 1e-9);
+    assert_eq!(min.alt(), 0f32);
+    assert_eq!(max.alt(), 80f32);
+}
+
+#[test]
+fn flyzone_centroid_test() {
+    // unit square
+    let a = make_point(0f32, 0f32, 0f32);
+    let b = make_point(0f32, 1f32, 0f32);
+    let c = make_point(1f32, 1f32, 0f32);
+    let d = make_point(1f32, 0f32, 0f32);
+    let mut pathfinder = Tanstar::create(1f32, vec![points_to_flyzone(vec![a, b, c, d])], vec![]);
+    let expected = make_point(0.5f32, 0.5f32, 0f32);
+    assert_point_eq(&pathfinder.compute_flyzone_centroid(0).unwrap(), &expected);
+
+    // L-shape made of a 20x10 and a 10x10 rectangle
+    let l_shape = vec![
+        make_point(0f32, 0f32, 0f32),
+        make_point(20f32, 0f32, 0f32),
+        make_point(20f32, 10f32, 0f32),
+        make_point(10f32, 10f32, 0f32),
+        make_point(10f32, 20f32, 0f32),
+        make_point(0f32, 20f32, 0f32),
+    ];
+    pathfinder.set_flyzone(vec![points_to_flyzone(l_shape)]);
+    let expected = make_point(25f32 / 3f32, 25f32 / 3f32, 0f32);
+    assert_point_eq(&pathfinder.compute_flyzone_centroid(0).unwrap(), &expected);
+
+    // triangle
+    let triangle = vec![
+        make_point(0f32, 0f32, 0f32),
+        make_point(30f32, 0f32, 0f32),
+        make_point(0f32, 30f32, 0f32),
+    ];
+    pathfinder.set_flyzone(vec![points_to_flyzone(triangle)]);
+    let expected = make_point(10f32, 10f32, 0f32);
+    assert_point_eq(&pathfinder.compute_flyzone_centroid(0).unwrap(), &expected);
+
+    assert!(pathfinder.compute_flyzone_centroid(1).is_err());
+}
+
+// Count the number of edges and sentinels in the graph
+fn graph_summary(pathfinder: &Tanstar) -> (usize, usize) {
+    let mut edges = 0;
+    let mut sentinels = 0;
+    for node in &pathfinder.nodes {
+        for vertex in node.borrow().vertices() {
+            edges += vertex.borrow().connection.len();
+            if vertex.borrow().sentinel {
+                sentinels += 1;
+            }
+        }
+    }
+    (edges, sentinels)
+}
+
+#[test]
+fn set_obstacle_position_test() {
+    // obstacles start overlapping, generating sentinels
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(120f32, 100f32, 20f32, 10f32),
+    ];
+    let mut pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let moved = Location::from_meters(300f32, 250f32, 10f32, &origin());
+    pathfinder
+        .set_obstacle_position(1, moved.lat_degree(), moved.lon_degree(), 10f32)
+        .unwrap();
+    assert_point_eq(
+        &pathfinder.nodes[1].borrow().origin,
+        &make_point(300f32, 250f32, 10f32),
+    );
+    pathfinder.update_dirty_nodes();
+
+    let expected = Tanstar::create(
+        1f32,
+        dummy_flyzones(),
+        vec![
+            obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+            obstacle_from_meters(300f32, 250f32, 20f32, 10f32),
+        ],
+    );
+    assert_eq!(graph_summary(&pathfinder), graph_summary(&expected));
+    assert_eq!(
+        pathfinder.set_obstacle_position(2, 0f64, 0f64, 0f32),
+        Err(PathfinderError::InvalidObstacleIndex { index: 2 })
+    );
+}
+
+#[test]
+fn ring_length_test() {
+    let mut num_vertices = 0;
+    let mut node = make_node(10f32, 10f32, 5f32, 0f32);
+    // header only
+    assert_eq!(node.left_ring.borrow().ring_length(), Some(1));
+
+    let v = Rc::new(RefCell::new(Vertex::new(
+        &mut num_vertices,
+        &node,
+        PI,
+        vec![],
+    )));
+    node.insert_vertex(v.clone());
+    assert_eq!(node.left_ring.borrow().ring_length(), Some(2));
+    assert_eq!(v.borrow().ring_length(), Some(2));
+
+    for angle in &[PI / 4f32, PI / 2f32] {
+        let v = Rc::new(RefCell::new(Vertex::new(
+            &mut num_vertices,
+            &node,
+            *angle,
+            vec![],
+        )));
+        node.insert_vertex(v);
+    }
+    assert_eq!(node.left_ring.borrow().ring_length(), Some(4));
+    assert_eq!(node.right_ring.borrow().ring_length(), Some(1));
+
+    // chain looping on itself without returning to the header
+    v.borrow_mut().next = Some(v.clone());
+    assert_eq!(node.left_ring.borrow().ring_length(), None);
+    assert_eq!(node.left_ring.borrow().ring_length_within(100), None);
+    // chain ending in null
+    v.borrow_mut().next = None;
+    assert_eq!(node.left_ring.borrow().ring_length(), None);
+    assert_eq!(node.left_ring.borrow().ring_length_within(100), None);
+}
+
+#[test]
+fn validate_graph_test() {
+    let obstacles = vec![obstacle_from_meters(100f32, 100f32, 20f32, 10f32)];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    assert_eq!(pathfinder.validate_graph(), Ok(()));
+
+    let first = pathfinder.nodes[0].borrow().vertices()[0].clone();
+    first.borrow_mut().next = Some(first.clone());
+    assert_eq!(
+        pathfinder.validate_graph(),
+        Err(PathfinderError::BrokenRing { node: 0 })
+    );
+}
+
+#[test]
+fn obstacle_centroids_test() {
+    let centers = [(100f32, 100f32), (200f32, 350f32), (320f32, 40f32)];
+    let obstacles = centers
+        .iter()
+        .map(|&(x, y)| obstacle_from_meters(x, y, 10f32, 10f32))
+        .collect();
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let centroids = pathfinder.obstacle_centroids();
+    assert_eq!(centroids.len(), centers.len());
+    for (centroid, &(x, y)) in centroids.iter().zip(centers.iter()) {
+        assert_eqp!(centroid[0], x, 0.01);
+        assert_eqp!(centroid[1], y, 0.01);
+    }
+    assert_eq!(pathfinder.obstacle_labels(), ["obs_0", "obs_1", "obs_2"]);
+
+    let pathfinder = dummy_pathfinder();
+    assert!(pathfinder.obstacle_centroids().is_empty());
+    assert!(pathfinder.obstacle_labels().is_empty());
+}
+
+#[test]
+fn obstacles_within_radius_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(200f32, 100f32, 10f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 30f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let center = Location::from_meters(100f32, 100f32, 0f32, &origin());
+    // center inside the first obstacle
+    assert_eq!(pathfinder.obstacles_within_radius(&center, 0.1f32), vec![0]);
+    // second obstacle boundary is 90 meters away
+    assert_eq!(pathfinder.obstacles_within_radius(&center, 89f32), vec![0]);
+    assert_eq!(
+        pathfinder.obstacles_within_radius(&center, 91f32),
+        vec![0, 1]
+    );
+    assert_eq!(
+        pathfinder.obstacles_within_radius(&center, 500f32),
+        vec![0, 1, 2]
+    );
+
+    let center = Location::from_meters(350f32, 50f32, 0f32, &origin());
+    assert!(pathfinder
+        .obstacles_within_radius(&center, 10f32)
+        .is_empty());
+}
+
+#[test]
+fn segment_flyover_altitude_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 30f32),
+        obstacle_from_meters(200f32, 100f32, 20f32, 60f32),
+        obstacle_from_meters(300f32, 100f32, 20f32, 40f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let location = |x, y| Location::from_meters(x, y, 10f32, &origin());
+
+    // clear of every obstacle
+    assert_eq!(
+        pathfinder.segment_flyover_altitude(&location(50f32, 300f32), &location(350f32, 300f32)),
+        0f32
+    );
+    // single obstacle
+    assert_eq!(
+        pathfinder.segment_flyover_altitude(&location(50f32, 100f32), &location(150f32, 100f32)),
+        30f32
+    );
+    // crosses all three
+    assert_eq!(
+        pathfinder.segment_flyover_altitude(&location(50f32, 100f32), &location(350f32, 100f32)),
+        60f32
+    );
+}
+
+#[test]
+fn is_reachable_from_test() {
+    let mut num_vertices = 0;
+    let node = make_node(0f32, 0f32, 5f32, 0f32);
+    let vertices: Vec<Rc<RefCell<Vertex>>> = (0..5)
+        .map(|i| {
+            Rc::new(RefCell::new(Vertex::new(
+                &mut num_vertices,
+                &node,
+                i as f32,
+                vec![],
+            )))
+        })
+        .collect();
+    let connect = |a: usize, b: usize| {
+        vertices[a]
+            .borrow_mut()
+            .connection
+            .push(Connection::new(vertices[b].clone(), 1f32, 0f32));
+    };
+    // 0 -> 1 -> 2 -> 0 cycle, 3 -> 4 disconnected from the cycle
+    connect(0, 1);
+    connect(1, 2);
+    connect(2, 0);
+    connect(3, 4);
+
+    // directly connected
+    assert!(is_reachable_from(&vertices[0], &vertices[1], 1));
+    // multi hop
+    assert!(is_reachable_from(&vertices[0], &vertices[2], 10));
+    assert!(!is_reachable_from(&vertices[0], &vertices[2], 1));
+    assert!(is_reachable_from(&vertices[1], &vertices[0], 2));
+    // disconnected, and edges are directed
+    assert!(!is_reachable_from(&vertices[0], &vertices[4], 10));
+    assert!(!is_reachable_from(&vertices[4], &vertices[3], 10));
+    assert!(is_reachable_from(&vertices[3], &vertices[4], 10));
+}
+
+#[test]
+fn connection_reciprocal_test() {
+    let mut num_vertices = 0;
+    let node = make_node(0f32, 0f32, 5f32, 0f32);
+    let u = Rc::new(RefCell::new(Vertex::new(
+        &mut num_vertices,
+        &node,
+        0f32,
+        vec![],
+    )));
+    let v = Rc::new(RefCell::new(Vertex::new(
+        &mut num_vertices,
+        &node,
+        PI,
+        vec![],
+    )));
+    let edge = Connection::new(v.clone(), 12.5f32, 20f32);
+
+    let reciprocal = edge.reciprocal(&u);
+    assert_eq!(reciprocal.neighbor, u);
+    assert_eqp!(reciprocal.distance, edge.distance, THRESHOLD);
+    assert_eqp!(reciprocal.threshold, edge.threshold, THRESHOLD);
+
+    let original = reciprocal.reciprocal(&v);
+    assert_eq!(original.neighbor, edge.neighbor);
+    assert_eqp!(original.distance, edge.distance, THRESHOLD);
+    assert_eqp!(original.threshold, edge.threshold, THRESHOLD);
+}
+
+#[test]
+fn obstacle_density_test() {
+    let obstacles = vec![
+        obstacle_from_meters(50f32, 50f32, 10f32, 10f32),
+        obstacle_from_meters(150f32, 50f32, 10f32, 10f32),
+        obstacle_from_meters(150f32, 150f32, 10f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 10f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let location = |x, y| Location::from_meters(x, y, 0f32, &origin());
+    // 200m by 200m square holding 3 obstacles
+    let region = vec![
+        location(0f32, 0f32),
+        location(0f32, 200f32),
+        location(200f32, 200f32),
+        location(200f32, 0f32),
+    ];
+    assert_eqp!(pathfinder.obstacle_density(&region), 75f32, 0.1);
+    // no area
+    let line = vec![location(0f32, 0f32), location(200f32, 200f32)];
+    assert_eq!(pathfinder.obstacle_density(&line), 0f32);
+}
+
+#[test]
+fn geofence_point_node_test() {
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
+    let location = Location::from_meters(20f32, 20f32, 0f32, &origin());
+    let geofence = Node::from_geofence_point(
+        location.lat_degree(),
+        location.lon_degree(),
+        5f32,
+        &origin(),
+    );
+    assert_point_eq(&geofence.origin, &make_point(20f32, 20f32, 0f32));
+    assert_eq!(geofence.radius, 5f32);
+    assert_eq!(geofence.height, 0f32);
+    assert_eq!(geofence.vertices().len(), 0);
+
+    let obstacle = obstacle_from_meters(30f32, 30f32, 5f32, 10f32);
+    let node = Node::from((&obstacle, &origin(), 0f32));
+    let reference = make_node(20f32, 20f32, 5f32, 0f32);
+    let expected = pathfinder.find_path(&reference, &node).0;
+    assert_eq!(expected.len(), 4);
+    assert_vec4_eqp(&pathfinder.find_path(&geofence, &node).0, &expected);
+}
+
+#[test]
+fn connection_count_between_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(200f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(230f32, 100f32, 20f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    // separate obstacles have two outer and two inner tangents
+    assert_eq!(pathfinder.connection_count_between(0, 1), 4);
+    assert_eq!(pathfinder.connection_count_between(1, 0), 4);
+    // overlapping obstacles only have outer tangents
+    assert_eq!(pathfinder.connection_count_between(1, 2), 2);
+    assert_eq!(pathfinder.connection_count_between(2, 1), 2);
+    assert_eq!(pathfinder.connection_count_between(0, 0), 0);
+    assert_eq!(pathfinder.connection_count_between(0, 10), 0);
+}
+
+#[test]
+fn insert_obstacle_between_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 200f32, 20f32, 10f32),
+        obstacle_from_meters(300f32, 200f32, 20f32, 10f32),
+    ];
+    let mut pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let node_count = pathfinder.nodes.len();
+    let virtual_edges = pathfinder.connection_count_between(0, 2);
+    assert_eq!(pathfinder.connection_count_between(0, 1), 4);
+    pathfinder.set_forbidden_edge(0, 2);
+
+    assert_eq!(
+        pathfinder.insert_obstacle_between(
+            0,
+            node_count,
+            obstacle_from_meters(200f32, 200f32, 20f32, 10f32)
+        ),
+        Err(PathfinderError::InvalidNodeIndex { index: node_count })
+    );
+    assert_eq!(pathfinder.nodes.len(), node_count);
+
+    pathfinder
+        .insert_obstacle_between(0, 1, obstacle_from_meters(200f32, 200f32, 20f32, 10f32))
+        .unwrap();
+    assert_eq!(pathfinder.obstacles.len(), 3);
+    assert_eq!(pathfinder.nodes.len(), node_count + 1);
+    assert_eq!(pathfinder.connection_count_between(0, 1), 0);
+    assert_eq!(pathfinder.connection_count_between(0, 2), 4);
+    assert_eq!(pathfinder.connection_count_between(1, 2), 4);
+    // virtual nodes moved up by one, with their edges left in place
+    assert_eq!(pathfinder.connection_count_between(0, 3), virtual_edges);
+    assert!(pathfinder.is_forbidden_edge(0, 3));
+    assert!(!pathfinder.is_forbidden_edge(0, 2));
+    assert!(pathfinder.validate_graph().is_ok());
+}
+
+#[test]
+fn min_spanning_tree_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 4f32, 10f32),
+        obstacle_from_meters(200f32, 100f32, 4f32, 10f32),
+        obstacle_from_meters(100f32, 200f32, 4f32, 10f32),
+        obstacle_from_meters(310f32, 300f32, 4f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    // shortest connection is the inner tangent, sqrt(d^2 - (r1 + r2)^2)
+    let expected = [
+        (0, 1, 9900f32.sqrt()),
+        (0, 2, 9900f32.sqrt()),
+        (1, 3, 52000f32.sqrt()),
+    ];
+    let tree = pathfinder.min_spanning_tree();
+    assert_eq!(tree.len(), expected.len());
+    for (&(a, b, weight), &(x, y, w)) in tree.iter().zip(expected.iter()) {
+        assert_eq!((a, b), (x, y));
+        assert_eqp!(weight, w, THRESHOLD);
+    }
+}
+
+#[test]
+fn estimate_graph_size_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(200f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(230f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 10f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let (nodes, edges) = pathfinder.estimate_graph_size();
+    assert_eq!(nodes, 8);
+    assert_eq!(edges, 8 * 7 / 2 * 8);
+
+    let mut actual_edges = 0;
+    for node in &pathfinder.nodes {
+        for vertex in node.borrow().vertices() {
+            actual_edges += vertex.borrow().connection.len();
+        }
+    }
+    assert!(pathfinder.nodes.len() <= nodes);
+    assert!(actual_edges > 0 && actual_edges <= edges);
+}
+
+#[test]
+fn vertex_csv_row_test() {
+    let mut num_vertices = 0;
+    let node = make_node(10f32, 20f32, 5f32, 0f32);
+    let mut vertex = Vertex::new(&mut num_vertices, &node, 0f32, vec![]);
+    vertex.g_cost = 1.5f32;
+    vertex.f_cost = 2.5f32;
+    assert_eq!(vertex.to_csv_row(), "1,0,15,20,0,1.5,2.5,false");
+}
+
+#[test]
+fn graph_diameter_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 4f32, 10f32),
+        obstacle_from_meters(200f32, 100f32, 4f32, 10f32),
+        obstacle_from_meters(100f32, 300f32, 4f32, 10f32),
+    ];
+    let config = TConfig {
+        buffer_size: 1f32,
+        virtualize_flyzone: false,
+        ..TConfig::default()
+    };
+    let mut pathfinder = Tanstar::new();
+    pathfinder.init(config, dummy_flyzones(), obstacles);
+
+    // obstacles 1 and 2 are connected directly by their inner tangent
+    assert_eqp!(
+        pathfinder.find_graph_diameter().unwrap(),
+        49900f32.sqrt(),
+        THRESHOLD
+    );
+    let floyd_warshall = pathfinder.all_pairs_shortest_paths(true);
+    let dijkstra = pathfinder.all_pairs_shortest_paths(false);
+    for (a, b) in floyd_warshall.iter().zip(dijkstra.iter()) {
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert_eqp!(*x, *y, THRESHOLD);
+        }
+    }
+
+    // obstacle outside of the flyzone cannot be reached
+    let mut obstacles = pathfinder.obstacles.clone();
+    obstacles.push(obstacle_from_meters(500f32, 500f32, 4f32, 10f32));
+    pathfinder.set_obstacles(obstacles);
+    assert_eq!(
+        pathfinder.find_graph_diameter(),
+        Err(PathfinderError::DisconnectedGraph)
+    );
+}
+
+#[test]
+fn find_visibility_graph_test() {
+    // a single obstacle between start and goal has no other node to connect to
+    let obstacles = vec![obstacle_from_meters(200f32, 200f32, 20f32, 10f32)];
+    let config = TConfig {
+        buffer_size: 1f32,
+        virtualize_flyzone: false,
+        ..TConfig::default()
+    };
+    let mut pathfinder = Tanstar::new();
+    pathfinder.init(config, dummy_flyzones(), obstacles);
+    assert!(pathfinder.find_visibility_graph().is_empty());
+
+    // with the flyzone corners as nodes the obstacle sees all of them
+    let obstacles = vec![obstacle_from_meters(200f32, 200f32, 20f32, 10f32)];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let visibility = pathfinder.find_visibility_graph();
+    for corner in 1..pathfinder.nodes.len() {
+        assert!(visibility.contains(&(0, corner)));
+    }
+    for &(a, b) in &visibility {
+        assert!(a < b);
+        assert!(pathfinder.connection_count_between(a, b) > 0);
+    }
+}
+
+#[test]
+fn cost_matrix_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 4f32, 10f32),
+        obstacle_from_meters(300f32, 100f32, 4f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 4f32, 10f32),
+        obstacle_from_meters(100f32, 300f32, 4f32, 10f32),
+    ];
+    let config = TConfig {
+        buffer_size: 1f32,
+        virtualize_flyzone: false,
+        ..TConfig::default()
+    };
+    let mut pathfinder = Tanstar::new();
+    pathfinder.init(config, dummy_flyzones(), obstacles);
+
+    let matrix = pathfinder.cost_matrix().unwrap();
+    assert_eq!(matrix.len(), 4);
+    for (i, row) in matrix.iter().enumerate() {
+        assert_eq!(row.len(), 4);
+        assert_eq!(row[i], 0f32);
+        for (j, distance) in row.iter().enumerate() {
+            assert!(distance.is_finite());
+            assert_eqp!(*distance, matrix[j][i], THRESHOLD);
+        }
+    }
+    // neighbors are connected directly by their inner tangent
+    assert_eqp!(matrix[0][1], 39900f32.sqrt(), THRESHOLD);
+    assert!(matrix[0][2] <= matrix[0][1] + matrix[1][2] + THRESHOLD as f32);
+
+    pathfinder.set_airspeed(20f32);
+    let times = pathfinder.flying_time_matrix().unwrap();
+    for (time_row, row) in times.iter().zip(matrix.iter()) {
+        for (time, distance) in time_row.iter().zip(row.iter()) {
+            assert_eqp!(*time, distance / 20f32, THRESHOLD);
+        }
+    }
+
+    // 5 m/s towards east is a tailwind from node 0 to node 1 and a headwind back
+    pathfinder.set_wind(5f32, 0f32);
+    let times = pathfinder.flying_time_matrix().unwrap();
+    assert!((times[0][1] - matrix[0][1] / 25f32).abs() < 0.05f32);
+    assert!((times[1][0] - matrix[0][1] / 15f32).abs() < 0.05f32);
+    // a crosswind barely changes the time north, the fastest tangent leans slightly downwind
+    assert!(times[1][2] <= matrix[1][2] / 20f32);
+    assert!(times[1][2] > matrix[1][2] / 20.5f32);
+    assert_eq!(times[2][2], 0f32);
+
+    // obstacle outside of the flyzone cannot be reached
+    let mut obstacles = pathfinder.obstacles.clone();
+    obstacles.push(obstacle_from_meters(500f32, 500f32, 4f32, 10f32));
+    pathfinder.set_obstacles(obstacles);
+    let matrix = pathfinder.cost_matrix().unwrap();
+    assert_eq!(matrix[4][4], 0f32);
+    for (i, row) in matrix.iter().enumerate().take(4) {
+        assert_eq!(row[4], f32::INFINITY);
+        assert_eq!(matrix[4][i], f32::INFINITY);
+    }
+}
+
+#[test]
+fn find_bottleneck_nodes_test() {
+    // L shaped flyzone, the obstacles at either end can only see the one in the corner
+    let flyzones = vec![points_to_flyzone(vec![
+        make_point(0f32, 0f32, 10f32),
+        make_point(400f32, 0f32, 10f32),
+        make_point(400f32, 100f32, 10f32),
+        make_point(100f32, 100f32, 10f32),
+        make_point(100f32, 400f32, 10f32),
+        make_point(0f32, 400f32, 10f32),
+    ])];
+    let obstacles = vec![
+        obstacle_from_meters(350f32, 50f32, 4f32, 10f32),
+        obstacle_from_meters(50f32, 50f32, 4f32, 10f32),
+        obstacle_from_meters(50f32, 350f32, 4f32, 10f32),
+    ];
+    let config = TConfig {
+        buffer_size: 1f32,
+        virtualize_flyzone: false,
+        ..TConfig::default()
+    };
+    let mut pathfinder = Tanstar::new();
+    pathfinder.init(config, flyzones, obstacles);
+    assert_eq!(pathfinder.connection_count_between(0, 2), 0);
+    assert_eq!(pathfinder.find_bottleneck_nodes(), [1]);
+    assert_eq!(pathfinder.find_visibility_graph(), [(0, 1), (1, 2)]);
+
+    // every obstacle can see every other in an open flyzone
+    let obstacles = pathfinder.obstacles.clone();
+    let config = TConfig {
+        buffer_size: 1f32,
+        virtualize_flyzone: false,
+        ..TConfig::default()
+    };
+    let mut pathfinder = Tanstar::new();
+    pathfinder.init(config, dummy_flyzones(), obstacles);
+    assert!(pathfinder.find_bottleneck_nodes().is_empty());
+    assert_eq!(pathfinder.find_visibility_graph(), [(0, 1), (0, 2), (1, 2)]);
+}
+
+#[test]
+fn effective_radius_test() {
+    let node = make_node(0f32, 0f32, 20f32, 100f32);
+    assert_eqp!(node.effective_radius(0f32), 20f32, THRESHOLD);
+    assert_eqp!(node.effective_radius(50f32), 10f32, THRESHOLD);
+    assert_eqp!(node.effective_radius(100f32), 0f32, THRESHOLD);
+    assert_eqp!(node.effective_radius(150f32), 0f32, THRESHOLD);
+
+    // only the obstacle tapers, the buffer stays around it up to its height
+    let buffered = Node::from((
+        &obstacle_from_meters(0f32, 0f32, 15f32, 100f32),
+        &origin(),
+        5f32,
+    ));
+    assert_eqp!(buffered.effective_radius(0f32), 20f32, THRESHOLD);
+    assert_eqp!(buffered.effective_radius(50f32), 12.5f32, THRESHOLD);
+    assert_eqp!(buffered.effective_radius(100f32), 5f32, THRESHOLD);
+    let obstacles = vec![obstacle_from_meters(200f32, 200f32, 15f32, 100f32)];
+    let pathfinder = Tanstar::create(5f32, dummy_flyzones(), obstacles);
+    let threshold = |y| {
+        let a = make_point(100f32, y, 0f32);
+        let b = make_point(300f32, y, 0f32);
+        pathfinder.obstacle_cone_threshold(&a, &b)
+    };
+    assert_eqp!(threshold(203f32), 100f32, 0.1);
+    assert_eqp!(threshold(212.5f32), 50f32, 0.1);
+    assert_eqp!(threshold(225f32), 0f32, 0.1);
+
+    // flat nodes keep their radius
+    let flat = Node::new(make_point(0f32, 0f32, 40f32), 5f32, 0f32);
+    assert_eqp!(flat.effective_radius(0f32), 5f32, THRESHOLD);
+    assert_eqp!(flat.effective_radius(80f32), 5f32, THRESHOLD);
+
+    // obstacles are cylinders until a flight altitude is set
+    let mut pathfinder = Tanstar::create(1f32, dummy_flyzones(), Vec::new());
+    let start = Node::new(make_point(100f32, 100f32, 100f32), 5f32, 0f32);
+    let obstacle = make_node(150f32, 100f32, 20f32, 100f32);
+    assert_eqp!(pathfinder.pair_radii(&start, &obstacle).1, 20f32, THRESHOLD);
+
+    // tangents from a node at 100m to an obstacle flown around at the ground are computed
+    // halfway up the obstacle, at half its radius
+    pathfinder.set_flight_altitude(0f32);
+    let reference = make_node(150f32, 100f32, 10f32, 0f32);
+    let expected = pathfinder.find_path(&start, &reference).0;
+    let (paths, _) = pathfinder.find_path(&start, &obstacle);
+    assert_eq!(paths.len(), expected.len());
+    for (a, b) in paths.iter().zip(expected.iter()) {
+        assert_eqp!(a.0, b.0, THRESHOLD);
+        assert_eqp!(a.1, b.1, THRESHOLD);
+    }
+    // the tangents touch the tapered ring, not the full radius
+    for path in &paths {
+        let p1 = Point::from((&start, path.0));
+        let p2 = Point::from((&obstacle, path.1, 10f32));
+        let line = |center: &Point| {
+            let (dx, dy) = (p2.x - p1.x, p2.y - p1.y);
+            ((center.x - p1.x) * dy - (center.y - p1.y) * dx).abs() / (dx * dx + dy * dy).sqrt()
+        };
+        assert_eqp!(line(&start.origin), 5f32, 0.01);
+        assert_eqp!(line(&obstacle.origin), 10f32, 0.01);
+        assert!(line(&obstacle.origin) < obstacle.radius - 5f32);
+    }
+
+    // obstacle pairs are tapered at the flight altitude
+    let other = make_node(250f32, 100f32, 20f32, 100f32);
+    let untapered = pathfinder.find_path(&obstacle, &other).0;
+    pathfinder.set_flight_altitude(50f32);
+    assert_eqp!(pathfinder.pair_radii(&obstacle, &other).0, 10f32, THRESHOLD);
+    assert_eqp!(pathfinder.pair_radii(&obstacle, &other).1, 10f32, THRESHOLD);
+    let other_reference = make_node(250f32, 100f32, 10f32, 0f32);
+    let expected = pathfinder.find_path(&reference, &other_reference).0;
+    let (paths, _) = pathfinder.find_path(&obstacle, &other);
+    assert_eq!(paths.len(), expected.len());
+    for (a, b) in paths.iter().zip(expected.iter()) {
+        assert_eqp!(a.0, b.0, THRESHOLD);
+        assert_eqp!(a.1, b.1, THRESHOLD);
+    }
+    assert!(paths
+        .iter()
+        .zip(untapered.iter())
+        .any(|(a, b)| (a.0 - b.0).abs() > 0.01));
+}
+
+#[test]
+fn tapered_ring_vertices_test() {
+    let obstacles = vec![
+        obstacle_from_meters(150f32, 200f32, 20f32, 100f32),
+        obstacle_from_meters(250f32, 200f32, 20f32, 100f32),
+    ];
+    let mut pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    pathfinder.set_flight_altitude(50f32);
+    pathfinder.build_graph();
+
+    // every vertex of an obstacle lies on a ring tapered below its full radius
+    for node in &pathfinder.nodes {
+        let node = node.borrow();
+        if node.height == 0f32 {
+            continue;
+        }
+        let vertices = node.vertices();
+        assert!(!vertices.is_empty());
+        for vertex in vertices {
+            let vertex = vertex.borrow();
+            assert!(vertex.radius < node.radius);
+            assert_eqp!(vertex.location.distance(&node.origin), vertex.radius, 0.01);
+            assert!(vertex.is_on_node(&node));
+        }
+    }
+
+    // paths at the flight altitude cut inside the full radius but clear the cones
+    let start = Location::from_meters(50f32, 200f32, 50f32, &origin());
+    let end = Location::from_meters(350f32, 200f32, 50f32, &origin());
+    let waypoints = pathfinder.find_shortest_path::<()>(start, end).unwrap();
+    let mut path = vec![start];
+    path.extend(waypoints.iter().map(|wp| wp.location));
+    path.push(end);
+    let mut closest = f32::INFINITY;
+    for leg in path.windows(2) {
+        let a = Point::from((&leg[0], &origin()));
+        let b = Point::from((&leg[1], &origin()));
+        for x in &[150f32, 250f32] {
+            let center = make_point(*x, 200f32, 0f32);
+            closest = closest.min(point_to_segment_distance_2d(&center, &a, &b));
+        }
+    }
+    assert!(closest < 20f32);
+    // the 1m buffer stays around the 10m cone at the flight altitude
+    assert!(closest > 10.5f32);
+}
+
+#[test]
+fn node_to_polygon_test() {
+    let node = Node::new(make_point(10f32, 20f32, 5f32), 4f32, 0f32);
+    let polygon = node.to_polygon(4);
+    let expected = [
+        make_point(14f32, 20f32, 5f32),
+        make_point(10f32, 24f32, 5f32),
+        make_point(6f32, 20f32, 5f32),
+        make_point(10f32, 16f32, 5f32),
+    ];
+    assert_eq!(polygon.len(), expected.len());
+    for (p, e) in polygon.iter().zip(expected.iter()) {
+        assert_point_eq(p, e);
+    }
+    assert!(polygon_area(&node.to_polygon(64)) > 0f32);
+}
+
+#[test]
+fn obstacle_shadow_test() {
+    let obstacles = vec![obstacle_from_meters(100f32, 100f32, 10f32, 20f32)];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let extent = |shadow: &[Point]| {
+        let min_x = shadow.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+        let max_x = shadow.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+        let min_y = shadow.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        let max_y = shadow.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        (min_x, max_x, min_y, max_y)
+    };
+
+    // sun directly overhead, the shadow is the base of the obstacle
+    let shadow = pathfinder.obstacle_shadow(0, (0f32, 0f32, -1f32)).unwrap();
+    assert_eq!(shadow.len(), SHADOW_SIDES);
+    let (min_x, max_x, min_y, max_y) = extent(&shadow);
+    assert_eqp!(min_x, 90f32, 0.01);
+    assert_eqp!(max_x, 110f32, 0.01);
+    assert_eqp!(min_y, 90f32, 0.01);
+    assert_eqp!(max_y, 110f32, 0.01);
+
+    // sun at 45 degrees from the west, the top is projected its height to the east
+    let shadow = pathfinder.obstacle_shadow(0, (1f32, 0f32, -1f32)).unwrap();
+    let (min_x, max_x, min_y, max_y) = extent(&shadow);
+    assert_eqp!(min_x, 90f32, 0.01);
+    assert_eqp!(max_x, 130f32, 0.01);
+    assert_eqp!(min_y, 90f32, 0.01);
+    assert_eqp!(max_y, 110f32, 0.01);
+    assert!(shadow.iter().all(|p| p.z == 0f32));
+    // circle swept 20 meters along the ground
+    let area = polygon_area(&shadow);
+    assert!(area < PI * 100f32 + 400f32);
+    assert!(area > 0.98 * (PI * 100f32 + 400f32));
+
+    // light from below the horizon casts no shadow
+    assert!(pathfinder
+        .obstacle_shadow(0, (1f32, 0f32, 0f32))
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        pathfinder.obstacle_shadow(1, (0f32, 0f32, -1f32)).err(),
+        Some(PathfinderError::InvalidObstacleIndex { index: 1 })
+    );
+}
+
+#[test]
+fn nodes_reachable_from_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 20f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    let mut reachable = pathfinder.nodes_reachable_from(1);
+    assert_eq!(reachable[0], 1);
+    reachable.sort();
+    assert_eq!(reachable, (0..pathfinder.nodes.len()).collect::<Vec<_>>());
+    assert!(pathfinder.is_fully_connected());
+    assert!(pathfinder
+        .nodes_reachable_from(pathfinder.nodes.len())
+        .is_empty());
+
+    // obstacle outside of the flyzone can not be connected to the others
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 20f32, 10f32),
+        obstacle_from_meters(500f32, 200f32, 20f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    assert!(!pathfinder.nodes_reachable_from(0).contains(&2));
+    assert_eq!(pathfinder.nodes_reachable_from(2), [2]);
+    assert!(!pathfinder.is_fully_connected());
+}
+
+#[test]
+fn debug_vertex_by_index_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 20f32, 10f32),
+    ];
+    let mut pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    // vertex indices keep counting up across rebuilds
+    let first_index = pathfinder.num_vertices;
+    pathfinder.build_graph();
+    let index = first_index + (pathfinder.num_vertices - first_index) / 2;
+    let vertex = pathfinder.debug_vertex_by_index(index).unwrap();
+    assert_eq!(vertex.borrow().index, index);
+    assert!(pathfinder.debug_vertex_by_index(first_index).is_none());
+    assert!(pathfinder
+        .debug_vertex_by_index(HEADER_VERTEX_INDEX)
+        .is_none());
+    assert!(pathfinder
+        .debug_vertex_by_index(pathfinder.num_vertices + 1)
+        .is_none());
+}
+
+#[test]
+fn is_on_node_test() {
+    let obstacles = vec![
+        obstacle_from_meters(100f32, 100f32, 20f32, 10f32),
+        obstacle_from_meters(300f32, 300f32, 20f32, 10f32),
+        obstacle_from_meters(100f32, 300f32, 30f32, 10f32),
+    ];
+    let pathfinder = Tanstar::create(1f32, dummy_flyzones(), obstacles);
+    for (i, node) in pathfinder.nodes.iter().enumerate() {
+        let node = node.borrow();
+        let vertices = node.vertices();
+        assert!(!vertices.is_empty());
+        for vertex in vertices {
+            assert!(vertex.borrow().is_on_node(&node));
+            for (j, other) in pathfinder.nodes.iter().enumerate() {
+                if j != i {
+                    assert!(!vertex.borrow().is_on_node(&other.borrow()));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn heuristic_to_test() {
+    let mut num_vertices = 0;
+    let mut node = make_node(10f32, 10f32, 5f32, 0f32);
+    let other = make_node(-50f32, 10f32, 5f32, 0f32);
+    let target = Rc::new(RefCell::new(Vertex::new(
+        &mut num_vertices,
+        &other,
+        0f32,
+        vec![],
+    )));
+    let entry = Rc::new(RefCell::new(Vertex::new(
+        &mut num_vertices,
+        &node,
+        PI / 2f32,
+        vec![],
+    )));
+    let exit = Rc::new(RefCell::new(Vertex::new(
+        &mut num_vertices,
+        &node,
+        PI,
+        vec![Connection::new(target.clone(), 50f32, 0f32)],
+    )));
+    let goal = make_point(-100f32, 10f32, 0f32);
+    // vertices outside of a ring only know the straight line distance
+    assert_eqp!(
+        entry.borrow().heuristic_to(&goal),
+        entry.borrow().location.distance(&goal),
+        THRESHOLD
+    );
+
+    node.insert_vertex(entry.clone());
+    node.insert_vertex(exit.clone());
+    assert_eqp!(exit.borrow().heuristic_to(&goal), 105f32, THRESHOLD);
+    // quarter circle to the exit before leaving the node
+    let arc = 5f32 * PI / 2f32;
+    assert_eqp!(entry.borrow().heuristic_to(&goal), arc + 105f32, THRESHOLD);
+    assert!(entry.borrow().heuristic_to(&goal) > entry.borrow().location.distance(&goal));
+    // admissible, the path through the exit is never shorter than the estimate
+    let cost = arc + 50f32 + target.borrow().location.distance(&goal);
+    assert!(entry.borrow().heuristic_to(&goal) <= cost);
+}
@@ -3,6 +3,8 @@
 
 use super::*;
 
+use std::collections::VecDeque;
+
 impl Tanstar {
     // Helper function to create and init tanstar object
     pub fn create(
@@ -32,6 +34,17 @@ pub fn normalize_angle(positive: bool, n: f32) -> f32 {
     }
 }
 
+// normalize_angle for f64 angles, such as latitudes and longitudes in radians
+pub fn normalize_angle_f64(positive: bool, n: f64) -> f64 {
+    use std::f64::consts::PI;
+    let angle = (n - (n / (2f64 * PI)).floor() * (2f64 * PI)).abs();
+    if positive {
+        angle
+    } else {
+        angle - 2f64 * PI
+    }
+}
+
 // Reverse the polarity of an angle
 // Left side angle is converted to the equivilent right side angle and vice versa
 pub fn reverse_polarity(alpha: f32) -> f32 {
@@ -42,6 +55,50 @@ pub fn reverse_polarity(alpha: f32) -> f32 {
     }
 }
 
+// Minimal signed angle to turn from angle from to angle to, in range (-PI, PI]
+// positive when turning counter-clockwise
+pub fn signed_angle_diff(from: f32, to: f32) -> f32 {
+    let angle = normalize_angle(true, to - from);
+    if angle > PI {
+        angle - 2f32 * PI
+    } else {
+        angle
+    }
+}
+
+// Minimal signed angle to turn from angle a to angle b, see signed_angle_diff
+pub fn angle_difference(a: f32, b: f32) -> f32 {
+    signed_angle_diff(a, b)
+}
+
+// Sum of angles a and b, normalized to the range [0, 2PI)
+pub fn add_angles(a: f32, b: f32) -> f32 {
+    let angle = normalize_angle(true, a + b);
+    // rounding can land exactly on 2PI for sums just below a multiple of 2PI
+    if angle >= 2f32 * PI {
+        0f32
+    } else {
+        angle
+    }
+}
+
+// Compass bearing in degrees from one point to another, 0 is north and 90 is east
+// x points east and y points north, the result is in the range [0, 360)
+pub fn heading_degrees(from: &Point, to: &Point) -> f32 {
+    let heading = (to.x - from.x).atan2(to.y - from.y).to_degrees();
+    let heading = if heading < 0f32 {
+        heading + 360f32
+    } else {
+        heading
+    };
+    // small negative angles round up to 360
+    if heading >= 360f32 {
+        0f32
+    } else {
+        heading
+    }
+}
+
 // Calculate the arc length from angle a to angle b on a circle of radius r
 pub fn arc_length(a: f32, b: f32, r: f32) -> f32 {
     let mut angle = if a >= 0f32 {
@@ -58,6 +115,29 @@ pub fn arc_length(a: f32, b: f32, r: f32) -> f32 {
     (angle * r)
 }
 
+// Rotate point p counter-clockwise around the origin in the xy plane by angle (radians)
+// z is left unchanged
+pub fn rotate_point(p: &Point, angle: f32) -> Point {
+    let (sin, cos) = angle.sin_cos();
+    Point::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos, p.z)
+}
+
+// signed area of the triangle a, b, c in the xy plane
+// positive if the points wind counter-clockwise, negative if clockwise, zero if colinear
+pub fn triangle_area(a: &Point, b: &Point, c: &Point) -> f32 {
+    ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)) / 2f32
+}
+
+// signed area of a simple polygon in the xy plane, positive if counter-clockwise
+pub fn polygon_area(points: &[Point]) -> f32 {
+    if points.len() < 3 {
+        return 0f32;
+    }
+    (1..points.len() - 1)
+        .map(|i| triangle_area(&points[0], &points[i], &points[i + 1]))
+        .sum()
+}
+
 // helper function for intersection calculation
 // returns the area between three points
 fn area(a: &Point, b: &Point, c: &Point) -> f32 {
@@ -148,6 +228,464 @@ pub fn intersect_distance(a: &Point, b: &Point, c: &Point) -> (f32, f32, f32, bo
     (x, y, (x - c.x).powi(2) + (y - c.y).powi(2), endpoint)
 }
 
+// minimum euclidean distance in the xy plane from point p to the segment a to b
+// unlike the distance to the full line, points past either end measure to the nearest endpoint
+pub fn point_to_segment_distance_2d(p: &Point, a: &Point, b: &Point) -> f32 {
+    intersect_distance(a, b, p).2.sqrt()
+}
+
+// closest point in the xy plane from point p to the segment a to b
+// z is taken from point p
+pub fn closest_point_on_segment(p: &Point, a: &Point, b: &Point) -> Point {
+    let (x, y, _, _) = intersect_distance(a, b, p);
+    Point::new(x, y, p.z)
+}
+
+// point at parameter t along the segment a to b, a + t * (b - a)
+// t is not clamped, so values outside 0 to 1 extend past either end
+pub fn perpendicular_point(a: &Point, b: &Point, t: f32) -> Point {
+    a.lerp_unclamped(b, t)
+}
+
+// ray casting test for whether point p lies inside the polygon in the xy plane
+// points exactly on the boundary may be reported either way
+pub fn point_in_polygon(p: &Point, polygon: &[Point]) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let a = &polygon[i];
+        let b = &polygon[(i + 1) % polygon.len()];
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// number of times the polygon winds around point p in the xy plane, counterclockwise positive
+// p is inside the polygon when the winding number is not 0
+pub fn winding_number(p: &Point, polygon: &[Point]) -> i32 {
+    let mut winding = 0;
+    for i in 0..polygon.len() {
+        let a = &polygon[i];
+        let b = &polygon[(i + 1) % polygon.len()];
+        // positive if p is left of the edge a to b
+        let side = (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y);
+        if a.y <= p.y {
+            if b.y > p.y && side > 0f32 {
+                winding += 1;
+            }
+        } else if b.y <= p.y && side < 0f32 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+// breadth first search along connections to check if goal can be reached from start
+// only paths of at most max_depth connections are explored
+pub fn is_reachable_from(
+    start: &Rc<RefCell<Vertex>>,
+    goal: &Rc<RefCell<Vertex>>,
+    max_depth: usize,
+) -> bool {
+    let goal_index = goal.borrow().index;
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    visited.insert(start.borrow().index);
+    queue.push_back((start.clone(), 0));
+    while let Some((current, depth)) = queue.pop_front() {
+        if current.borrow().index == goal_index {
+            return true;
+        }
+        if depth == max_depth {
+            continue;
+        }
+        for connection in &current.borrow().connection {
+            if visited.insert(connection.neighbor.borrow().index) {
+                queue.push_back((connection.neighbor.clone(), depth + 1));
+            }
+        }
+    }
+    false
+}
+
+// shrink a simple polygon by moving every edge inward by distance in the xy plane
+// vertices are moved along the bisector of their two edges
+pub fn inset_polygon(points: &[Point], distance: f32) -> Vec<Point> {
+    let size = points.len();
+    // inward side of an edge depends on the winding of the polygon
+    let side = if polygon_area(points) > 0f32 {
+        1f32
+    } else {
+        -1f32
+    };
+    let normal = |a: &Point, b: &Point| {
+        let length = a.distance(b);
+        Point::new(
+            -side * (b.y - a.y) / length,
+            side * (b.x - a.x) / length,
+            0f32,
+        )
+    };
+    (0..size)
+        .map(|i| {
+            let prev = &points[(i + size - 1) % size];
+            let current = &points[i];
+            let next = &points[(i + 1) % size];
+            let n1 = normal(prev, current);
+            let n2 = normal(current, next);
+            let scale = distance / (1f32 + n1.x * n2.x + n1.y * n2.y);
+            Point::new(
+                current.x + (n1.x + n2.x) * scale,
+                current.y + (n1.y + n2.y) * scale,
+                current.z,
+            )
+        })
+        .collect()
+}
+
+// convex hull of points in the xy plane using graham scan, returned counter-clockwise starting
+// from the lowest point. Duplicate and colinear points are dropped, so colinear input gives
+// its two endpoints. Inputs with fewer than 3 distinct points are returned deduplicated
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    // computed in f64 so nearly colinear turns keep the right sign
+    let cross = |o: &Point, a: &Point, b: &Point| {
+        (f64::from(a.x) - f64::from(o.x)) * (f64::from(b.y) - f64::from(o.y))
+            - (f64::from(b.x) - f64::from(o.x)) * (f64::from(a.y) - f64::from(o.y))
+    };
+    let distance = |o: &Point, a: &Point| (a.x - o.x).powi(2) + (a.y - o.y).powi(2);
+
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| {
+        (a.y, a.x)
+            .partial_cmp(&(b.y, b.x))
+            .unwrap_or(Ordering::Equal)
+    });
+    points.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+    if points.len() < 3 {
+        return points;
+    }
+
+    // sort by angle around the lowest point, closer points first when colinear
+    let pivot = points[0];
+    points[1..].sort_by(|a, b| {
+        let turn = cross(&pivot, a, b);
+        if turn > 0f64 {
+            Ordering::Less
+        } else if turn < 0f64 {
+            Ordering::Greater
+        } else {
+            distance(&pivot, a)
+                .partial_cmp(&distance(&pivot, b))
+                .unwrap_or(Ordering::Equal)
+        }
+    });
+
+    let mut hull: Vec<Point> = Vec::with_capacity(points.len());
+    for point in points {
+        while hull.len() >= 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], &point) <= 0f64
+        {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    hull
+}
+
+// offset every edge of a simple polygon inward by offset_m in the xy plane, a negative offset
+// grows the polygon instead. Vertices are placed where adjacent offset edges intersect
+// A vertex whose offset passes the chord between its neighbours, like the tip of a notch
+// narrower than twice the offset, collapses and is dropped. Offsets that still flip an edge or
+// make the polygon cross itself are degenerate
+pub fn offset_polygon(polygon: &[Point], offset_m: f32) -> Result<Vec<Point>, PathfinderError> {
+    let size = polygon.len();
+    if size < 3 || polygon_area(polygon) == 0f32 {
+        return Err(PathfinderError::DegeneratePolygon);
+    }
+    for i in 0..size {
+        let prev = &polygon[(i + size - 1) % size];
+        let current = &polygon[i];
+        let next = &polygon[(i + 1) % size];
+        let (ax, ay) = (current.x - prev.x, current.y - prev.y);
+        let (bx, by) = (next.x - current.x, next.y - current.y);
+        // offset edges of an empty edge or a fold never intersect
+        let folds = ax * by - ay * bx == 0f32 && ax * bx + ay * by < 0f32;
+        if (ax == 0f32 && ay == 0f32) || folds {
+            return Err(PathfinderError::DegeneratePolygon);
+        }
+    }
+
+    let mut polygon = polygon.to_vec();
+    let mut offset = inset_polygon(&polygon, offset_m);
+    loop {
+        let size = polygon.len();
+        let collapsed: Vec<bool> = (0..size)
+            .map(|i| {
+                let prev = &polygon[(i + size - 1) % size];
+                let next = &polygon[(i + 1) % size];
+                let side = area(prev, next, &polygon[i]);
+                side != 0f32 && area(prev, next, &offset[i]) * side <= 0f32
+            })
+            .collect();
+        if !collapsed.contains(&true) {
+            break;
+        }
+        let mut kept = collapsed.iter().map(|collapsed| !collapsed);
+        polygon.retain(|_| kept.next().unwrap());
+        if polygon.len() < 3 || polygon_area(&polygon) == 0f32 {
+            return Err(PathfinderError::DegeneratePolygon);
+        }
+        offset = inset_polygon(&polygon, offset_m);
+    }
+
+    let size = polygon.len();
+    for i in 0..size {
+        let next = (i + 1) % size;
+        let (ax, ay) = (
+            polygon[next].x - polygon[i].x,
+            polygon[next].y - polygon[i].y,
+        );
+        let (bx, by) = (offset[next].x - offset[i].x, offset[next].y - offset[i].y);
+        if ax * bx + ay * by <= 0f32 {
+            return Err(PathfinderError::DegeneratePolygon);
+        }
+        // edges sharing a vertex always touch
+        for j in i + 2..size {
+            if (j + 1) % size == i {
+                continue;
+            }
+            if intersect(
+                &offset[i],
+                &offset[next],
+                &offset[j],
+                &offset[(j + 1) % size],
+            ) {
+                return Err(PathfinderError::DegeneratePolygon);
+            }
+        }
+    }
+    Ok(offset)
+}
+
+// union of two simple polygons in the xy plane, returned counter-clockwise
+// the boundary is walked along whichever polygon is outside the other, switching polygons at
+// every crossing. Only the outer boundary is kept, and polygons touching without crossing are
+// treated as disjoint. Returns none if the polygons do not overlap
+pub fn polygon_union(a: &[Point], b: &[Point]) -> Option<Vec<Point>> {
+    let counter_clockwise = |polygon: &[Point]| {
+        let mut polygon = polygon.to_vec();
+        if polygon_area(&polygon) < 0f32 {
+            polygon.reverse();
+        }
+        polygon
+    };
+    let polygons = [counter_clockwise(a), counter_clockwise(b)];
+
+    // boundaries with crossings inserted, crossings hold their index into the other boundary
+    let mut boundaries: [Vec<(Point, Option<usize>)>; 2] = [Vec::new(), Vec::new()];
+    let mut crossings: [Vec<Vec<(f32, usize)>>; 2] = [
+        vec![Vec::new(); polygons[0].len()],
+        vec![Vec::new(); polygons[1].len()],
+    ];
+    let mut points = Vec::new();
+    let (n, m) = (polygons[0].len(), polygons[1].len());
+    for i in 0..n {
+        let (p, p2) = (&polygons[0][i], &polygons[0][(i + 1) % n]);
+        for j in 0..m {
+            let (q, q2) = (&polygons[1][j], &polygons[1][(j + 1) % m]);
+            let (rx, ry, sx, sy) = (p2.x - p.x, p2.y - p.y, q2.x - q.x, q2.y - q.y);
+            let denom = rx * sy - ry * sx;
+            if denom == 0f32 {
+                continue;
+            }
+            let t = ((q.x - p.x) * sy - (q.y - p.y) * sx) / denom;
+            let u = ((q.x - p.x) * ry - (q.y - p.y) * rx) / denom;
+            if t > 0f32 && t < 1f32 && u > 0f32 && u < 1f32 {
+                crossings[0][i].push((t, points.len()));
+                crossings[1][j].push((u, points.len()));
+                points.push(Point::new(p.x + t * rx, p.y + t * ry, p.z));
+            }
+        }
+    }
+
+    if points.is_empty() {
+        return if point_in_polygon(&polygons[1][0], &polygons[0]) {
+            Some(polygons[0].clone())
+        } else if point_in_polygon(&polygons[0][0], &polygons[1]) {
+            Some(polygons[1].clone())
+        } else {
+            None
+        };
+    }
+
+    // position of every crossing in each boundary
+    let mut position = [vec![0; points.len()], vec![0; points.len()]];
+    let mut vertex_position = [vec![0; n], vec![0; m]];
+    for k in 0..2 {
+        for (i, vertex) in polygons[k].iter().enumerate() {
+            vertex_position[k][i] = boundaries[k].len();
+            boundaries[k].push((*vertex, None));
+            crossings[k][i].sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal));
+            for &(_, id) in &crossings[k][i] {
+                position[k][id] = boundaries[k].len();
+                boundaries[k].push((points[id], Some(id)));
+            }
+        }
+    }
+
+    // start from a vertex of either polygon lying outside the other
+    let (mut k, start) = match (0..2)
+        .flat_map(|k| (0..polygons[k].len()).map(move |i| (k, i)))
+        .find(|&(k, i)| !point_in_polygon(&polygons[k][i], &polygons[1 - k]))
+    {
+        Some((k, i)) => (k, vertex_position[k][i]),
+        None => return Some(polygons[0].clone()),
+    };
+    let start_polygon = k;
+    let mut i = start;
+    let mut union = Vec::new();
+    loop {
+        let (point, crossing) = boundaries[k][i];
+        union.push(point);
+        if let Some(id) = crossing {
+            k = 1 - k;
+            i = position[k][id];
+        }
+        i = (i + 1) % boundaries[k].len();
+        if (k == start_polygon && i == start)
+            || union.len() > boundaries[0].len() + boundaries[1].len()
+        {
+            break;
+        }
+    }
+    Some(union)
+}
+
+// intersection of two simple polygons in the xy plane, returned counter-clockwise
+// Sutherland-Hodgman clipping of one polygon by every edge of the other, so at least one of the
+// polygons must be convex; the convex one is used as the clip. Returns an empty vec if the
+// polygons do not overlap or only touch
+pub fn polygon_intersection(subject: &[Point], clip: &[Point]) -> Vec<Point> {
+    let counter_clockwise = |polygon: &[Point]| {
+        let mut polygon = polygon.to_vec();
+        if polygon_area(&polygon) < 0f32 {
+            polygon.reverse();
+        }
+        polygon
+    };
+    let is_convex = |polygon: &[Point]| {
+        let size = polygon.len();
+        (0..size).all(|i| {
+            triangle_area(
+                &polygon[i],
+                &polygon[(i + 1) % size],
+                &polygon[(i + 2) % size],
+            ) >= 0f32
+        })
+    };
+    if subject.len() < 3 || clip.len() < 3 {
+        return Vec::new();
+    }
+    let (mut subject, mut clip) = (counter_clockwise(subject), counter_clockwise(clip));
+    if !is_convex(&clip) && is_convex(&subject) {
+        std::mem::swap(&mut subject, &mut clip);
+    }
+
+    let mut output = subject;
+    for i in 0..clip.len() {
+        let (c1, c2) = (clip[i], clip[(i + 1) % clip.len()]);
+        let input = output;
+        output = Vec::new();
+        for j in 0..input.len() {
+            let (p, q) = (input[j], input[(j + 1) % input.len()]);
+            let (side_p, side_q) = (triangle_area(&c1, &c2, &p), triangle_area(&c1, &c2, &q));
+            if side_p >= 0f32 {
+                output.push(p);
+            }
+            if (side_p >= 0f32) != (side_q >= 0f32) {
+                let t = side_p / (side_p - side_q);
+                output.push(Point::new(
+                    p.x + t * (q.x - p.x),
+                    p.y + t * (q.y - p.y),
+                    p.z + t * (q.z - p.z),
+                ));
+            }
+        }
+        if output.is_empty() {
+            return output;
+        }
+    }
+
+    output.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+    while output.len() > 1
+        && output[0].x == output[output.len() - 1].x
+        && output[0].y == output[output.len() - 1].y
+    {
+        output.pop();
+    }
+    if polygon_area(&output) <= 0f32 {
+        return Vec::new();
+    }
+    output
+}
+
+// evaluate the bezier curve defined by control points at t_steps evenly spaced values of t
+// from 0 to 1, using de casteljau's algorithm so any number of control points is supported
+pub fn bezier_path(control_points: &[Point], t_steps: usize) -> Vec<Point> {
+    if control_points.is_empty() || t_steps == 0 {
+        return Vec::new();
+    }
+    if t_steps == 1 {
+        return vec![control_points[0]];
+    }
+    (0..t_steps)
+        .map(|i| {
+            let t = i as f32 / (t_steps - 1) as f32;
+            let mut points = control_points.to_vec();
+            for n in (1..points.len()).rev() {
+                for j in 0..n {
+                    points[j] = Point::new(
+                        points[j].x + t * (points[j + 1].x - points[j].x),
+                        points[j].y + t * (points[j + 1].y - points[j].y),
+                        points[j].z + t * (points[j + 1].z - points[j].z),
+                    );
+                }
+            }
+            points[0]
+        })
+        .collect()
+}
+
+// smooth a path with one cubic bezier curve between each pair of waypoints
+// tangents at each waypoint are estimated from its neighbors, so the curve passes through every
+// waypoint and t_steps points are sampled per segment
+pub fn bezier_smooth(waypoints: &[Point], t_steps: usize) -> Vec<Point> {
+    if waypoints.len() < 2 {
+        return waypoints.to_vec();
+    }
+    let last = waypoints.len() - 1;
+    let tangent = |i: usize| {
+        let (a, b) = (
+            &waypoints[i.saturating_sub(1)],
+            &waypoints[(i + 1).min(last)],
+        );
+        Point::new((b.x - a.x) / 6f32, (b.y - a.y) / 6f32, (b.z - a.z) / 6f32)
+    };
+    let mut path = vec![waypoints[0]];
+    for i in 0..last {
+        let (a, b) = (waypoints[i], waypoints[i + 1]);
+        let (ta, tb) = (tangent(i), tangent(i + 1));
+        let control = [
+            a,
+            Point::new(a.x + ta.x, a.y + ta.y, a.z + ta.z),
+            Point::new(b.x - tb.x, b.y - tb.y, b.z - tb.z),
+            b,
+        ];
+        path.extend(bezier_path(&control, t_steps).into_iter().skip(1));
+    }
+    path
+}
+
 fn output_ring(origin: &Location, mut current: Rc<RefCell<Vertex>>) {
     let temp = match current.borrow().next {
         Some(ref v) => v.clone(),
@@ -217,21 +755,19 @@ pub fn perpendicular_intersect(
 ) -> (Option<Point>, Option<Point>) {
     // intersect distance gives x and y of intersect point, then distance squared
     // calculates the shortest distance between the segment and obstacle. If less than radius, it intersects.
-    // #TODO: endpoint not used, why is it here?
-    let (x, y, distance, _endpoint) = intersect_distance(a, b, &Point::from((&c.location, origin)));
-    if distance.sqrt() < c.radius as f32 {
-        println!(
-            "intersect with obstacle: dist {} r {}",
-            distance.sqrt(),
-            c.radius
-        );
+    let center = Point::from((&c.location, origin));
+    let distance = point_to_segment_distance_2d(&center, a, b);
+    if distance < c.radius as f32 {
+        println!("intersect with obstacle: dist {} r {}", distance, c.radius);
+        // #TODO: endpoint not used, why is it here?
+        let (x, y, _, _endpoint) = intersect_distance(a, b, &center);
         // immediately check if the endpoint is the shortest distance; can't fly over in this case
         // EXCEPTION: endpoint is inside obstacle but still generates a perpendicular.
         // if endpoint {
         //     // not technically none, but should be considered as such as we will stop calculations
         //     return (None, None);
         // }
-        let mag = (c.radius.powi(2) - distance).sqrt();
+        let mag = (c.radius.powi(2) - distance.powi(2)).sqrt();
         //println!("mag: {}", mag);
         //calculate unit vectors for y and x directions
         let dx = (a.x - b.x) / a.distance(b);
@@ -351,8 +887,138 @@ pub fn circular_intersect(
     }
 }
 
+// Angles of the tangents between the circle c1 of radius r1 and the circle c2 of radius r2
+// returns (angle on c1, angle on c2) of each tangent, outer tangents first, and when the circles
+// overlap or touch the (angle on c1, angle on c2) of the sentinels where the circles meet.
+// Left side angles are kept in 0 to 2pi and right side angles in -2pi to 0. There are no
+// tangents when one circle is inside the other, and no sentinels when a radius is 0
+pub fn circle_circle_tangent_angles(
+    c1: &Point,
+    r1: f32,
+    c2: &Point,
+    r2: f32,
+) -> (Vec<(f32, f32)>, Option<Sentinel>) {
+    let dist: f32 = c1.distance(c2);
+    if dist == 0f32 || dist < (r1 - r2).abs() {
+        return (Vec::new(), None);
+    }
+
+    // theta1 is the angle from a to b, theta2 the angle from b to a
+    // normalized between 0 and 2pi
+    let theta = (c2.y - c1.y).atan2(c2.x - c1.x);
+    let theta1 = add_angles(theta, 0f32);
+    let theta2 = add_angles(theta, PI);
+
+    // gamma1 and gamma2 are the angle between reference axis and the tangents
+    // gamma1 is angle to inner tangent, gamma2 is angle to outer tangent
+    let gamma1 = ((r1 + r2).abs() / dist).acos();
+    let mut gamma2 = ((r1 - r2).abs() / dist).acos();
+
+    // we assume r1 is greater than r2 for the math to work, so find complement if otherwise
+    if r2 > r1 {
+        gamma2 = PI - gamma2;
+    }
+
+    // Outer tangent always exists
+    let mut candidates = vec![
+        (add_angles(theta1, -gamma2), add_angles(theta2, PI - gamma2)),
+        (
+            normalize_angle(false, add_angles(theta1, gamma2)),
+            normalize_angle(false, add_angles(theta2, PI + gamma2)),
+        ),
+    ];
+
+    let mut sentinels = None;
+    if r1 != 0f32 && r2 != 0f32 && dist > r1 + r2 {
+        candidates.append(&mut vec![
+            // Inner left tangent
+            (
+                add_angles(theta1, -gamma1),
+                normalize_angle(false, add_angles(theta2, -gamma1)),
+            ),
+            // Inner right tangent
+            (
+                normalize_angle(false, add_angles(theta1, gamma1)),
+                add_angles(theta2, gamma1),
+            ),
+        ]);
+    } else if r1 != 0f32 && r2 != 0f32 {
+        // sentinels sit where the circles meet, theta_s either side of the line from c1 to c2
+        let theta_s = ((r1.powi(2) + dist.powi(2) - r2.powi(2)) / (2f32 * r1 * dist)).acos();
+        let meet = |angle: f32| {
+            let offset = rotate_point(&Point::new(r1, 0f32, 0f32), theta + angle);
+            Point::new(c1.x + offset.x, c1.y + offset.y, c1.z)
+        };
+        let (upper, lower) = (meet(theta_s), meet(-theta_s));
+        let angle_on = |c: &Point, p: &Point| (p.y - c.y).atan2(p.x - c.x);
+        let left = |angle: f32| add_angles(angle, 0f32);
+        let right = |angle: f32| normalize_angle(false, angle);
+
+        //sentinel vertices on A
+        let a_s1 = left(angle_on(c1, &upper));
+        let a_s2 = right(angle_on(c1, &lower));
+        let a_s3 = right(angle_on(c1, &upper));
+        let a_s4 = left(angle_on(c1, &lower));
+        //sentinel vertices on B
+        let b_s1 = left(angle_on(c2, &upper));
+        let b_s2 = left(angle_on(c2, &lower));
+        let b_s3 = right(angle_on(c2, &lower));
+        let b_s4 = right(angle_on(c2, &upper));
+        sentinels = Some(vec![(a_s1, b_s1), (a_s2, b_s2), (a_s3, b_s3), (a_s4, b_s4)]);
+    }
+    (candidates, sentinels)
+}
+
+// Constructors shared by the tests of the graph
+#[cfg(test)]
+pub mod test_helpers {
+    use super::*;
+
+    pub fn make_point(x: f32, y: f32, z: f32) -> Point {
+        Point::new(x, y, z)
+    }
+
+    // node centered at height, like the nodes generated from obstacles
+    pub fn make_node(x: f32, y: f32, radius: f32, height: f32) -> Node {
+        Node::new(Point::new(x, y, height), radius, height)
+    }
+
+    pub fn make_obstacle(lat: f64, lon: f64, radius: f32, height: f32) -> Obstacle {
+        Obstacle::new(Location::from_degrees(lat, lon, height), radius, height)
+    }
+
+    // reference location of the flyzones and obstacles built in meters
+    pub fn origin() -> Location {
+        Location::from_radians(0f64, 0f64, 0f32)
+    }
+
+    // square flyzone of sides size with a corner at the origin
+    pub fn square_flyzone(size: f32) -> Vec<Vec<Location>> {
+        vec![vec![
+            Location::from_meters(0f32, 0f32, 0f32, &origin()),
+            Location::from_meters(0f32, size, 0f32, &origin()),
+            Location::from_meters(size, size, 0f32, &origin()),
+            Location::from_meters(size, 0f32, 0f32, &origin()),
+        ]]
+    }
+
+    // obstacle x and y meters from the origin, located at its height
+    pub fn obstacle_from_meters(x: f32, y: f32, radius: f32, height: f32) -> Obstacle {
+        Obstacle::new(
+            Location::from_meters(x, y, height, &origin()),
+            radius,
+            height,
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
+    extern crate proptest;
+    extern crate rand;
+
+    use self::proptest::prelude::*;
+    use self::rand::{thread_rng, Rng};
     use super::*;
 
     const THRESHOLD: f32 = 0.001;
@@ -379,6 +1045,493 @@ mod test {
         assert_eqf!(normalize_angle(false, -3f32 * PI / 2f32), -3f32 * PI / 2f32);
     }
 
+    #[test]
+    fn rotate_point_test() {
+        let p = Point::new(10f32, 0f32, 5f32);
+
+        let r = rotate_point(&p, 0f32);
+        assert_eqf!(r.x, 10f32);
+        assert_eqf!(r.y, 0f32);
+        assert_eqf!(r.z, 5f32);
+
+        let r = rotate_point(&p, PI / 2f32);
+        assert_eqf!(r.x, 0f32);
+        assert_eqf!(r.y, 10f32);
+        assert_eqf!(r.z, 5f32);
+
+        let r = rotate_point(&p, PI);
+        assert_eqf!(r.x, -10f32);
+        assert_eqf!(r.y, 0f32);
+
+        let r = rotate_point(&p, 3f32 * PI / 2f32);
+        assert_eqf!(r.x, 0f32);
+        assert_eqf!(r.y, -10f32);
+
+        let r = rotate_point(&Point::new(3f32, 4f32, 0f32), PI / 6f32);
+        assert_eqf!(r.x, 3f32 * (PI / 6f32).cos() - 4f32 * (PI / 6f32).sin());
+        assert_eqf!(r.y, 3f32 * (PI / 6f32).sin() + 4f32 * (PI / 6f32).cos());
+        assert_eqf!(r.x.hypot(r.y), 5f32);
+    }
+
+    #[test]
+    fn point_to_segment_distance_test() {
+        let a = Point::new(0f32, 0f32, 0f32);
+        let b = Point::new(10f32, 0f32, 0f32);
+        let distance =
+            |x: f32, y: f32| point_to_segment_distance_2d(&Point::new(x, y, 0f32), &a, &b);
+
+        // perpendicular to the segment
+        assert_eqf!(distance(5f32, 3f32), 3f32);
+        assert_eqf!(distance(5f32, -4f32), 4f32);
+        assert_eqf!(distance(0f32, 2f32), 2f32);
+        assert_eqf!(distance(10f32, 2f32), 2f32);
+        // on the segment
+        assert_eqf!(distance(5f32, 0f32), 0f32);
+        assert_eqf!(distance(0f32, 0f32), 0f32);
+        assert_eqf!(distance(10f32, 0f32), 0f32);
+        // past either end, measured to the endpoint and not the line
+        assert_eqf!(distance(-3f32, 4f32), 5f32);
+        assert_eqf!(distance(13f32, -4f32), 5f32);
+        assert_eqf!(distance(20f32, 0f32), 10f32);
+        // height is ignored
+        assert_eqf!(
+            point_to_segment_distance_2d(&Point::new(5f32, 1f32, 50f32), &a, &b),
+            1f32
+        );
+        // degenerate segment
+        assert_eqf!(
+            point_to_segment_distance_2d(&Point::new(3f32, 4f32, 0f32), &a, &a),
+            5f32
+        );
+    }
+
+    #[test]
+    fn perpendicular_point_test() {
+        let a = Point::new(2f32, -4f32, 10f32);
+        let b = Point::new(12f32, 6f32, 30f32);
+        let check = |t: f32, x: f32, y: f32, z: f32| {
+            let p = perpendicular_point(&a, &b, t);
+            assert_eqf!(p.x, x);
+            assert_eqf!(p.y, y);
+            assert_eqf!(p.z, z);
+        };
+
+        check(0f32, 2f32, -4f32, 10f32);
+        check(1f32, 12f32, 6f32, 30f32);
+        check(0.5f32, 7f32, 1f32, 20f32);
+        check(0.25f32, 4.5f32, -1.5f32, 15f32);
+        // past either end
+        check(2f32, 22f32, 16f32, 50f32);
+        check(-1f32, -8f32, -14f32, -10f32);
+        // degenerate segment
+        let p = perpendicular_point(&a, &a, 3f32);
+        assert_eqf!(p.x, a.x);
+        assert_eqf!(p.y, a.y);
+        assert_eqf!(p.z, a.z);
+    }
+
+    #[test]
+    fn normalize_angle_f64_test() {
+        let angles = [
+            0f64, 1f64, -1f64, 3.5f64, -3.5f64, 6.5f64, -6.5f64, 10f64, -10f64, 100f64,
+        ];
+        for &angle in &angles {
+            for &positive in &[true, false] {
+                let a = normalize_angle_f64(positive, angle);
+                let b = f64::from(normalize_angle(positive, angle as f32));
+                assert!((a - b).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn angle_difference_test() {
+        // same angle
+        assert_eqf!(angle_difference(1f32, 1f32), 0f32);
+        assert_eqf!(angle_difference(PI / 2f32, -3f32 * PI / 2f32), 0f32);
+        // opposite angles
+        assert_eqf!(angle_difference(0f32, PI), PI);
+        assert_eqf!(angle_difference(PI, 0f32), PI);
+        assert_eqf!(angle_difference(PI / 2f32, -PI / 2f32), PI);
+        // regular turns
+        assert_eqf!(angle_difference(0f32, PI / 4f32), PI / 4f32);
+        assert_eqf!(angle_difference(PI / 4f32, 0f32), -PI / 4f32);
+        // crossing zero
+        assert_eqf!(angle_difference(-0.1f32, 0.1f32), 0.2f32);
+        assert_eqf!(angle_difference(0.1f32, 2f32 * PI - 0.1f32), -0.2f32);
+        assert_eqf!(angle_difference(2f32 * PI - 0.1f32, 0.1f32), 0.2f32);
+    }
+
+    #[test]
+    fn signed_angle_diff_test() {
+        assert_eqf!(signed_angle_diff(1f32, 1f32), 0f32);
+        assert_eqf!(signed_angle_diff(0f32, PI), PI);
+        assert_eqf!(signed_angle_diff(PI, 0f32), PI);
+        // shorter way round crossing zero
+        assert_eqf!(
+            signed_angle_diff(3f32 * PI / 2f32, PI / 4f32),
+            3f32 * PI / 4f32
+        );
+        assert_eqf!(
+            signed_angle_diff(PI / 4f32, 3f32 * PI / 2f32),
+            -3f32 * PI / 4f32
+        );
+        // unnormalized inputs
+        assert_eqf!(
+            signed_angle_diff(-7f32 * PI, 4f32 * PI + 0.5f32),
+            0.5f32 - PI
+        );
+    }
+
+    #[test]
+    fn heading_degrees_test() {
+        let center = Point::new(10f32, 10f32, 0f32);
+        let heading = |x, y| heading_degrees(&center, &Point::new(10f32 + x, 10f32 + y, 5f32));
+        assert_eqf!(heading(0f32, 1f32), 0f32);
+        assert_eqf!(heading(1f32, 1f32), 45f32);
+        assert_eqf!(heading(1f32, 0f32), 90f32);
+        assert_eqf!(heading(1f32, -1f32), 135f32);
+        assert_eqf!(heading(0f32, -1f32), 180f32);
+        assert_eqf!(heading(-1f32, -1f32), 225f32);
+        assert_eqf!(heading(-1f32, 0f32), 270f32);
+        assert_eqf!(heading(-1f32, 1f32), 315f32);
+        assert!(heading(-1e-7f32, 1f32) < 360f32);
+    }
+
+    #[test]
+    fn add_angles_test() {
+        assert_eqf!(add_angles(0f32, 0f32), 0f32);
+        assert_eqf!(add_angles(PI / 2f32, PI / 4f32), 3f32 * PI / 4f32);
+        assert_eqf!(add_angles(3f32 * PI / 2f32, PI), PI / 2f32);
+        assert_eqf!(add_angles(PI / 4f32, -PI / 2f32), 7f32 * PI / 4f32);
+        assert_eqf!(add_angles(-PI, -PI), 0f32);
+        assert_eqf!(add_angles(5f32 * PI, 0f32), PI);
+        assert!(add_angles(-1e-7f32, 0f32) < 2f32 * PI);
+    }
+
+    proptest! {
+        #[test]
+        fn add_angles_properties(a in -10f32 * PI..10f32 * PI, b in -10f32 * PI..10f32 * PI) {
+            let sum = add_angles(a, b);
+            prop_assert!((0f32..2f32 * PI).contains(&sum));
+            prop_assert_eq!(sum, add_angles(b, a));
+        }
+
+        #[test]
+        fn signed_angle_diff_properties(
+            a in -10f32 * PI..10f32 * PI,
+            b in -10f32 * PI..10f32 * PI,
+            turns in -3i32..4i32,
+        ) {
+            let diff = signed_angle_diff(a, b);
+            prop_assert!(diff > -PI && diff <= PI);
+            prop_assert_eq!(diff, angle_difference(a, b));
+            // turning by the difference from a reaches b
+            prop_assert!(signed_angle_diff(b, add_angles(a, diff)).abs() < 1e-3);
+            if diff.abs() < PI - 1e-3 {
+                prop_assert!((diff + signed_angle_diff(b, a)).abs() < 1e-3);
+            }
+            // differences do not depend on whole turns added to either angle
+            let turns = turns as f32 * 2f32 * PI;
+            prop_assert!(signed_angle_diff(diff, signed_angle_diff(a + turns, b)).abs() < 1e-3);
+        }
+
+        #[test]
+        fn turn_by_angle_properties(a in -10f32 * PI..10f32 * PI, turn in -PI + 1e-3..PI - 1e-3) {
+            prop_assert!((signed_angle_diff(a, add_angles(a, turn)) - turn).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn bezier_path_test() {
+        let control = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(10f32, 20f32, 5f32),
+            Point::new(30f32, 20f32, 5f32),
+            Point::new(40f32, 0f32, 10f32),
+        ];
+        let curve = bezier_path(&control, 51);
+        assert_eq!(curve.len(), 51);
+        assert_eqf!(curve[0].distance3d(&control[0]), 0f32);
+        assert_eqf!(curve[50].distance3d(&control[3]), 0f32);
+        // symmetric control points peak at 3/4 of the control height
+        assert_eqf!(curve.iter().map(|p| p.y).fold(0f32, f32::max), 15f32);
+        // continuous, no jumps between samples
+        for pair in curve.windows(2) {
+            assert!(pair[0].distance3d(&pair[1]) < 2f32);
+        }
+        assert!(bezier_path(&control, 0).is_empty());
+        assert!(bezier_path(&[], 10).is_empty());
+
+        let waypoints = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(50f32, 0f32, 0f32),
+            Point::new(50f32, 50f32, 0f32),
+        ];
+        let path = bezier_smooth(&waypoints, 11);
+        assert_eq!(path.len(), 21);
+        assert_eqf!(path[0].distance3d(&waypoints[0]), 0f32);
+        assert_eqf!(path[10].distance3d(&waypoints[1]), 0f32);
+        assert_eqf!(path[20].distance3d(&waypoints[2]), 0f32);
+        for pair in path.windows(2) {
+            assert!(pair[0].distance(&pair[1]) < 10f32);
+        }
+    }
+
+    #[test]
+    fn triangle_area_test() {
+        let a = Point::new(0f32, 0f32, 0f32);
+        let b = Point::new(4f32, 0f32, 0f32);
+        let c = Point::new(0f32, 3f32, 5f32);
+        // counter-clockwise
+        assert_eqf!(triangle_area(&a, &b, &c), 6f32);
+        // clockwise
+        assert_eqf!(triangle_area(&a, &c, &b), -6f32);
+        // colinear
+        assert_eqf!(triangle_area(&a, &b, &Point::new(10f32, 0f32, 0f32)), 0f32);
+        // equal points
+        assert_eqf!(triangle_area(&a, &a, &a), 0f32);
+        assert_eqf!(triangle_area(&a, &b, &b), 0f32);
+
+        let square = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+        ];
+        assert_eqf!(polygon_area(&square), 100f32);
+        let reversed: Vec<Point> = square.iter().rev().cloned().collect();
+        assert_eqf!(polygon_area(&reversed), -100f32);
+        assert_eqf!(polygon_area(&square[..2]), 0f32);
+    }
+
+    #[test]
+    fn inset_polygon_test() {
+        let clockwise = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+        ];
+        let counter_clockwise: Vec<Point> = clockwise.iter().rev().cloned().collect();
+        for polygon in &[clockwise, counter_clockwise] {
+            for point in inset_polygon(polygon, 1f32) {
+                assert_eqf!((point.x - 5f32).abs(), 4f32);
+                assert_eqf!((point.y - 5f32).abs(), 4f32);
+            }
+        }
+    }
+
+    #[test]
+    fn offset_polygon_convex_test() {
+        let square = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+        ];
+        for point in offset_polygon(&square, 2f32).unwrap() {
+            assert_eqf!((point.x - 5f32).abs(), 3f32);
+            assert_eqf!((point.y - 5f32).abs(), 3f32);
+        }
+        // outward buffer
+        for point in offset_polygon(&square, -2f32).unwrap() {
+            assert_eqf!((point.x - 5f32).abs(), 7f32);
+            assert_eqf!((point.y - 5f32).abs(), 7f32);
+        }
+        let triangle = [
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(0f32, 30f32, 0f32),
+            Point::new(40f32, 0f32, 0f32),
+        ];
+        let inset = offset_polygon(&triangle, 1f32).unwrap();
+        // each vertex is offset from both edges meeting at the original vertex
+        for (i, point) in inset.iter().enumerate() {
+            let prev = &triangle[(i + 2) % 3];
+            let next = &triangle[(i + 1) % 3];
+            assert!(point_in_polygon(point, &triangle));
+            assert_eqf!(
+                point_to_segment_distance_2d(point, prev, &triangle[i]),
+                1f32
+            );
+            assert_eqf!(
+                point_to_segment_distance_2d(point, &triangle[i], next),
+                1f32
+            );
+        }
+    }
+
+    #[test]
+    fn offset_polygon_concave_test() {
+        // l shaped polygon with a reflex corner at (10, 10)
+        let l_shape = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(20f32, 0f32, 0f32),
+            Point::new(20f32, 10f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(10f32, 20f32, 0f32),
+            Point::new(0f32, 20f32, 0f32),
+        ];
+        let expected = [
+            (1f32, 1f32),
+            (19f32, 1f32),
+            (19f32, 9f32),
+            (9f32, 9f32),
+            (9f32, 19f32),
+            (1f32, 19f32),
+        ];
+        let inset = offset_polygon(&l_shape, 1f32).unwrap();
+        for (point, &(x, y)) in inset.iter().zip(expected.iter()) {
+            assert_eqf!(point.x, x);
+            assert_eqf!(point.y, y);
+        }
+        let grown = offset_polygon(&l_shape, -1f32).unwrap();
+        assert_eqf!(grown[3].x, 11f32);
+        assert_eqf!(grown[3].y, 11f32);
+    }
+
+    #[test]
+    fn offset_polygon_degenerate_test() {
+        let line = [
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(5f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+        ];
+        assert_eq!(
+            offset_polygon(&line, 1f32).err(),
+            Some(PathfinderError::DegeneratePolygon)
+        );
+        assert_eq!(
+            offset_polygon(&line[..2], 1f32).err(),
+            Some(PathfinderError::DegeneratePolygon)
+        );
+        let repeated = [
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+        ];
+        assert_eq!(
+            offset_polygon(&repeated, 1f32).err(),
+            Some(PathfinderError::DegeneratePolygon)
+        );
+        let spike = [
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(20f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+        ];
+        assert_eq!(
+            offset_polygon(&spike, 1f32).err(),
+            Some(PathfinderError::DegeneratePolygon)
+        );
+
+        // the tip of a 2m wide notch collapses once inset by 1m, leaving the rectangle
+        let notch = [
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(30f32, 0f32, 0f32),
+            Point::new(30f32, 20f32, 0f32),
+            Point::new(10f32, 20f32, 0f32),
+            Point::new(10f32, 11f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+            Point::new(10f32, 9f32, 0f32),
+        ];
+        let inset = offset_polygon(&notch, 1f32).unwrap();
+        assert_eq!(inset.len(), 6);
+        for point in &inset {
+            assert_eqf!((point.x - 20f32).abs().max(9f32), 9f32);
+            assert_eqf!((point.y - 10f32).abs().max(9f32), 9f32);
+        }
+        // a narrower inset keeps the tip inside the notch
+        let inset = offset_polygon(&notch, 0.5f32).unwrap();
+        assert_eq!(inset.len(), 7);
+        assert!(inset[5].x < 10f32);
+        // insetting past the middle of a square collapses every corner
+        let square = [
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+        ];
+        assert_eq!(
+            offset_polygon(&square, 6f32).err(),
+            Some(PathfinderError::DegeneratePolygon)
+        );
+        // an edge shorter than the inset flips
+        let sliver = [
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(20f32, 0f32, 0f32),
+            Point::new(20f32, 20f32, 0f32),
+            Point::new(11f32, 1f32, 0f32),
+            Point::new(10f32, 1f32, 0f32),
+            Point::new(0f32, 20f32, 0f32),
+        ];
+        assert!(offset_polygon(&sliver, 0.1f32).is_ok());
+        assert_eq!(
+            offset_polygon(&sliver, 2f32).err(),
+            Some(PathfinderError::DegeneratePolygon)
+        );
+    }
+
+    #[test]
+    fn point_in_polygon_test() {
+        let square = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+        ];
+        assert!(point_in_polygon(&Point::new(5f32, 5f32, 0f32), &square));
+        assert!(point_in_polygon(&Point::new(0.1f32, 9.9f32, 0f32), &square));
+        assert!(!point_in_polygon(&Point::new(-1f32, 5f32, 0f32), &square));
+        assert!(!point_in_polygon(&Point::new(5f32, 11f32, 0f32), &square));
+        assert!(!point_in_polygon(&Point::new(20f32, 20f32, 0f32), &square));
+
+        let closest =
+            closest_point_on_segment(&Point::new(-5f32, 3f32, 7f32), &square[0], &square[1]);
+        assert_eqf!(closest.x, 0f32);
+        assert_eqf!(closest.y, 3f32);
+        assert_eqf!(closest.z, 7f32);
+    }
+
+    #[test]
+    fn winding_number_test() {
+        let square = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(10f32, 0f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(0f32, 10f32, 0f32),
+        ];
+        assert_eq!(winding_number(&Point::new(5f32, 5f32, 0f32), &square), 1);
+        assert_eq!(winding_number(&Point::new(15f32, 5f32, 0f32), &square), 0);
+        let clockwise: Vec<Point> = square.iter().rev().cloned().collect();
+        assert_eq!(
+            winding_number(&Point::new(5f32, 5f32, 0f32), &clockwise),
+            -1
+        );
+
+        // the hole of a u shape is outside
+        let u_shape = vec![
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(30f32, 0f32, 0f32),
+            Point::new(30f32, 30f32, 0f32),
+            Point::new(20f32, 30f32, 0f32),
+            Point::new(20f32, 10f32, 0f32),
+            Point::new(10f32, 10f32, 0f32),
+            Point::new(10f32, 30f32, 0f32),
+            Point::new(0f32, 30f32, 0f32),
+        ];
+        for &(x, y) in &[(5f32, 20f32), (25f32, 20f32), (15f32, 5f32), (15f32, 20f32)] {
+            let p = Point::new(x, y, 0f32);
+            assert_eq!(
+                winding_number(&p, &u_shape) != 0,
+                point_in_polygon(&p, &u_shape)
+            );
+        }
+        assert_eq!(winding_number(&Point::new(15f32, 20f32, 0f32), &u_shape), 0);
+    }
+
     #[test]
     fn is_between() {
         let a = Point::new(40f32, 40f32, 10f32);
@@ -424,4 +1577,498 @@ mod test {
         assert_eq!(intersect(&a, &b, &c, &d), false);
         assert_eq!(intersect(&a, &c, &b, &d), true);
     }
+
+    // checks that hull is strictly convex, counter-clockwise, made of input points and
+    // contains every input point
+    fn assert_valid_hull(points: &[Point], hull: &[Point]) {
+        let size = hull.len();
+        assert!(size >= 3);
+        for i in 0..size {
+            let (a, b, c) = (&hull[i], &hull[(i + 1) % size], &hull[(i + 2) % size]);
+            assert!(triangle_area(a, b, c) > 0f32);
+            assert!(points.iter().any(|p| p.x == a.x && p.y == a.y));
+            for p in points {
+                assert!(triangle_area(a, b, p) >= -THRESHOLD);
+            }
+        }
+    }
+
+    fn hull_of(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+        let points: Vec<Point> = points
+            .iter()
+            .map(|&(x, y)| Point::new(x, y, 0f32))
+            .collect();
+        convex_hull(&points).iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn convex_hull_empty() {
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    #[test]
+    fn convex_hull_single_point() {
+        assert_eq!(hull_of(&[(3f32, 4f32)]), [(3f32, 4f32)]);
+    }
+
+    #[test]
+    fn convex_hull_two_points() {
+        assert_eq!(
+            hull_of(&[(5f32, 5f32), (0f32, 0f32)]),
+            [(0f32, 0f32), (5f32, 5f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_identical_points() {
+        assert_eq!(hull_of(&[(1f32, 1f32); 5]), [(1f32, 1f32)]);
+    }
+
+    #[test]
+    fn convex_hull_duplicate_pair() {
+        assert_eq!(
+            hull_of(&[(2f32, 0f32), (0f32, 0f32), (2f32, 0f32), (0f32, 0f32)]),
+            [(0f32, 0f32), (2f32, 0f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_triangle() {
+        assert_eq!(
+            hull_of(&[(0f32, 10f32), (10f32, 0f32), (0f32, 0f32)]),
+            [(0f32, 0f32), (10f32, 0f32), (0f32, 10f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_clockwise_input() {
+        assert_eq!(
+            hull_of(&[(0f32, 0f32), (0f32, 10f32), (10f32, 10f32), (10f32, 0f32)]),
+            [(0f32, 0f32), (10f32, 0f32), (10f32, 10f32), (0f32, 10f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_interior_points() {
+        assert_eq!(
+            hull_of(&[
+                (5f32, 5f32),
+                (0f32, 0f32),
+                (2f32, 7f32),
+                (10f32, 0f32),
+                (10f32, 10f32),
+                (8f32, 1f32),
+                (0f32, 10f32),
+            ]),
+            [(0f32, 0f32), (10f32, 0f32), (10f32, 10f32), (0f32, 10f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_colinear_line() {
+        assert_eq!(
+            hull_of(&[(2f32, 2f32), (0f32, 0f32), (3f32, 3f32), (1f32, 1f32)]),
+            [(0f32, 0f32), (3f32, 3f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_colinear_horizontal() {
+        assert_eq!(
+            hull_of(&[(4f32, 1f32), (-2f32, 1f32), (0f32, 1f32), (9f32, 1f32)]),
+            [(-2f32, 1f32), (9f32, 1f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_colinear_vertical() {
+        assert_eq!(
+            hull_of(&[(1f32, 4f32), (1f32, -3f32), (1f32, 0f32), (1f32, 8f32)]),
+            [(1f32, -3f32), (1f32, 8f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_colinear_with_duplicates() {
+        assert_eq!(
+            hull_of(&[
+                (1f32, 2f32),
+                (0f32, 0f32),
+                (1f32, 2f32),
+                (2f32, 4f32),
+                (0f32, 0f32)
+            ]),
+            [(0f32, 0f32), (2f32, 4f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_colinear_edges() {
+        // points along every edge of the square, including the closing edge
+        assert_eq!(
+            hull_of(&[
+                (0f32, 0f32),
+                (5f32, 0f32),
+                (10f32, 0f32),
+                (10f32, 5f32),
+                (10f32, 10f32),
+                (5f32, 10f32),
+                (0f32, 10f32),
+                (0f32, 5f32),
+                (0f32, 2f32),
+            ]),
+            [(0f32, 0f32), (10f32, 0f32), (10f32, 10f32), (0f32, 10f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_duplicate_corners() {
+        assert_eq!(
+            hull_of(&[
+                (0f32, 0f32),
+                (4f32, 0f32),
+                (0f32, 0f32),
+                (0f32, 4f32),
+                (4f32, 0f32),
+                (0f32, 4f32),
+            ]),
+            [(0f32, 0f32), (4f32, 0f32), (0f32, 4f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_lowest_point_tie() {
+        // lowest point is chosen by y, then by x
+        assert_eq!(
+            hull_of(&[(5f32, 0f32), (0f32, 0f32), (2f32, 3f32)]),
+            [(0f32, 0f32), (5f32, 0f32), (2f32, 3f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_negative_coordinates() {
+        assert_eq!(
+            hull_of(&[(-5f32, -5f32), (5f32, -5f32), (0f32, 0f32), (0f32, 5f32)]),
+            [(-5f32, -5f32), (5f32, -5f32), (0f32, 5f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_ignores_z() {
+        let points = [
+            Point::new(0f32, 0f32, 10f32),
+            Point::new(0f32, 0f32, 20f32),
+            Point::new(4f32, 0f32, 30f32),
+            Point::new(0f32, 4f32, 40f32),
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(hull.len(), 3);
+        assert_eq!(hull[1].z, 30f32);
+        assert_eq!(hull[2].z, 40f32);
+    }
+
+    #[test]
+    fn convex_hull_nearly_colinear() {
+        // the middle point is barely above the line, so it is part of the hull
+        let hull = hull_of(&[
+            (0f32, 0f32),
+            (500f32, 0.01f32),
+            (1000f32, 0f32),
+            (500f32, -1f32),
+        ]);
+        assert_eq!(
+            hull,
+            [
+                (500f32, -1f32),
+                (1000f32, 0f32),
+                (500f32, 0.01f32),
+                (0f32, 0f32)
+            ]
+        );
+    }
+
+    #[test]
+    fn convex_hull_nearly_colinear_line() {
+        // points on a line with a large offset, where f32 cross products lose the sign
+        let points: Vec<Point> = (0..50)
+            .map(|i| {
+                let t = i as f32 * 0.37f32;
+                Point::new(1000f32 + t, 2000f32 + 2f32 * t, 0f32)
+            })
+            .collect();
+        let hull = convex_hull(&points);
+        assert!(hull.len() <= 3);
+        if hull.len() == 3 {
+            assert_valid_hull(&points, &hull);
+        }
+    }
+
+    #[test]
+    fn convex_hull_circle() {
+        let points: Vec<Point> = (0..36)
+            .map(|i| {
+                let angle = i as f32 * PI / 18f32;
+                Point::new(100f32 * angle.cos(), 100f32 * angle.sin(), 0f32)
+            })
+            .collect();
+        let hull = convex_hull(&points);
+        assert_eq!(hull.len(), 36);
+        assert_valid_hull(&points, &hull);
+    }
+
+    #[test]
+    fn convex_hull_grid() {
+        let mut points = Vec::new();
+        for x in 0..10 {
+            for y in 0..10 {
+                points.push(Point::new(x as f32, y as f32, 0f32));
+            }
+        }
+        assert_eq!(
+            convex_hull(&points)
+                .iter()
+                .map(|p| (p.x, p.y))
+                .collect::<Vec<_>>(),
+            [(0f32, 0f32), (9f32, 0f32), (9f32, 9f32), (0f32, 9f32)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_random_stress() {
+        let mut rng = thread_rng();
+        let points: Vec<Point> = (0..1000)
+            .map(|_| {
+                Point::new(
+                    rng.gen_range(-500f32, 500f32),
+                    rng.gen_range(-500f32, 500f32),
+                    0f32,
+                )
+            })
+            .collect();
+        let hull = convex_hull(&points);
+        assert_valid_hull(&points, &hull);
+        assert!(polygon_area(&hull) <= 1000f32 * 1000f32);
+    }
+
+    // point on the circle at c with radius r at angle, see rotate_point
+    fn on_circle(c: &Point, r: f32, angle: f32) -> Point {
+        Point::new(c.x + r * angle.cos(), c.y + r * angle.sin(), 0f32)
+    }
+
+    // check that each pair of angles gives a segment tangent to both circles
+    fn assert_tangents(c1: &Point, r1: f32, c2: &Point, r2: f32, tangents: &[(f32, f32)]) {
+        for &(i, j) in tangents {
+            assert!(i.abs() <= 2f32 * PI && j.abs() <= 2f32 * PI);
+            let (p1, p2) = (on_circle(c1, r1, i), on_circle(c2, r2, j));
+            let (dx, dy) = (p2.x - p1.x, p2.y - p1.y);
+            let length = (dx * dx + dy * dy).sqrt();
+            assert!(length > 0f32);
+            for (p, c) in [(p1, c1), (p2, c2)].iter() {
+                let radius = p.distance(c);
+                if radius > 0f32 {
+                    let dot = dx * (p.x - c.x) + dy * (p.y - c.y);
+                    assert!((dot / (length * radius)).abs() < THRESHOLD);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tangent_angles_equal_radii() {
+        let (c1, c2) = (Point::new(0f32, 0f32, 0f32), Point::new(100f32, 0f32, 0f32));
+        let (tangents, sentinels) = circle_circle_tangent_angles(&c1, 10f32, &c2, 10f32);
+        assert_eq!(tangents.len(), 4);
+        assert!(sentinels.is_none());
+        // outer tangents run parallel to the line between the centers
+        assert_eqf!(tangents[0].0, 3f32 * PI / 2f32);
+        assert_eqf!(tangents[0].1, 3f32 * PI / 2f32);
+        assert_eqf!(tangents[1].0, -3f32 * PI / 2f32);
+        assert_eqf!(tangents[1].1, -3f32 * PI / 2f32);
+        assert_tangents(&c1, 10f32, &c2, 10f32, &tangents);
+    }
+
+    #[test]
+    fn tangent_angles_far_apart() {
+        let (c1, c2) = (
+            Point::new(0f32, 0f32, 0f32),
+            Point::new(300f32, 400f32, 0f32),
+        );
+        for &(r1, r2) in &[(30f32, 10f32), (10f32, 30f32)] {
+            let (tangents, sentinels) = circle_circle_tangent_angles(&c1, r1, &c2, r2);
+            assert_eq!(tangents.len(), 4);
+            assert!(sentinels.is_none());
+            assert_tangents(&c1, r1, &c2, r2, &tangents);
+        }
+
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let c2 = Point::new(
+                rng.gen_range(-500f32, 500f32),
+                rng.gen_range(-500f32, 500f32),
+                0f32,
+            );
+            let r1 = rng.gen_range(1f32, 50f32);
+            let r2 = rng.gen_range(1f32, 50f32);
+            if c1.distance(&c2) <= r1 + r2 + 1f32 {
+                continue;
+            }
+            let (tangents, _) = circle_circle_tangent_angles(&c1, r1, &c2, r2);
+            assert_tangents(&c1, r1, &c2, r2, &tangents);
+        }
+    }
+
+    #[test]
+    fn tangent_angles_touching() {
+        let c1 = Point::new(0f32, 0f32, 0f32);
+        // touching circles only have outer tangents, and their sentinels meet where they touch
+        let c2 = Point::new(20f32, 0f32, 0f32);
+        let (tangents, sentinels) = circle_circle_tangent_angles(&c1, 10f32, &c2, 10f32);
+        assert_eq!(tangents.len(), 2);
+        assert_tangents(&c1, 10f32, &c2, 10f32, &tangents);
+        let touch = Point::new(10f32, 0f32, 0f32);
+        for (i, j) in sentinels.unwrap() {
+            assert!(on_circle(&c1, 10f32, i).distance(&touch) < THRESHOLD);
+            assert!(on_circle(&c2, 10f32, j).distance(&touch) < THRESHOLD);
+        }
+
+        // overlapping circles have sentinels on both intersection points
+        let c2 = Point::new(15f32, 0f32, 0f32);
+        let (tangents, sentinels) = circle_circle_tangent_angles(&c1, 10f32, &c2, 8f32);
+        assert_eq!(tangents.len(), 2);
+        assert_tangents(&c1, 10f32, &c2, 8f32, &tangents);
+        let sentinels = sentinels.unwrap();
+        assert_eq!(sentinels.len(), 4);
+        for (i, j) in sentinels {
+            let (p1, p2) = (on_circle(&c1, 10f32, i), on_circle(&c2, 8f32, j));
+            assert_eqf!(p1.distance(&c2), 8f32);
+            assert_eqf!(p2.distance(&c1), 10f32);
+        }
+    }
+
+    #[test]
+    fn tangent_angles_sentinels_rotated() {
+        let c1 = Point::new(0f32, 0f32, 0f32);
+        let horizontal =
+            circle_circle_tangent_angles(&c1, 10f32, &Point::new(15f32, 0f32, 0f32), 8f32)
+                .1
+                .unwrap();
+        // same circles with the second one rotated around the first by a quarter turn
+        let c2 = rotate_point(&Point::new(15f32, 0f32, 0f32), PI / 2f32);
+        let (_, sentinels) = circle_circle_tangent_angles(&c1, 10f32, &c2, 8f32);
+        let sentinels = sentinels.unwrap();
+        assert_eq!(sentinels.len(), 4);
+        for (&(i, j), &(h_i, h_j)) in sentinels.iter().zip(horizontal.iter()) {
+            let (p1, p2) = (on_circle(&c1, 10f32, i), on_circle(&c2, 8f32, j));
+            assert_eqf!(p1.distance(&c2), 8f32);
+            assert_eqf!(p2.distance(&c1), 10f32);
+            // each sentinel is the horizontal one rotated, on the same side of its ring
+            let rotated = rotate_point(&on_circle(&c1, 10f32, h_i), PI / 2f32);
+            assert!(p1.distance(&rotated) < THRESHOLD);
+            assert_eq!(i >= 0f32, h_i >= 0f32);
+            assert_eq!(j >= 0f32, h_j >= 0f32);
+        }
+    }
+
+    #[test]
+    fn tangent_angles_inside() {
+        let c1 = Point::new(0f32, 0f32, 0f32);
+        let (tangents, sentinels) =
+            circle_circle_tangent_angles(&c1, 50f32, &Point::new(10f32, 0f32, 0f32), 10f32);
+        assert!(tangents.is_empty());
+        assert!(sentinels.is_none());
+        let (tangents, sentinels) = circle_circle_tangent_angles(&c1, 10f32, &c1, 10f32);
+        assert!(tangents.is_empty());
+        assert!(sentinels.is_none());
+    }
+
+    #[test]
+    fn tangent_angles_zero_radius() {
+        let (c1, c2) = (Point::new(0f32, 0f32, 0f32), Point::new(100f32, 0f32, 0f32));
+        // a point has one tangent to each side of the circle
+        let (tangents, sentinels) = circle_circle_tangent_angles(&c1, 0f32, &c2, 10f32);
+        assert_eq!(tangents.len(), 2);
+        assert!(sentinels.is_none());
+        assert_tangents(&c1, 0f32, &c2, 10f32, &tangents);
+        let (tangents, sentinels) = circle_circle_tangent_angles(&c2, 10f32, &c1, 0f32);
+        assert_eq!(tangents.len(), 2);
+        assert!(sentinels.is_none());
+        assert_tangents(&c2, 10f32, &c1, 0f32, &tangents);
+
+        // two points are joined by the segment between them
+        let (tangents, sentinels) = circle_circle_tangent_angles(&c1, 0f32, &c2, 0f32);
+        assert_eq!(tangents.len(), 2);
+        assert!(sentinels.is_none());
+    }
+
+    fn polygon_of(points: &[(f32, f32)]) -> Vec<Point> {
+        points
+            .iter()
+            .map(|&(x, y)| Point::new(x, y, 0f32))
+            .collect()
+    }
+
+    // checks that polygon is counter-clockwise with the expected area, inside both inputs
+    fn assert_intersection(polygon: &[Point], a: &[Point], b: &[Point], area: f32) {
+        assert!((polygon_area(polygon) - area).abs() < 1e-2);
+        for p in polygon {
+            for input in &[a, b] {
+                let inside = point_in_polygon(p, input);
+                let on_edge = (0..input.len()).any(|i| {
+                    point_to_segment_distance_2d(p, &input[i], &input[(i + 1) % input.len()])
+                        < THRESHOLD
+                });
+                assert!(inside || on_edge);
+            }
+        }
+    }
+
+    #[test]
+    fn polygon_intersection_overlapping_squares() {
+        let a = polygon_of(&[(0f32, 0f32), (10f32, 0f32), (10f32, 10f32), (0f32, 10f32)]);
+        let b = polygon_of(&[(5f32, 5f32), (15f32, 5f32), (15f32, 15f32), (5f32, 15f32)]);
+        let polygon = polygon_intersection(&a, &b);
+        assert_eq!(polygon.len(), 4);
+        assert_intersection(&polygon, &a, &b, 25f32);
+        // clockwise input gives the same counter-clockwise result
+        let reversed: Vec<Point> = b.iter().rev().cloned().collect();
+        assert_intersection(&polygon_intersection(&a, &reversed), &a, &b, 25f32);
+    }
+
+    #[test]
+    fn polygon_intersection_contained() {
+        let outer = polygon_of(&[(0f32, 0f32), (20f32, 0f32), (20f32, 20f32), (0f32, 20f32)]);
+        let inner = polygon_of(&[(5f32, 5f32), (10f32, 5f32), (10f32, 10f32), (5f32, 10f32)]);
+        assert_intersection(&polygon_intersection(&outer, &inner), &outer, &inner, 25f32);
+        assert_intersection(&polygon_intersection(&inner, &outer), &outer, &inner, 25f32);
+    }
+
+    #[test]
+    fn polygon_intersection_disjoint() {
+        let a = polygon_of(&[(0f32, 0f32), (10f32, 0f32), (10f32, 10f32), (0f32, 10f32)]);
+        let b = polygon_of(&[(20f32, 0f32), (30f32, 0f32), (30f32, 10f32), (20f32, 10f32)]);
+        assert!(polygon_intersection(&a, &b).is_empty());
+        // sharing an edge only is not an overlap
+        let c = polygon_of(&[(10f32, 0f32), (20f32, 0f32), (20f32, 10f32), (10f32, 10f32)]);
+        assert!(polygon_intersection(&a, &c).is_empty());
+    }
+
+    #[test]
+    fn polygon_intersection_l_shape() {
+        let l = polygon_of(&[
+            (0f32, 0f32),
+            (20f32, 0f32),
+            (20f32, 10f32),
+            (10f32, 10f32),
+            (10f32, 20f32),
+            (0f32, 20f32),
+        ]);
+        let square = polygon_of(&[(5f32, 5f32), (15f32, 5f32), (15f32, 15f32), (5f32, 15f32)]);
+        // the notch of the l cuts a corner out of the square
+        let polygon = polygon_intersection(&l, &square);
+        assert_eq!(polygon.len(), 6);
+        assert_intersection(&polygon, &l, &square, 75f32);
+        // the convex polygon is the clip whichever order they are given in
+        let polygon = polygon_intersection(&square, &l);
+        assert_intersection(&polygon, &l, &square, 75f32);
+    }
 }
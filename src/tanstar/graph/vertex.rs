@@ -70,22 +70,34 @@ impl fmt::Display for Vertex {
 
 impl Vertex {
     pub fn new(num_vertex: &mut i32, node: &Node, angle: f32, connection: Vec<Connection>) -> Self {
+        Self::with_radius(num_vertex, node, angle, node.radius, connection)
+    }
+
+    // Vertex on a ring of radius around the node origin, smaller than the node radius where the
+    // node is tapered
+    pub fn with_radius(
+        num_vertex: &mut i32,
+        node: &Node,
+        angle: f32,
+        radius: f32,
+        connection: Vec<Connection>,
+    ) -> Self {
         Self::base_vertex(
             num_vertex,
-            node.radius,
+            radius,
             angle,
-            Point::from((node, angle)),
+            Point::from((node, angle, radius)),
             connection,
             false,
         )
     }
 
-    pub fn new_sentinel(num_vertex: &mut i32, node: &Node, angle: f32) -> Self {
+    pub fn new_sentinel(num_vertex: &mut i32, node: &Node, angle: f32, radius: f32) -> Self {
         Self::base_vertex(
             num_vertex,
-            node.radius,
+            radius,
             angle,
-            Point::from((node, angle)),
+            Point::from((node, angle, radius)),
             vec![],
             true,
         )
@@ -122,6 +134,119 @@ impl Vertex {
         }
     }
 
+    // Count the vertices in the ring this vertex belongs to, including itself
+    // returns none if the chain is broken or cycles without wrapping back to this vertex
+    pub fn ring_length(&self) -> Option<usize> {
+        let mut visited = HashSet::new();
+        let mut current = match self.next {
+            Some(ref next) => next.clone(),
+            None => return None,
+        };
+        let mut length = 1;
+        while current.borrow().index != self.index {
+            if !visited.insert(current.borrow().index) {
+                return None;
+            }
+            length += 1;
+            let next = match current.borrow().next {
+                Some(ref next) => next.clone(),
+                None => return None,
+            };
+            current = next;
+        }
+        Some(length)
+    }
+
+    // Count the vertices in the ring, giving up once more than limit vertices are visited
+    // returns none if the chain is broken or does not wrap back to this vertex within limit
+    pub fn ring_length_within(&self, limit: usize) -> Option<usize> {
+        let mut current = match self.next {
+            Some(ref next) => next.clone(),
+            None => return None,
+        };
+        let mut length = 1;
+        while current.borrow().index != self.index {
+            if length >= limit {
+                return None;
+            }
+            length += 1;
+            let next = match current.borrow().next {
+                Some(ref next) => next.clone(),
+                None => return None,
+            };
+            current = next;
+        }
+        Some(length)
+    }
+
+    // Whether this vertex lies on a ring of node, within a centimeter
+    // vertices do not keep a reference to their node, so this compares positions. Rings of
+    // tapered nodes are smaller than the node radius
+    pub fn is_on_node(&self, node: &Node) -> bool {
+        const TOLERANCE: f32 = 0.01;
+        self.radius < node.radius + TOLERANCE
+            && (self.location.distance(&node.origin) - self.radius).abs() < TOLERANCE
+    }
+
+    // Format the vertex as a csv row of index,angle,x,y,z,g_cost,f_cost,sentinel
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.index,
+            self.angle,
+            self.location.x,
+            self.location.y,
+            self.location.z,
+            self.g_cost,
+            self.f_cost,
+            self.sentinel
+        )
+    }
+
+    // Admissible estimate of the cost from this vertex to goal
+    // a vertex without connections can only be left by following the ring to a vertex with
+    // connections, so the arc to the cheapest such exit is added to its straight line distance
+    pub fn heuristic_to(&self, goal: &Point) -> f32 {
+        let direct = self.location.distance(goal);
+        if !self.connection.is_empty() {
+            return direct;
+        }
+        let mut current = match self.next {
+            Some(ref next) => next.clone(),
+            None => return direct,
+        };
+        let mut arc = self.get_neighbor_weight();
+        let mut best = f32::INFINITY;
+        while arc < best {
+            let next = {
+                let vertex = current.borrow();
+                // search does not continue past sentinels
+                if vertex.sentinel {
+                    break;
+                }
+                if vertex.index != HEADER_VERTEX_INDEX && !vertex.connection.is_empty() {
+                    best = best.min(arc + vertex.location.distance(goal));
+                }
+                match vertex.next {
+                    // stop once back at this vertex, which may already be borrowed by the caller
+                    Some(ref next) if ::std::ptr::eq(next.as_ptr(), self) => break,
+                    Some(ref next) => {
+                        arc += vertex.get_neighbor_weight();
+                        next.clone()
+                    }
+                    None => break,
+                }
+            };
+            current = next;
+        }
+        // no exit found within reach, fall back to the straight line distance
+        if best.is_finite() {
+            best.max(direct)
+        } else {
+            direct
+        }
+    }
+
     pub fn get_neighbor_weight(&self) -> f32 {
         if let Some(ref neighbor) = self.next {
             return arc_length(self.angle, neighbor.borrow().angle, self.radius);
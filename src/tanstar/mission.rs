@@ -0,0 +1,2753 @@
+// mission.rs
+// Contains mission level planning built on top of the path finding
+
+use super::*;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+// Number of points used to approximate a loiter circle
+const LOITER_POINTS: usize = 16;
+// Largest distance between the points of fixed-wing paths, in meters
+const DUBINS_STEP: f32 = 5f32;
+// Most turning radii added to the buffer when the curves through the shortest path are blocked
+const DUBINS_MAX_MARGIN: usize = 2;
+// Heuristic inflation of the first anytime search, and how much it drops after each solution
+const ANYTIME_INITIAL_WEIGHT: f32 = 3f32;
+const ANYTIME_WEIGHT_STEP: f32 = 0.5f32;
+
+// (from, to) vertex indices of each segment of a path
+type PathEdges = Vec<(i32, i32)>;
+
+// Part of the graph a waypoint of a path came from, so ground control can pick its icon
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WaypointType {
+    StartPoint,
+    GoalPoint,
+    PathHugStart,  // first vertex of a node flown around, reached by a tangent
+    PathHugEnd,    // vertex reached by flying around the ring of its node
+    FlyoverApex,   // vertex reached by a tangent flying over an obstacle
+    DirectSegment, // vertex reached and left by tangents
+}
+
+// Planner with one setting replaced for the searches made through it. The old value is put
+// back when the override is dropped, even when a search panics
+struct Override<'a, V> {
+    planner: &'a mut Tanstar,
+    field: fn(&mut Tanstar) -> &mut V,
+    old: Option<V>,
+}
+
+impl<'a, V> Deref for Override<'a, V> {
+    type Target = Tanstar;
+
+    fn deref(&self) -> &Tanstar {
+        self.planner
+    }
+}
+
+impl<'a, V> DerefMut for Override<'a, V> {
+    fn deref_mut(&mut self) -> &mut Tanstar {
+        self.planner
+    }
+}
+
+impl<'a, V> Drop for Override<'a, V> {
+    fn drop(&mut self) {
+        if let Some(old) = self.old.take() {
+            *(self.field)(self.planner) = old;
+        }
+    }
+}
+
+impl Tanstar {
+    // Replace the setting picked by field with value until the returned planner is dropped
+    fn with_override<V>(&mut self, field: fn(&mut Tanstar) -> &mut V, value: V) -> Override<'_, V> {
+        let old = mem::replace(field(self), value);
+        Override {
+            planner: self,
+            field,
+            old: Some(old),
+        }
+    }
+
+    // Find a path from every agent to a common goal. Shorter paths are padded with a holding
+    // pattern so that all paths are as long as the longest one; assuming a uniform airspeed,
+    // every agent arrives at the goal at the same time.
+    // Each path starts at the agent location and ends at the goal
+    pub fn find_rendezvous_path(
+        &mut self,
+        agents: &[Location],
+        goal: Location,
+    ) -> Result<Vec<Vec<Location>>, PathfinderError> {
+        let mut paths = Vec::new();
+        for agent in agents {
+            let waypoints = match self.adjust_path::<()>(*agent, goal) {
+                Some(waypoints) => waypoints,
+                None => return Err(PathfinderError::NoPathFound),
+            };
+            paths.push(Self::assemble_path(*agent, &waypoints, goal));
+        }
+
+        let lengths: Vec<f32> = paths.iter().map(|path| self.path_length(path)).collect();
+        let longest = lengths.iter().cloned().fold(0f32, f32::max);
+        for (path, length) in paths.iter_mut().zip(lengths) {
+            self.insert_holding_pattern(path, longest - length);
+        }
+        Ok(paths)
+    }
+
+    // Find a path from start to goal passing through every must pass location in order
+    // The path includes start, goal and every must pass location, each appearing once
+    pub fn find_path_with_waypoints(
+        &mut self,
+        start: Location,
+        goal: Location,
+        must_pass: &[Location],
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let mut path = vec![start];
+        let mut current = start;
+        for &next in must_pass.iter().chain(Some(&goal)) {
+            if next == current {
+                continue;
+            }
+            match self.adjust_path::<()>(current, next) {
+                Some(waypoints) => path.extend(waypoints.iter().map(|wp| wp.location)),
+                None => return Err(PathfinderError::NoPathFound),
+            }
+            path.push(next);
+            current = next;
+        }
+        Ok(self.deduplicate_waypoints(path))
+    }
+
+    // Merge consecutive waypoints closer than the waypoint tolerance
+    // the first waypoint of each group is kept, except that the last waypoint always ends the path
+    pub fn deduplicate_waypoints(&self, waypoints: Vec<Location>) -> Vec<Location> {
+        let mut merged: Vec<Location> = Vec::new();
+        let count = waypoints.len();
+        for (i, waypoint) in waypoints.into_iter().enumerate() {
+            let close = match merged.last() {
+                Some(last) => {
+                    let a = Point::from((last, &self.origin));
+                    let b = Point::from((&waypoint, &self.origin));
+                    a.distance3d(&b) < self.waypoint_tolerance
+                }
+                None => false,
+            };
+            if !close {
+                merged.push(waypoint);
+            } else if i == count - 1 && merged.len() > 1 {
+                let end = merged.len() - 1;
+                merged[end] = waypoint;
+            }
+        }
+        merged
+    }
+
+    // Plan every (start, goal) query in order, each path including its start and goal
+    // search costs are cleared between queries so no query depends on the ones before it
+    pub fn find_path_batch(
+        &mut self,
+        queries: &[(Location, Location)],
+    ) -> Vec<Result<Vec<Location>, PathfinderError>> {
+        let mut results = Vec::new();
+        for &(start, goal) in queries {
+            self.reset_vertex_costs();
+            results.push(
+                self.find_shortest_path::<()>(start, goal)
+                    .map(|waypoints| Self::assemble_path(start, &waypoints, goal)),
+            );
+        }
+        results
+    }
+
+    // Clear the costs and parents left on every vertex by previous searches
+    fn reset_vertex_costs(&mut self) {
+        for node in &self.nodes {
+            for vertex in node.borrow().vertices() {
+                let mut vertex = vertex.borrow_mut();
+                vertex.f_cost = -1f32;
+                vertex.g_cost = -1f32;
+                vertex.parent = None;
+            }
+        }
+    }
+
+    // Find a path from start to goal along with the (from, to) vertex indices of every segment
+    // the start and end of the path are the temporary vertices START_VERTEX_INDEX and
+    // END_VERTEX_INDEX, and consecutive vertices on the same node are joined by an arc
+    pub fn find_path_returning_graph_edges(
+        &mut self,
+        start: Location,
+        goal: Location,
+    ) -> Result<(Vec<Location>, PathEdges), PathfinderError> {
+        let waypoints = self.find_shortest_path::<()>(start, goal)?;
+        let path = Self::assemble_path(start, &waypoints, goal);
+        let edges = self
+            .last_path_vertices
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        Ok((path, edges))
+    }
+
+    // Type of each waypoint of a path, from start to goal
+    // the types are kept from the search of the last path found, and any other path only has
+    // its start and goal told apart from direct segments
+    pub fn path_waypoint_types(&self, waypoints: &[Location]) -> Vec<WaypointType> {
+        let is_last_path = match self.last_path {
+            Some(ref path) => {
+                path.len() == waypoints.len()
+                    && path.iter().zip(waypoints).all(|(a, b)| {
+                        Point::from((a, &self.origin)).distance3d(&Point::from((b, &self.origin)))
+                            < 0.01
+                    })
+            }
+            None => false,
+        };
+        if is_last_path && self.last_path_types.len() == waypoints.len() {
+            return self.last_path_types.clone();
+        }
+        (0..waypoints.len())
+            .map(|i| {
+                if i == 0 {
+                    WaypointType::StartPoint
+                } else if i == waypoints.len() - 1 {
+                    WaypointType::GoalPoint
+                } else {
+                    WaypointType::DirectSegment
+                }
+            })
+            .collect()
+    }
+
+    // Number of arcs a path flies around obstacles, each a PathHugStart followed by a PathHugEnd
+    // the types come from path_waypoint_types, so only the last path found can have arcs
+    pub fn count_path_hug_arcs(&self, waypoints: &[Location]) -> usize {
+        let mut arcs = 0;
+        let mut hugging = false;
+        for kind in self.path_waypoint_types(waypoints) {
+            match kind {
+                WaypointType::PathHugStart => hugging = true,
+                WaypointType::PathHugEnd if hugging => {
+                    arcs += 1;
+                    hugging = false;
+                }
+                _ => (),
+            }
+        }
+        arcs
+    }
+
+    // Find a path from start to goal that never climbs above max_alt, going around obstacles
+    // that can not be flown over under the limit. Start and goal must be under the limit
+    pub fn find_path_under_altitude(
+        &mut self,
+        start: Location,
+        goal: Location,
+        max_alt: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        for location in &[start, goal] {
+            if location.alt() > max_alt {
+                return Err(PathfinderError::AltitudeOutOfRange {
+                    altitude: location.alt(),
+                });
+            }
+        }
+        match self.find_shortest_path::<()>(start, goal) {
+            Ok(waypoints) => Ok(Self::assemble_path(start, &waypoints, goal)),
+            Err(PathfinderError::NoPathFound) => {
+                Err(PathfinderError::NoPathUnderAltitudeLimit { limit: max_alt })
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Find a path from start to goal that never flies over an obstacle, for payloads that
+    // must keep clear of everything below them
+    pub fn find_path_no_flyover(
+        &mut self,
+        start: Location,
+        goal: Location,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let result = self
+            .with_override(|planner| &mut planner.no_flyover, true)
+            .find_shortest_path::<()>(start, goal);
+        match result {
+            Ok(waypoints) => Ok(Self::assemble_path(start, &waypoints, goal)),
+            Err(PathfinderError::NoPathFound) => Err(PathfinderError::NoPathWithoutFlyover),
+            Err(error) => Err(error),
+        }
+    }
+
+    // Find a path from start to goal spending as little time as possible in sight of the threats
+    // every edge costs its length plus the threat weight times the seconds flown at airspeed and
+    // the number of threats with a line of sight to the middle of the edge
+    pub fn find_path_minimizing_exposure(
+        &mut self,
+        start: Location,
+        goal: Location,
+        threat_locations: &[Location],
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let threats = threat_locations
+            .iter()
+            .map(|threat| Point::from((threat, &self.origin)))
+            .collect();
+        let waypoints = self
+            .with_override(|planner| &mut planner.threats, threats)
+            .find_shortest_path::<()>(start, goal)?;
+        Ok(Self::assemble_path(start, &waypoints, goal))
+    }
+
+    // Find a path from start to goal passing over as many targets as possible
+    // every edge covering targets within sensor_fov_m of its middle costs the coverage bonus less
+    // per target, so the path bends towards clusters of targets while clearing obstacles and
+    // staying in the flyzones
+    pub fn find_path_maximizing_sensor_coverage(
+        &mut self,
+        start: Location,
+        goal: Location,
+        sensor_fov_m: f32,
+        targets: &[Location],
+    ) -> Result<Vec<Location>, PathfinderError> {
+        assert!(sensor_fov_m >= 0f32);
+        let targets = targets
+            .iter()
+            .map(|target| Point::from((target, &self.origin)))
+            .collect();
+        let mut planner = self.with_override(|planner| &mut planner.targets, targets);
+        let waypoints = planner
+            .with_override(|planner| &mut planner.sensor_fov, sensor_fov_m)
+            .find_shortest_path::<()>(start, goal)?;
+        Ok(Self::assemble_path(start, &waypoints, goal))
+    }
+
+    // Find a path from start to goal turning at most max_turns times
+    // the search minimizes length plus the turn penalty of each turn, so the turn penalty
+    // should be set for paths with fewer turns to be preferred over shorter ones
+    pub fn find_path_with_max_turns(
+        &mut self,
+        start: Location,
+        goal: Location,
+        max_turns: usize,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let waypoints = self
+            .with_override(|planner| &mut planner.max_turns, Some(max_turns))
+            .find_shortest_path::<()>(start, goal)?;
+        Ok(Self::assemble_path(start, &waypoints, goal))
+    }
+
+    // Find a path a fixed-wing can fly from start to goal, leaving start and arriving at goal
+    // with the given compass headings. The path is made of Dubins curves with the turning
+    // radius, flown directly if they clear every obstacle or else through the waypoints of the
+    // shortest path, and is returned as points at most DUBINS_STEP apart
+    pub fn find_path_for_fixed_wing(
+        &mut self,
+        start: Location,
+        start_heading_deg: f32,
+        goal: Location,
+        goal_heading_deg: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        self.validate_location(&start)?;
+        self.validate_location(&goal)?;
+        let radius = self.config.turning_radius;
+        let to_angle = |heading: f32| (90f32 - heading).to_radians();
+        let (a, b) = (
+            Point::from((&start, &self.origin)),
+            Point::from((&goal, &self.origin)),
+        );
+        let (alpha, beta) = (to_angle(start_heading_deg), to_angle(goal_heading_deg));
+
+        let mut points = dubins_path(&a, alpha, &b, beta, radius, DUBINS_STEP);
+        if self.validate_curve(&points).is_err() {
+            // curves drift off the tangent lines by up to a turning radius, so separate planners
+            // with a growing buffer are tried until the chained curves clear every obstacle
+            let mut result = self.chain_dubins(start, alpha, goal, beta);
+            for margin in 1..=DUBINS_MAX_MARGIN {
+                if result.is_ok() {
+                    break;
+                }
+                let config = TConfig {
+                    buffer_size: self.config.buffer_size + margin as f32 * radius,
+                    ..self.config.clone()
+                };
+                result = self
+                    .detached_planner(config, self.obstacles.clone())
+                    .chain_dubins(start, alpha, goal, beta);
+            }
+            points = result?;
+        }
+
+        let mut path: Vec<Location> = points
+            .iter()
+            .map(|point| Location::from((point, &self.origin)))
+            .collect();
+        let last = path.len() - 1;
+        path[0] = start;
+        path[last] = goal;
+        Ok(path)
+    }
+
+    // Chain Dubins curves through the waypoints of the shortest path
+    // interior waypoints head along the chord between their neighbors
+    fn chain_dubins(
+        &mut self,
+        start: Location,
+        alpha: f32,
+        goal: Location,
+        beta: f32,
+    ) -> Result<Vec<Point>, PathfinderError> {
+        let radius = self.config.turning_radius;
+        let waypoints = self.find_shortest_path::<()>(start, goal)?;
+        let mut poses = vec![Point::from((&start, &self.origin))];
+        poses.extend(
+            waypoints
+                .iter()
+                .map(|wp| Point::from((&wp.location, &self.origin))),
+        );
+        poses.push(Point::from((&goal, &self.origin)));
+        let mut angles = vec![alpha];
+        for triple in poses.windows(3) {
+            angles.push((triple[2].y - triple[0].y).atan2(triple[2].x - triple[0].x));
+        }
+        angles.push(beta);
+
+        let mut points = vec![poses[0]];
+        for i in 0..poses.len() - 1 {
+            let curve = dubins_path(
+                &poses[i],
+                angles[i],
+                &poses[i + 1],
+                angles[i + 1],
+                radius,
+                DUBINS_STEP,
+            );
+            points.extend_from_slice(&curve[1..]);
+        }
+        self.validate_curve(&points)?;
+        Ok(points)
+    }
+
+    // Check that every segment between consecutive points stays in the flyzones and clears the
+    // obstacles at the lower altitude of its ends
+    fn validate_curve(&self, points: &[Point]) -> Result<(), PathfinderError> {
+        for leg in points.windows(2) {
+            match self.valid_path(&leg[0], &leg[1]) {
+                PathValidity::Invalid => return Err(PathfinderError::ExitsFlyzone),
+                PathValidity::Flyover(threshold) if threshold >= leg[0].z.min(leg[1].z) => {
+                    return Err(PathfinderError::IntersectsObstacle)
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Find the shortest path from start to goal no longer than max_range_m
+    // partial paths past the range are dropped during the search. If no path fits, the error
+    // holds the length of the shortest path found without the range limit
+    pub fn find_shortest_path_with_fuel_constraint(
+        &mut self,
+        start: Location,
+        goal: Location,
+        max_range_m: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let result = self
+            .with_override(|planner| &mut planner.max_range, Some(max_range_m))
+            .find_shortest_path::<()>(start, goal);
+        let waypoints = match result {
+            Err(PathfinderError::NoPathFound) => {
+                let waypoints = self.find_shortest_path::<()>(start, goal)?;
+                let path = Self::assemble_path(start, &waypoints, goal);
+                return Err(PathfinderError::PathExceedsFuelRange {
+                    best_distance: self.path_length(&path),
+                });
+            }
+            result => result?,
+        };
+        Ok(Self::assemble_path(start, &waypoints, goal))
+    }
+
+    // Find a path from start to goal that improves as long as time allows, with Anytime A*
+    // the first search inflates the heuristic ANYTIME_INITIAL_WEIGHT times to find a path
+    // quickly, and every later search lowers the inflation by ANYTIME_WEIGHT_STEP, dropping
+    // partial paths longer than the best path so far. The best path is returned once the
+    // inflation reaches 1 or the deadline passes
+    pub fn find_path_anytime(
+        &mut self,
+        start: Location,
+        goal: Location,
+        deadline: Instant,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        self.last_anytime_costs.clear();
+        let mut planner = self.with_override(|planner| &mut planner.planning_timeout, None);
+        let mut planner = planner.with_override(|planner| &mut planner.heuristic_weight, 1f32);
+        let mut planner = planner.with_override(|planner| &mut planner.max_range, None);
+        let mut best: Result<Vec<Location>, PathfinderError> = Err(PathfinderError::NoPathFound);
+        let mut weight = ANYTIME_INITIAL_WEIGHT;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                if best.is_err() {
+                    best = Err(PathfinderError::PlanningTimeout { partial_path: None });
+                }
+                break;
+            }
+            planner.planning_timeout = Some(deadline - now);
+            planner.heuristic_weight = weight;
+            match planner.find_shortest_path::<()>(start, goal) {
+                Ok(waypoints) => {
+                    // the bound only lets through paths at most as long as this one
+                    planner.max_range = Some(planner.last_path_cost);
+                    let cost = planner.last_path_cost;
+                    planner.last_anytime_costs.push(cost);
+                    best = Ok(Self::assemble_path(start, &waypoints, goal));
+                }
+                Err(PathfinderError::NoPathFound) if best.is_ok() => {}
+                Err(e) => {
+                    if best.is_err() {
+                        best = Err(e);
+                    }
+                    break;
+                }
+            }
+            if weight <= 1f32 {
+                break;
+            }
+            weight = (weight - ANYTIME_WEIGHT_STEP).max(1f32);
+        }
+        best
+    }
+
+    // Find the path from start to the closest reachable goal, and the index of that goal
+    // every goal is reached from a single dijkstra search, without a heuristic, which stops at
+    // the first goal it reaches, flying edges at the lowest altitude of start and every goal.
+    // Goals outside the flyzones or inside obstacles are skipped
+    pub fn find_path_multi_goal(
+        &mut self,
+        start: Location,
+        goals: &[Location],
+    ) -> Result<(Vec<Location>, usize), PathfinderError> {
+        let indices: Vec<usize> = (0..goals.len())
+            .filter(|&i| self.validate_location(&goals[i]).is_ok())
+            .collect();
+        if indices.is_empty() {
+            return Err(PathfinderError::AllGoalsUnreachable);
+        }
+        let valid: Vec<Location> = indices.iter().map(|&i| goals[i]).collect();
+        let result = self
+            .with_override(|planner| &mut planner.heuristic_weight, 0f32)
+            .search_goals::<()>(start, &valid);
+        match result {
+            Ok((waypoints, goal)) => Ok((
+                Self::assemble_path(start, &waypoints, valid[goal]),
+                indices[goal],
+            )),
+            Err(e @ PathfinderError::PlanningTimeout { .. }) => Err(e),
+            Err(_) => Err(PathfinderError::AllGoalsUnreachable),
+        }
+    }
+
+    // (position, radius, height) of every obstacle t_s seconds from now
+    // moving obstacles follow their velocity, climbing with it, and the others stay in place
+    pub fn environment_at_time(&self, t_s: f32) -> Vec<(Location, f32, f32)> {
+        self.obstacles
+            .iter()
+            .enumerate()
+            .map(|(i, obstacle)| {
+                let climb = self.obstacle_velocities.get(&i).map_or(0f32, |v| v.2 * t_s);
+                (
+                    self.predict_obstacle_position(i, t_s),
+                    obstacle.radius,
+                    obstacle.height + climb,
+                )
+            })
+            .collect()
+    }
+
+    // Find a path from start to goal departing departure_time seconds from now
+    // a separate planner is built over the obstacles where environment_at_time puts them, so the
+    // graph of this planner is left untouched. Moving obstacles keep moving during the flight
+    pub fn find_path_at_time(
+        &mut self,
+        start: Location,
+        goal: Location,
+        departure_time: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let obstacles = self
+            .environment_at_time(departure_time)
+            .into_iter()
+            .map(|(location, radius, height)| Obstacle::new(location, radius, height))
+            .collect();
+        let mut planner = self.detached_planner(self.config.clone(), obstacles);
+        let waypoints = planner.find_shortest_path::<()>(start, goal)?;
+        Ok(Self::assemble_path(start, &waypoints, goal))
+    }
+
+    // Planner with the same restrictions as this one over its own graph, built from config and
+    // obstacles, so searches on it leave this graph untouched
+    fn detached_planner(&self, config: TConfig, obstacles: Vec<Obstacle>) -> Tanstar {
+        let mut planner = Tanstar::new();
+        planner.keep_out_zones = self.keep_out_zones.clone();
+        planner.forbidden_edges = self.forbidden_edges.clone();
+        planner.forbidden_nodes = self.forbidden_nodes.clone();
+        planner.passable_obstacles = self.passable_obstacles.clone();
+        planner.obstacle_velocities = self.obstacle_velocities.clone();
+        planner.airspeed = self.airspeed;
+        planner.obstacle_uncertainty = self.obstacle_uncertainty.clone();
+        planner.risk_sigma = self.risk_sigma;
+        planner.altitude_limits = self.altitude_limits;
+        planner.init(config, self.flyzones.clone(), obstacles);
+        planner
+    }
+
+    // Find a path from start to goal that stays as high as possible, for imaging missions
+    // obstacles are treated as terrain cells and the path hops between their centers. Every
+    // segment is flown the buffer size above the tallest obstacle it crosses or touches, and no
+    // lower than the start and goal it joins. The path maximizing the lowest of these altitudes
+    // is returned, preferring the shortest among ties
+    pub fn find_path_maximizing_altitude(
+        &self,
+        start: Location,
+        goal: Location,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        // start is 0, goal is 1, obstacle i is i + 2
+        let mut points = vec![
+            Point::from((&start, &self.origin)),
+            Point::from((&goal, &self.origin)),
+        ];
+        // obstacles are only cleared when flown strictly above them
+        let clearance = self.config.buffer_size.max(f32::EPSILON);
+        let mut heights = vec![start.alt(), goal.alt()];
+        for obstacle in &self.obstacles {
+            points.push(Point::from((&obstacle.location, &self.origin)));
+            heights.push(obstacle.height + clearance);
+        }
+        let size = points.len();
+        let floor = |a: usize, b: usize| match self.valid_path(&points[a], &points[b]) {
+            PathValidity::Flyover(threshold) if threshold > 0f32 => {
+                Some((threshold + clearance).max(heights[a]).max(heights[b]))
+            }
+            PathValidity::Flyover(_) | PathValidity::Valid => Some(heights[a].max(heights[b])),
+            PathValidity::Invalid => None,
+        };
+
+        // modified Dijkstra, labels are the lowest altitude so far and the distance so far
+        let mut labels = vec![None; size];
+        let mut parents = vec![None; size];
+        let mut done = vec![false; size];
+        labels[0] = Some((f32::INFINITY, 0f32));
+        loop {
+            let mut best: Option<(usize, (f32, f32))> = None;
+            for (i, label) in labels.iter().enumerate() {
+                if let Some(label) = *label {
+                    let better = match best {
+                        Some((_, other)) => Self::is_higher(label, other),
+                        None => true,
+                    };
+                    if !done[i] && better {
+                        best = Some((i, label));
+                    }
+                }
+            }
+            let (cur, (lowest, distance)) = match best {
+                Some(best) => best,
+                None => return Err(PathfinderError::NoPathFound),
+            };
+            if cur == 1 {
+                break;
+            }
+            done[cur] = true;
+            for next in 0..size {
+                if done[next] || next == cur {
+                    continue;
+                }
+                if let Some(altitude) = floor(cur, next) {
+                    let label = (
+                        lowest.min(altitude),
+                        distance + points[cur].distance(&points[next]),
+                    );
+                    let better = match labels[next] {
+                        Some(other) => Self::is_higher(label, other),
+                        None => true,
+                    };
+                    if better {
+                        labels[next] = Some(label);
+                        parents[next] = Some(cur);
+                    }
+                }
+            }
+        }
+
+        let mut chain = vec![1];
+        while let Some(parent) = parents[chain[chain.len() - 1]] {
+            chain.push(parent);
+        }
+        chain.reverse();
+        let mut path = vec![start];
+        for i in 1..chain.len() - 1 {
+            let (prev, cur, next) = (chain[i - 1], chain[i], chain[i + 1]);
+            let altitude = floor(prev, cur).unwrap().max(floor(cur, next).unwrap());
+            let mut location = Location::from((&points[cur], &self.origin));
+            location.alt = altitude.into();
+            path.push(location);
+        }
+        path.push(goal);
+        Ok(path)
+    }
+
+    // Whether the (lowest altitude, distance) label a is preferred over b
+    fn is_higher(a: (f32, f32), b: (f32, f32)) -> bool {
+        a.0 > b.0 || (a.0 == b.0 && a.1 < b.1)
+    }
+
+    // Find a path from start to goal that arrives over the goal at target_alt
+    // the last leg of the path climbs or descends straight to target_alt, unless it would be
+    // steeper than the maximum flight angle, in which case the altitude change is flown back and
+    // forth along the last leg at half of the maximum flight angle once over the goal
+    pub fn find_path_with_altitude_target(
+        &mut self,
+        start: Location,
+        goal: Location,
+        target_alt: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let target = Location::from_radians(goal.lat(), goal.lon(), target_alt);
+        self.validate_location(&target)?;
+        let waypoints = self.find_shortest_path::<()>(start, goal)?;
+        let mut path = Self::assemble_path(start, &waypoints, goal);
+        let climb = target_alt - goal.alt();
+        if climb == 0f32 {
+            return Ok(path);
+        }
+
+        let end = Point::from((&goal, &self.origin));
+        let prev = Point::from((&path[path.len() - 2], &self.origin));
+        let leg = end.distance(&prev);
+        if leg == 0f32 {
+            // the altitude change would be flown straight up or down
+            return Err(PathfinderError::AltitudeOutOfRange {
+                altitude: target_alt,
+            });
+        }
+
+        let (mut from, descent) = if (target_alt - prev.z).abs().atan2(leg) <= MAX_FLIGHT_ANGLE {
+            path.pop();
+            (prev, vec![target])
+        } else {
+            let slope = (MAX_FLIGHT_ANGLE / 2f32).tan() * climb.signum();
+            // every excursion is flown out and back
+            let mut remaining = climb.abs() / slope.abs() / 2f32;
+            let mut alt = goal.alt();
+            let mut descent = Vec::new();
+            while remaining > 0f32 {
+                let distance = remaining.min(leg);
+                let mut turn = end.lerp(&prev, distance / leg);
+                turn.z = alt + slope * distance;
+                alt += 2f32 * slope * distance;
+                descent.push(Location::from((&turn, &self.origin)));
+                descent.push(Location::from_radians(goal.lat(), goal.lon(), alt));
+                remaining -= distance;
+            }
+            // land exactly on the target
+            descent.pop();
+            descent.push(target);
+            (end, descent)
+        };
+
+        for location in &descent {
+            let to = Point::from((location, &self.origin));
+            match self.valid_path(&from, &to) {
+                PathValidity::Invalid => return Err(PathfinderError::ExitsFlyzone),
+                PathValidity::Flyover(height) if from.z.min(to.z) <= height => {
+                    return Err(PathfinderError::IntersectsObstacle)
+                }
+                _ => {}
+            }
+            from = to;
+        }
+        path.extend(descent);
+        Ok(path)
+    }
+
+    // Raise waypoint altitudes so that no leg climbs or descends faster than max_climb_rate_m_per_m
+    // meters per horizontal meter. Altitudes are only ever raised, so flyover thresholds stay met
+    pub fn smooth_altitude_profile(&self, waypoints: &mut [Location], max_climb_rate_m_per_m: f32) {
+        let distance = |a: &Location, b: &Location| {
+            let a = Point::from((a, &self.origin));
+            let b = Point::from((b, &self.origin));
+            ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt()
+        };
+        let raise = |waypoint: &mut Location, alt: f32| {
+            if alt > waypoint.alt() {
+                *waypoint = Location::from_radians(waypoint.lat(), waypoint.lon(), alt);
+            }
+        };
+        // backward pass limits the climb into each waypoint
+        for i in (0..waypoints.len().saturating_sub(1)).rev() {
+            let max_rise = max_climb_rate_m_per_m * distance(&waypoints[i], &waypoints[i + 1]);
+            let alt = waypoints[i + 1].alt() - max_rise;
+            raise(&mut waypoints[i], alt);
+        }
+        // forward pass limits the descent out of each waypoint
+        for i in 1..waypoints.len() {
+            let max_drop = max_climb_rate_m_per_m * distance(&waypoints[i - 1], &waypoints[i]);
+            let alt = waypoints[i - 1].alt() - max_drop;
+            raise(&mut waypoints[i], alt);
+        }
+    }
+
+    // Find obstacles that every path from start to end has to go around
+    // An obstacle is critical if no path can be found once its node is removed from the search
+    // returns an empty list if there is no path to begin with
+    pub fn find_critical_obstacles(&mut self, start: Location, end: Location) -> Vec<usize> {
+        let last_path = self.last_path.clone();
+        let mut critical = Vec::new();
+        if self.adjust_path::<()>(start, end).is_some() {
+            for i in 0..self.obstacles.len().min(self.nodes.len()) {
+                self.forbidden_nodes.insert(i);
+                if self.adjust_path::<()>(start, end).is_none() {
+                    critical.push(i);
+                }
+                self.forbidden_nodes.remove(&i);
+            }
+        }
+        self.last_path = last_path;
+        critical
+    }
+
+    // Generate a loiter pattern circling center at radius, repeated for the number of laps
+    // The circle is approximated by a polygon and ends back at the first point of the circle.
+    // The circle must lie inside a flyzone and clear every obstacle it crosses at altitude
+    pub fn path_loiter(
+        &self,
+        center: Location,
+        radius: f32,
+        laps: u32,
+        altitude: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let origin = Point::from((&center, &self.origin));
+        let circle: Vec<Point> = (0..LOITER_POINTS)
+            .map(|i| {
+                let angle = 2f32 * PI * i as f32 / LOITER_POINTS as f32;
+                let offset = rotate_point(&Point::new(radius, 0f32, 0f32), angle);
+                Point::new(origin.x + offset.x, origin.y + offset.y, altitude)
+            })
+            .collect();
+
+        // segments only fail to cross a flyzone edge, so check the circle starts inside one
+        if !self.point_within_flyzone(&circle[0]) {
+            return Err(PathfinderError::ExitsFlyzone);
+        }
+        // every lap flies the same segments, so only validate one lap
+        for i in 0..LOITER_POINTS {
+            match self.valid_path(&circle[i], &circle[(i + 1) % LOITER_POINTS]) {
+                PathValidity::Invalid => return Err(PathfinderError::ExitsFlyzone),
+                PathValidity::Flyover(threshold) if threshold >= altitude => {
+                    return Err(PathfinderError::IntersectsObstacle)
+                }
+                _ => {}
+            }
+        }
+
+        let mut path = Vec::new();
+        for _ in 0..laps {
+            for point in &circle {
+                path.push(Location::from((point, &self.origin)));
+            }
+        }
+        if laps > 0 {
+            path.push(Location::from((&circle[0], &self.origin)));
+        }
+        Ok(path)
+    }
+
+    // Find a path to goal that ends by loitering around it for loiter_time_s at the airspeed
+    // the approach leaves the last waypoint before goal along the tangent to the loiter circle
+    // that joins it counter-clockwise, like path_loiter flies, and the loiter starts at the
+    // point of the circle nearest to the tangent point. The path ends on the loiter circle
+    pub fn find_path_with_loiter_at_goal(
+        &mut self,
+        start: Location,
+        goal: Location,
+        loiter_radius: f32,
+        loiter_time_s: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        assert!(loiter_radius > 0f32 && loiter_time_s >= 0f32);
+        let waypoints = self.find_shortest_path::<()>(start, goal)?;
+        let mut path = vec![start];
+        path.extend(waypoints.iter().map(|wp| wp.location));
+
+        let laps = (loiter_time_s * self.airspeed / (2f32 * PI * loiter_radius)).ceil() as u32;
+        let loiter = self.path_loiter(goal, loiter_radius, 1, goal.alt())?;
+        let last = Point::from((&path[path.len() - 1], &self.origin));
+        let center = Node::from((&goal, &self.origin, loiter_radius));
+        let (tangents, _) = self.find_path(
+            &Node::from((&path[path.len() - 1], &self.origin, 0f32)),
+            &center,
+        );
+        // counter-clockwise flight at the tangent point heads along the tangent
+        let entry = tangents
+            .iter()
+            .map(|&(_, angle, _, _)| (Point::from((&center, angle)), angle))
+            .find(|&(point, angle)| {
+                (point.x - last.x) * -angle.sin() + (point.y - last.y) * angle.cos() > 0f32
+            })
+            .map_or(last, |(point, _)| point);
+
+        let circle: Vec<Point> = loiter[..LOITER_POINTS]
+            .iter()
+            .map(|location| Point::from((location, &self.origin)))
+            .collect();
+        let mut first = 0;
+        for (i, point) in circle.iter().enumerate() {
+            if point.distance(&entry) < circle[first].distance(&entry) {
+                first = i;
+            }
+        }
+        self.validate_curve(&[last, circle[first]])?;
+        for i in 0..=laps as usize * LOITER_POINTS {
+            let point = &circle[(first + i) % LOITER_POINTS];
+            path.push(Location::from((point, &self.origin)));
+        }
+        Ok(path)
+    }
+
+    // Generate an expanding square search pattern centered at the home location
+    // Spacing between rows is the sensor footprint at altitude, reduced by overlap percent
+    // Legs blocked by obstacles are skipped, and the pattern ends once it reaches the flyzone
+    pub fn find_search_path(&self, altitude: f32, sensor_fov: f32, overlap: f32) -> Vec<Location> {
+        let home = self.home_location();
+        let mut current = Point::from((&home, &self.origin));
+        current.z = altitude;
+        let mut path = vec![Location::from((&current, &self.origin))];
+
+        let footprint = 2f32 * altitude * (sensor_fov.to_radians() / 2f32).tan();
+        let spacing = footprint * (1f32 - overlap / 100f32);
+        if spacing <= 0f32 || !spacing.is_finite() {
+            return path;
+        }
+
+        // the pattern can not grow past twice the extent of the bounding box from any home
+        let (min, max) = self.bounding_box();
+        let (min, max) = (
+            Point::from((&min, &self.origin)),
+            Point::from((&max, &self.origin)),
+        );
+        let extent = (max.x - min.x).max(max.y - min.y);
+        let max_legs = 2 * ((2f32 * extent / spacing).ceil() as usize + 1);
+
+        // legs go north, east, south, west and grow by one spacing every two legs
+        let directions = [(0f32, 1f32), (1f32, 0f32), (0f32, -1f32), (-1f32, 0f32)];
+        let mut turn = current;
+        for leg in 0..max_legs {
+            let length = spacing * (leg / 2 + 1) as f32;
+            let (dx, dy) = directions[leg % 4];
+            turn = Point::new(turn.x + dx * length, turn.y + dy * length, altitude);
+            if !self.point_within_flyzone(&turn) {
+                break;
+            }
+            // legs blocked by obstacles or keep out zones skip to the next turn
+            match self.valid_path(&current, &turn) {
+                PathValidity::Invalid => continue,
+                PathValidity::Flyover(threshold) if threshold >= altitude => continue,
+                _ => {}
+            }
+            path.push(Location::from((&turn, &self.origin)));
+            current = turn;
+        }
+        path
+    }
+
+    // Cover the flyzone with a regular grid of waypoints resolution_m apart at altitude_m
+    // Grid points outside the flyzones or inside obstacles are dropped, and the rest are visited
+    // greedily by always flying to the closest point that can be reached with a valid segment
+    pub fn grid_path(
+        &self,
+        altitude_m: f32,
+        resolution_m: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        assert!(resolution_m > 0f32);
+        let (min, max) = self.bounding_box();
+        let (min, max) = (
+            Point::from((&min, &self.origin)),
+            Point::from((&max, &self.origin)),
+        );
+        let mut grid = Vec::new();
+        let mut y = min.y + resolution_m / 2f32;
+        while y < max.y {
+            let mut x = min.x + resolution_m / 2f32;
+            while x < max.x {
+                let point = Point::new(x, y, altitude_m);
+                match self.validate_location(&Location::from((&point, &self.origin))) {
+                    Ok(()) => grid.push(point),
+                    Err(PathfinderError::OutsideFlyzone)
+                    | Err(PathfinderError::InsideObstacle { .. }) => {}
+                    Err(e) => return Err(e),
+                }
+                x += resolution_m;
+            }
+            y += resolution_m;
+        }
+        if grid.is_empty() {
+            return Err(PathfinderError::NoPathFound);
+        }
+
+        let mut current = grid.remove(0);
+        let mut path = vec![Location::from((&current, &self.origin))];
+        while !grid.is_empty() {
+            let mut nearest: Option<(f32, usize)> = None;
+            for (i, point) in grid.iter().enumerate() {
+                let distance = current.distance(point);
+                match nearest {
+                    Some((best, _)) if best <= distance => {}
+                    _ if self.validate_curve(&[current, *point]).is_ok() => {
+                        nearest = Some((distance, i))
+                    }
+                    _ => {}
+                }
+            }
+            let (_, i) = nearest.ok_or(PathfinderError::NoPathFound)?;
+            current = grid.remove(i);
+            path.push(Location::from((&current, &self.origin)));
+        }
+        Ok(path)
+    }
+
+    // Find the closest location that is inside the flyzone and clear of obstacles
+    // A location outside every flyzone is moved onto the nearest boundary and then inward by
+    // the buffer, and a location inside an obstacle is pushed out past its radius by the buffer
+    pub fn find_closest_safe_location(&self, loc: &Location) -> Location {
+        let mut point = Point::from((loc, &self.origin));
+        let buffer = self.config.buffer_size;
+        let zones: Vec<Vec<Point>> = self
+            .flyzones
+            .iter()
+            .map(|zone| {
+                zone.iter()
+                    .map(|location| Point::from((location, &self.origin)))
+                    .collect()
+            })
+            .collect();
+
+        if !zones.iter().any(|zone| point_in_polygon(&point, zone)) {
+            let mut closest: Option<(f32, Point, usize)> = None;
+            for (z, zone) in zones.iter().enumerate() {
+                for i in 0..zone.len() {
+                    let a = &zone[i];
+                    let b = &zone[(i + 1) % zone.len()];
+                    let candidate = closest_point_on_segment(&point, a, b);
+                    let distance = candidate.distance(&point);
+                    match closest {
+                        Some((best, _, _)) if best <= distance => {}
+                        _ => closest = Some((distance, candidate, z)),
+                    }
+                }
+            }
+            if let Some((distance, boundary, z)) = closest {
+                // point lies away from the boundary opposite the inside of the flyzone
+                let (dx, dy) = if distance > 0f32 {
+                    (
+                        (boundary.x - point.x) / distance,
+                        (boundary.y - point.y) / distance,
+                    )
+                } else {
+                    (0f32, 0f32)
+                };
+                let inward =
+                    Point::new(boundary.x + dx * buffer, boundary.y + dy * buffer, point.z);
+                point = if point_in_polygon(&inward, &zones[z]) {
+                    inward
+                } else {
+                    boundary
+                };
+            }
+        }
+
+        for obstacle in &self.obstacles {
+            let center = Point::from((&obstacle.location, &self.origin));
+            let distance = center.distance(&point);
+            if distance < obstacle.radius && point.z < obstacle.height {
+                let (dx, dy) = if distance > 0f32 {
+                    (
+                        (point.x - center.x) / distance,
+                        (point.y - center.y) / distance,
+                    )
+                } else {
+                    (1f32, 0f32)
+                };
+                let radius = obstacle.radius + buffer;
+                point = Point::new(center.x + dx * radius, center.y + dy * radius, point.z);
+            }
+        }
+        Location::from((&point, &self.origin))
+    }
+
+    // Generate a patrol along the boundary of a flyzone at altitude
+    // The boundary is inset by the buffer so the patrol stays inside the flyzone, and the patrol
+    // starts and ends at the point on the boundary closest to the home location.
+    // Segments blocked by obstacles taller than the altitude are routed around them
+    pub fn generate_waypoints_for_polygon_boundary(
+        &mut self,
+        zone_index: usize,
+        altitude: f32,
+    ) -> Result<Vec<Location>, PathfinderError> {
+        let zone = match self.flyzones.get(zone_index) {
+            Some(zone) => zone
+                .iter()
+                .map(|location| Point::from((location, &self.origin)))
+                .collect::<Vec<Point>>(),
+            None => return Err(PathfinderError::InvalidFlyzoneIndex { index: zone_index }),
+        };
+        let boundary: Vec<Point> = inset_polygon(&zone, self.config.buffer_size)
+            .iter()
+            .map(|p| Point::new(p.x, p.y, altitude))
+            .collect();
+        let size = boundary.len();
+
+        // start on the closest edge to home and follow the flyzone order from there
+        let home = Point::from((&self.home_location(), &self.origin));
+        let mut start = (0, boundary[0]);
+        let mut best = f32::MAX;
+        for i in 0..size {
+            let closest = closest_point_on_segment(&home, &boundary[i], &boundary[(i + 1) % size]);
+            if closest.distance(&home) < best {
+                best = closest.distance(&home);
+                start = (i, Point::new(closest.x, closest.y, altitude));
+            }
+        }
+        let (edge, start) = start;
+        let mut points = vec![start];
+        for i in 1..=size {
+            points.push(boundary[(edge + i) % size]);
+        }
+        points.push(start);
+
+        let mut path = vec![Location::from((&start, &self.origin))];
+        for leg in points.windows(2) {
+            let (a, b) = (
+                Location::from((&leg[0], &self.origin)),
+                Location::from((&leg[1], &self.origin)),
+            );
+            match self.valid_path(&leg[0], &leg[1]) {
+                PathValidity::Invalid => return Err(PathfinderError::ExitsFlyzone),
+                PathValidity::Flyover(threshold) if threshold >= altitude => {
+                    match self.adjust_path::<()>(a, b) {
+                        Some(waypoints) => path.extend(waypoints.iter().map(|wp| wp.location)),
+                        None => return Err(PathfinderError::NoPathFound),
+                    }
+                }
+                _ => {}
+            }
+            path.push(b);
+        }
+        Ok(path)
+    }
+
+    // Total 3d length of a path in meters
+    pub fn path_length(&self, path: &[Location]) -> f32 {
+        path.windows(2)
+            .map(|leg| {
+                let a = Point::from((&leg[0], &self.origin));
+                let b = Point::from((&leg[1], &self.origin));
+                a.distance3d(&b)
+            })
+            .sum()
+    }
+
+    // Seconds needed to fly a path at the airspeed, with the wind along each leg
+    pub fn estimate_flight_time(&self, waypoints: &[Location]) -> f32 {
+        waypoints
+            .windows(2)
+            .map(|leg| self.leg_flight_time(&leg[0], &leg[1]))
+            .sum()
+    }
+
+    // Seconds needed to fly from a to b at the ground speed of the leg
+    // infinite when the headwind is at least as fast as the airspeed
+    pub fn leg_flight_time(&self, a: &Location, b: &Location) -> f32 {
+        let distance = Point::from((a, &self.origin)).distance3d(&Point::from((b, &self.origin)));
+        if distance == 0f32 {
+            return 0f32;
+        }
+        distance / self.ground_speed(a, b)
+    }
+
+    // Speed in m/s over the ground from a to b, the airspeed plus the wind along the leg
+    // never below 0, so a headwind at least as fast as the airspeed makes no progress
+    pub fn ground_speed(&self, a: &Location, b: &Location) -> f32 {
+        (self.airspeed + self.segment_wind_component(a, b)).max(0f32)
+    }
+
+    // Component in m/s of the wind along the direction from a to b
+    // positive for a tailwind and negative for a headwind, 0 for a pure crosswind
+    pub fn segment_wind_component(&self, a: &Location, b: &Location) -> f32 {
+        let heading = heading_degrees(
+            &Point::from((a, &self.origin)),
+            &Point::from((b, &self.origin)),
+        )
+        .to_radians();
+        // compass headings are clockwise from north
+        self.wind.0 * heading.sin() + self.wind.1 * heading.cos()
+    }
+
+    // Unix timestamp of the arrival at the end of a path, rounded to the nearest second
+    pub fn estimate_arrival_time(&self, waypoints: &[Location], departure_unix_s: u64) -> u64 {
+        let flight_time = self.estimate_flight_time(waypoints);
+        departure_unix_s.saturating_add(flight_time.round() as u64)
+    }
+
+    // Resample a path to exactly n waypoints spaced equally along its 3d length
+    // The first and last waypoints are kept. A resampled segment that cuts a corner into an
+    // obstacle or out of the flyzone has its nearest interior waypoint moved onto that corner
+    pub fn find_equidistant_waypoints(&self, waypoints: &[Location], n: usize) -> Vec<Location> {
+        assert!(n >= 2);
+        if waypoints.len() < 2 {
+            return waypoints.to_vec();
+        }
+        let points: Vec<Point> = waypoints
+            .iter()
+            .map(|waypoint| Point::from((waypoint, &self.origin)))
+            .collect();
+        // distance along the path of each original waypoint
+        let mut flown = vec![0f32];
+        for leg in points.windows(2) {
+            let last = flown[flown.len() - 1];
+            flown.push(last + leg[0].distance3d(&leg[1]));
+        }
+        let total = flown[flown.len() - 1];
+
+        let mut distances = Vec::with_capacity(n);
+        let mut resampled = Vec::with_capacity(n);
+        let mut leg = 0;
+        for i in 0..n {
+            let distance = total * i as f32 / (n - 1) as f32;
+            while leg < points.len() - 2 && flown[leg + 1] < distance {
+                leg += 1;
+            }
+            let length = flown[leg + 1] - flown[leg];
+            let t = if length > 0f32 {
+                (distance - flown[leg]) / length
+            } else {
+                0f32
+            };
+            distances.push(distance);
+            resampled.push(points[leg].lerp(&points[leg + 1], t));
+        }
+        resampled[0] = points[0];
+        resampled[n - 1] = points[points.len() - 1];
+
+        for i in 0..n - 1 {
+            if self.validate_curve(&resampled[i..i + 2]).is_ok() {
+                continue;
+            }
+            // closest pair of a skipped original waypoint and an interior end of the segment, as
+            // (distance between them along the path, original index, resampled index)
+            let mut nearest: Option<(f32, usize, usize)> = None;
+            for (j, &along) in flown.iter().enumerate().take(points.len() - 1).skip(1) {
+                if along <= distances[i] || along >= distances[i + 1] {
+                    continue;
+                }
+                for &k in &[i, i + 1] {
+                    if k == 0 || k == n - 1 {
+                        continue;
+                    }
+                    let offset = (along - distances[k]).abs();
+                    match nearest {
+                        Some((best, _, _)) if best <= offset => {}
+                        _ => nearest = Some((offset, j, k)),
+                    }
+                }
+            }
+            if let Some((_, j, k)) = nearest {
+                resampled[k] = points[j];
+                distances[k] = flown[j];
+            }
+        }
+
+        let mut path: Vec<Location> = resampled
+            .iter()
+            .map(|point| Location::from((point, &self.origin)))
+            .collect();
+        path[0] = waypoints[0];
+        path[n - 1] = waypoints[waypoints.len() - 1];
+        path
+    }
+
+    // Extend a path by flying back and forth along its first leg until extra meters are covered
+    // The first leg is already validated so the holding pattern does not need to be rechecked
+    fn insert_holding_pattern(&self, path: &mut Vec<Location>, extra: f32) {
+        if extra <= 0f32 || path.len() < 2 {
+            return;
+        }
+        let start = Point::from((&path[0], &self.origin));
+        let next = Point::from((&path[1], &self.origin));
+        let leg = start.distance(&next);
+        if leg == 0f32 {
+            return;
+        }
+
+        // every excursion is flown out and back
+        let mut remaining = extra / 2f32;
+        let mut holding = Vec::new();
+        while remaining > 0f32 {
+            let t = remaining.min(leg) / leg;
+            let turn = Point::new(
+                start.x + t * (next.x - start.x),
+                start.y + t * (next.y - start.y),
+                start.z,
+            );
+            holding.push(Location::from((&turn, &self.origin)));
+            holding.push(path[0]);
+            remaining -= leg;
+        }
+        path.splice(1..1, holding);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::graph::util::test_helpers::*;
+    use super::*;
+
+    #[test]
+    fn rendezvous_arrival_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 30f32, 50f32)];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let airspeed = 20f32;
+        let agents = vec![
+            Location::from_meters(50f32, 200f32, 20f32, &origin()),
+            Location::from_meters(300f32, 100f32, 20f32, &origin()),
+        ];
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let paths = pathfinder.find_rendezvous_path(&agents, goal).unwrap();
+
+        assert_eq!(paths.len(), 2);
+        let eta: Vec<f32> = paths
+            .iter()
+            .map(|path| pathfinder.path_length(path) / airspeed)
+            .collect();
+        assert!((eta[0] - eta[1]).abs() < 0.01);
+        for (path, agent) in paths.iter().zip(agents) {
+            assert_eq!(path[0], agent);
+            assert_eq!(path[path.len() - 1], goal);
+        }
+    }
+
+    #[test]
+    fn loiter_test() {
+        let obstacles = vec![obstacle_from_meters(300f32, 300f32, 20f32, 50f32)];
+        let pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let center = Location::from_meters(100f32, 100f32, 0f32, &origin());
+        let path = pathfinder.path_loiter(center, 50f32, 3, 80f32).unwrap();
+        assert_eq!(path.len(), 3 * LOITER_POINTS + 1);
+        assert_eq!(path[0], path[path.len() - 1]);
+        let center = Point::from((&center, &pathfinder.origin));
+        for location in &path {
+            let point = Point::from((location, &pathfinder.origin));
+            assert!((point.distance(&center) - 50f32).abs() < 0.01);
+            assert!((point.z - 80f32).abs() < 0.01);
+        }
+
+        // circle crossing the obstacle, which is only cleared above its height
+        let center = Location::from_meters(300f32, 250f32, 0f32, &origin());
+        assert_eq!(
+            pathfinder.path_loiter(center, 50f32, 1, 40f32),
+            Err(PathfinderError::IntersectsObstacle)
+        );
+        assert!(pathfinder.path_loiter(center, 50f32, 1, 80f32).is_ok());
+
+        // circle leaving the flyzone
+        let center = Location::from_meters(20f32, 200f32, 0f32, &origin());
+        assert_eq!(
+            pathfinder.path_loiter(center, 50f32, 1, 80f32),
+            Err(PathfinderError::ExitsFlyzone)
+        );
+        // circle entirely outside of the flyzone
+        let center = Location::from_meters(600f32, 200f32, 0f32, &origin());
+        assert_eq!(
+            pathfinder.path_loiter(center, 50f32, 1, 80f32),
+            Err(PathfinderError::ExitsFlyzone)
+        );
+    }
+
+    #[test]
+    fn search_path_test() {
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), vec![]);
+        pathfinder.set_home_location(Location::from_meters(200f32, 200f32, 0f32, &origin()));
+        // 100m footprint with 20% overlap gives 80m spacing
+        let path = pathfinder.find_search_path(50f32, 90f32, 20f32);
+        let expected = vec![
+            (200f32, 200f32),
+            (200f32, 280f32),
+            (280f32, 280f32),
+            (280f32, 120f32),
+            (120f32, 120f32),
+            (120f32, 360f32),
+            (360f32, 360f32),
+            (360f32, 40f32),
+            (40f32, 40f32),
+        ];
+        assert_eq!(path.len(), expected.len());
+        for (location, &(x, y)) in path.iter().zip(expected.iter()) {
+            let point = Point::from((location, &pathfinder.origin));
+            assert!((point.x - x).abs() < 0.01);
+            assert!((point.y - y).abs() < 0.01);
+            assert!((point.z - 50f32).abs() < 0.01);
+        }
+
+        // obstacle taller than the search altitude on the fourth leg
+        let obstacles = vec![obstacle_from_meters(200f32, 120f32, 10f32, 100f32)];
+        pathfinder.set_obstacles(obstacles);
+        let path = pathfinder.find_search_path(50f32, 90f32, 20f32);
+        assert!(path.len() < expected.len());
+        for leg in path.windows(2) {
+            let a = Point::from((&leg[0], &pathfinder.origin));
+            let b = Point::from((&leg[1], &pathfinder.origin));
+            match pathfinder.valid_path(&a, &b) {
+                PathValidity::Flyover(threshold) => assert!(threshold < 50f32),
+                _ => panic!(),
+            }
+        }
+
+        // a keep out zone on the first leg is skipped instead of ending the pattern
+        pathfinder.set_obstacles(Vec::new());
+        pathfinder.add_no_fly_corridor(
+            Location::from_meters(190f32, 240f32, 0f32, &origin()),
+            Location::from_meters(210f32, 240f32, 0f32, &origin()),
+            10f32,
+        );
+        let path = pathfinder.find_search_path(50f32, 90f32, 20f32);
+        assert_eq!(path.len(), expected.len() - 1);
+        let last = Point::from((&path[path.len() - 1], &pathfinder.origin));
+        assert!((last.x - 40f32).abs() < 0.01 && (last.y - 40f32).abs() < 0.01);
+
+        // nearly full overlap still ends once the pattern leaves the flyzone
+        let path = pathfinder.find_search_path(50f32, 90f32, 99f32);
+        assert!(path.len() > expected.len());
+        assert!(path.iter().all(|location| {
+            pathfinder.point_within_flyzone(&Point::from((location, &pathfinder.origin)))
+        }));
+    }
+
+    #[test]
+    fn closest_safe_location_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 30f32, 50f32)];
+        let pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let to_point = |location: &Location| Point::from((location, &pathfinder.origin));
+
+        // already safe
+        let point = to_point(
+            &pathfinder.find_closest_safe_location(&Location::from_meters(
+                100f32,
+                100f32,
+                20f32,
+                &origin(),
+            )),
+        );
+        assert!((point.x - 100f32).abs() < 0.01);
+        assert!((point.y - 100f32).abs() < 0.01);
+
+        // west of the flyzone
+        let point = to_point(
+            &pathfinder.find_closest_safe_location(&Location::from_meters(
+                -50f32,
+                100f32,
+                20f32,
+                &origin(),
+            )),
+        );
+        assert!((point.x - 5f32).abs() < 0.01);
+        assert!((point.y - 100f32).abs() < 0.01);
+        assert!((point.z - 20f32).abs() < 0.01);
+
+        // north east of the flyzone
+        let point = to_point(
+            &pathfinder.find_closest_safe_location(&Location::from_meters(
+                450f32,
+                500f32,
+                20f32,
+                &origin(),
+            )),
+        );
+        assert!(point.x < 400f32 && point.y < 400f32);
+        assert!(point.distance(&Point::new(400f32, 400f32, 0f32)) < 5.01);
+
+        // inside the obstacle
+        let point = to_point(
+            &pathfinder.find_closest_safe_location(&Location::from_meters(
+                210f32,
+                200f32,
+                20f32,
+                &origin(),
+            )),
+        );
+        assert!((point.x - 235f32).abs() < 0.01);
+        assert!((point.y - 200f32).abs() < 0.01);
+
+        // above the obstacle
+        let point = to_point(
+            &pathfinder.find_closest_safe_location(&Location::from_meters(
+                210f32,
+                200f32,
+                60f32,
+                &origin(),
+            )),
+        );
+        assert!((point.x - 210f32).abs() < 0.01);
+    }
+
+    #[test]
+    fn boundary_patrol_test() {
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), vec![]);
+        pathfinder.set_home_location(Location::from_meters(200f32, 50f32, 0f32, &origin()));
+        let path = pathfinder
+            .generate_waypoints_for_polygon_boundary(0, 50f32)
+            .unwrap();
+        let expected = [
+            (200f32, 5f32),
+            (5f32, 5f32),
+            (5f32, 395f32),
+            (395f32, 395f32),
+            (395f32, 5f32),
+            (200f32, 5f32),
+        ];
+        assert_eq!(path.len(), expected.len());
+        for (location, &(x, y)) in path.iter().zip(expected.iter()) {
+            let point = Point::from((location, &pathfinder.origin));
+            assert!((point.x - x).abs() < 0.01);
+            assert!((point.y - y).abs() < 0.01);
+            assert!((point.z - 50f32).abs() < 0.01);
+        }
+        assert_eq!(
+            pathfinder.generate_waypoints_for_polygon_boundary(1, 50f32),
+            Err(PathfinderError::InvalidFlyzoneIndex { index: 1 })
+        );
+
+        // obstacle taller than the patrol blocking the west edge
+        let obstacles = vec![obstacle_from_meters(20f32, 200f32, 20f32, 100f32)];
+        pathfinder.set_obstacles(obstacles);
+        let path = pathfinder
+            .generate_waypoints_for_polygon_boundary(0, 50f32)
+            .unwrap();
+        assert!(path.len() > expected.len());
+        assert_eq!(path[0], path[path.len() - 1]);
+        for leg in path.windows(2) {
+            let a = Point::from((&leg[0], &pathfinder.origin));
+            let b = Point::from((&leg[1], &pathfinder.origin));
+            // arcs around the obstacle may repeat a waypoint
+            if a.distance(&b) < 0.01 {
+                continue;
+            }
+            match pathfinder.valid_path(&a, &b) {
+                PathValidity::Flyover(threshold) => assert!(threshold < 50f32),
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn critical_obstacles_test() {
+        // narrow corridor with an obstacle in the middle leaving less than the buffer on either
+        // side, and one obstacle off to the side
+        let flyzones = vec![vec![
+            Location::from_meters(0f32, 0f32, 0f32, &origin()),
+            Location::from_meters(0f32, 60f32, 0f32, &origin()),
+            Location::from_meters(400f32, 60f32, 0f32, &origin()),
+            Location::from_meters(400f32, 0f32, 0f32, &origin()),
+        ]];
+        let obstacles = vec![
+            obstacle_from_meters(350f32, 15f32, 5f32, 100f32),
+            obstacle_from_meters(200f32, 30f32, 29.5f32, 100f32),
+        ];
+        let mut pathfinder = Tanstar::create(1f32, flyzones, obstacles);
+        let start = Location::from_meters(20f32, 30f32, 20f32, &origin());
+        let end = Location::from_meters(300f32, 30f32, 20f32, &origin());
+        assert_eq!(pathfinder.find_critical_obstacles(start, end), vec![1]);
+    }
+
+    #[test]
+    fn path_with_waypoints_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 30f32, 50f32)];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let location = |x, y| Location::from_meters(x, y, 20f32, &origin());
+        let start = location(50f32, 200f32);
+        let goal = location(350f32, 200f32);
+        let must_pass = vec![location(100f32, 350f32), location(300f32, 50f32)];
+        let path = pathfinder
+            .find_path_with_waypoints(start, goal, &must_pass)
+            .unwrap();
+
+        assert_eq!(path[0], start);
+        assert_eq!(path[path.len() - 1], goal);
+        let first = path.iter().position(|&l| l == must_pass[0]).unwrap();
+        let second = path.iter().position(|&l| l == must_pass[1]).unwrap();
+        assert!(0 < first && first < second && second < path.len() - 1);
+        for checkpoint in &must_pass {
+            assert_eq!(path.iter().filter(|&l| l == checkpoint).count(), 1);
+        }
+
+        // duplicated checkpoints do not repeat in the path
+        let path = pathfinder
+            .find_path_with_waypoints(start, goal, &[start, goal])
+            .unwrap();
+        assert_eq!(path.iter().filter(|&&l| l == goal).count(), 1);
+    }
+
+    #[test]
+    fn smooth_altitude_profile_test() {
+        let pathfinder = Tanstar::create(5f32, square_flyzone(400f32), vec![]);
+        let mut waypoints: Vec<Location> = [
+            (0f32, 20f32),
+            (100f32, 20f32),
+            (110f32, 80f32),
+            (120f32, 20f32),
+            (300f32, 20f32),
+        ]
+        .iter()
+        .map(|&(x, alt)| Location::from_meters(x, 100f32, alt, &origin()))
+        .collect();
+        let original = waypoints.clone();
+        pathfinder.smooth_altitude_profile(&mut waypoints, 0.5f32);
+
+        let expected = [25f32, 75f32, 80f32, 75f32, 20f32];
+        for (i, waypoint) in waypoints.iter().enumerate() {
+            assert!((waypoint.alt() - expected[i]).abs() < 0.01);
+            assert!(waypoint.alt() >= original[i].alt());
+            assert!((waypoint.lat() - original[i].lat()).abs() < 1e-9);
+            assert!((waypoint.lon() - original[i].lon()).abs() < 1e-9);
+        }
+        for leg in waypoints.windows(2) {
+            let a = Point::from((&leg[0], &pathfinder.origin));
+            let b = Point::from((&leg[1], &pathfinder.origin));
+            let distance = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+            assert!((b.z - a.z).abs() <= 0.5f32 * distance + 0.01);
+        }
+
+        // profiles that are already smooth are unchanged
+        let mut smooth = original.clone();
+        pathfinder.smooth_altitude_profile(&mut smooth, 10f32);
+        for (a, b) in smooth.iter().zip(original.iter()) {
+            assert!((a.alt() - b.alt()).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn path_under_altitude_test() {
+        // wide obstacle between two poles that can not be flown over
+        let obstacle = |x, radius, height| obstacle_from_meters(x, 200f32, radius, height);
+        let obstacles = vec![
+            obstacle(100f32, 5f32, 200f32),
+            obstacle(200f32, 60f32, 75f32),
+            obstacle(300f32, 5f32, 200f32),
+        ];
+        // buffer wider than the sag of the chord across the arc around the wide obstacle
+        let mut pathfinder = Tanstar::create(10f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 100f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 100f32, &origin());
+        let flies_over = |pathfinder: &Tanstar, path: &[Location]| {
+            path.windows(2)
+                .any(|leg| pathfinder.segment_flyover_altitude(&leg[0], &leg[1]) == 75f32)
+        };
+
+        // high enough to fly straight over the wide obstacle
+        let path = pathfinder
+            .find_path_under_altitude(start, goal, 120f32)
+            .unwrap();
+        assert!(flies_over(&pathfinder, &path));
+
+        // start and goal above the limit are rejected rather than lowered
+        assert_eq!(
+            pathfinder.find_path_under_altitude(start, goal, 50f32),
+            Err(PathfinderError::AltitudeOutOfRange { altitude: 100f32 })
+        );
+
+        // below the wide obstacle, so the path goes around it
+        let start = Location::from_meters(50f32, 200f32, 50f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 50f32, &origin());
+        let path = pathfinder
+            .find_path_under_altitude(start, goal, 50f32)
+            .unwrap();
+        assert!(!flies_over(&pathfinder, &path));
+        assert!(path.iter().all(|location| location.alt() <= 50f32));
+        assert_eq!(path[0], start);
+        assert_eq!(path[path.len() - 1], goal);
+
+        // obstacle filling the only corridor to the goal can only be flown over
+        let flyzone = [
+            (0f32, 0f32),
+            (200f32, 0f32),
+            (200f32, 80f32),
+            (460f32, 80f32),
+            (460f32, 120f32),
+            (200f32, 120f32),
+            (200f32, 200f32),
+            (0f32, 200f32),
+        ];
+        let flyzones = vec![flyzone
+            .iter()
+            .map(|&(x, y)| Location::from_meters(x, y, 0f32, &origin()))
+            .collect()];
+        let obstacles = vec![obstacle_from_meters(300f32, 100f32, 30f32, 50f32)];
+        let mut pathfinder = Tanstar::create(5f32, flyzones, obstacles);
+        let start = Location::from_meters(50f32, 100f32, 100f32, &origin());
+        let goal = Location::from_meters(400f32, 100f32, 100f32, &origin());
+        assert!(pathfinder
+            .find_path_under_altitude(start, goal, 100f32)
+            .is_ok());
+        let start = Location::from_meters(50f32, 100f32, 40f32, &origin());
+        let goal = Location::from_meters(400f32, 100f32, 40f32, &origin());
+        assert_eq!(
+            pathfinder.find_path_under_altitude(start, goal, 40f32),
+            Err(PathfinderError::NoPathUnderAltitudeLimit { limit: 40f32 })
+        );
+        // other errors are passed through
+        let goal = Location::from_meters(500f32, 100f32, 40f32, &origin());
+        assert_eq!(
+            pathfinder.find_path_under_altitude(start, goal, 40f32),
+            Err(PathfinderError::OutsideFlyzone)
+        );
+    }
+
+    #[test]
+    fn path_no_flyover_test() {
+        let obstacle = |x, radius, height| obstacle_from_meters(x, 200f32, radius, height);
+        let obstacles = vec![
+            obstacle(100f32, 5f32, 200f32),
+            obstacle(200f32, 60f32, 75f32),
+            obstacle(300f32, 5f32, 200f32),
+        ];
+        // buffer wider than the sag of the chord across the arc around the wide obstacle
+        let mut pathfinder = Tanstar::create(10f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 100f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 100f32, &origin());
+        let flies_over = |pathfinder: &Tanstar, path: &[Location]| {
+            path.windows(2)
+                .any(|leg| pathfinder.segment_flyover_altitude(&leg[0], &leg[1]) == 75f32)
+        };
+
+        // the shortest path flies over the wide obstacle
+        let waypoints = pathfinder.adjust_path::<()>(start, goal).unwrap();
+        let path = Tanstar::assemble_path(start, &waypoints, goal);
+        assert!(flies_over(&pathfinder, &path));
+
+        let path = pathfinder.find_path_no_flyover(start, goal).unwrap();
+        assert!(!flies_over(&pathfinder, &path));
+        assert_eq!(path[0], start);
+        assert_eq!(path[path.len() - 1], goal);
+
+        // obstacle filling the only corridor to the goal can only be flown over
+        let flyzone = [
+            (0f32, 0f32),
+            (200f32, 0f32),
+            (200f32, 80f32),
+            (460f32, 80f32),
+            (460f32, 120f32),
+            (200f32, 120f32),
+            (200f32, 200f32),
+            (0f32, 200f32),
+        ];
+        let flyzones = vec![flyzone
+            .iter()
+            .map(|&(x, y)| Location::from_meters(x, y, 0f32, &origin()))
+            .collect()];
+        let obstacles = vec![obstacle_from_meters(300f32, 100f32, 30f32, 50f32)];
+        let mut pathfinder = Tanstar::create(5f32, flyzones, obstacles);
+        let start = Location::from_meters(50f32, 100f32, 100f32, &origin());
+        let goal = Location::from_meters(400f32, 100f32, 100f32, &origin());
+        assert!(pathfinder.adjust_path::<()>(start, goal).is_some());
+        assert_eq!(
+            pathfinder.find_path_no_flyover(start, goal),
+            Err(PathfinderError::NoPathWithoutFlyover)
+        );
+        // the filter only applies to find_path_no_flyover
+        assert!(pathfinder.adjust_path::<()>(start, goal).is_some());
+    }
+
+    #[test]
+    fn path_batch_test() {
+        let obstacles = [(100f32, 100f32), (200f32, 250f32), (300f32, 120f32)]
+            .iter()
+            .map(|&(x, y)| obstacle_from_meters(x, y, 25f32, 50f32))
+            .collect::<Vec<_>>();
+        let at = |x, y| Location::from_meters(x, y, 20f32, &origin());
+        let mut queries: Vec<(Location, Location)> = (0..9)
+            .map(|i| {
+                let offset = 30f32 * i as f32;
+                (at(20f32, 40f32 + offset), at(380f32, 360f32 - offset))
+            })
+            .collect();
+        // unreachable goal does not affect the queries after it
+        queries.insert(4, (at(20f32, 20f32), at(500f32, 200f32)));
+
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles.clone());
+        let results = pathfinder.find_path_batch(&queries);
+        assert_eq!(results.len(), 10);
+        for (result, &(start, goal)) in results.iter().zip(queries.iter()) {
+            let mut single = Tanstar::create(5f32, square_flyzone(400f32), obstacles.clone());
+            let expected = single
+                .find_shortest_path::<()>(start, goal)
+                .map(|waypoints| Tanstar::assemble_path(start, &waypoints, goal));
+            assert_eq!(*result, expected);
+        }
+        assert_eq!(results[4], Err(PathfinderError::OutsideFlyzone));
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 9);
+    }
+
+    #[test]
+    fn path_with_altitude_target_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 30f32, 40f32)];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let location = |x, y, z| Location::from_meters(x, y, z, &origin());
+        let start = location(50f32, 200f32, 60f32);
+        let goal = location(350f32, 200f32, 60f32);
+        let angle = |a: &Location, b: &Location| {
+            let a = Point::from((a, &origin()));
+            let b = Point::from((b, &origin()));
+            (b.z - a.z).abs().atan2(a.distance(&b))
+        };
+        let over_goal = |path: &[Location], goal: &Location, target| {
+            let last = path[path.len() - 1];
+            assert!((last.lat() - goal.lat()).abs() < 1e-12);
+            assert!((last.lon() - goal.lon()).abs() < 1e-12);
+            assert_eq!(last.alt(), target);
+        };
+
+        // already at the target altitude
+        let base = pathfinder
+            .find_path_with_altitude_target(start, goal, 60f32)
+            .unwrap();
+        let n = base.len();
+        assert_eq!(base[n - 1], goal);
+
+        // shallow enough to climb or descend straight to the target from the last waypoint
+        for &target in &[10f32, 110f32] {
+            let path = pathfinder
+                .find_path_with_altitude_target(start, goal, target)
+                .unwrap();
+            assert_eq!(path.len(), n);
+            assert_eq!(path[..n - 1], base[..n - 1]);
+            over_goal(&path, &goal, target);
+            assert!(angle(&path[n - 2], &path[n - 1]) <= MAX_FLIGHT_ANGLE);
+        }
+
+        // too steep for the last leg, flown back and forth over the goal
+        let last_leg =
+            Point::from((&base[n - 2], &origin())).distance(&Point::from((&goal, &origin())));
+        for &target in &[60f32 + 2f32 * last_leg, 60f32 + 3f32 * last_leg] {
+            let path = pathfinder
+                .find_path_with_altitude_target(start, goal, target)
+                .unwrap();
+            over_goal(&path, &goal, target);
+            assert!(path.len() > n);
+            assert_eq!(path[..n], base[..]);
+            // the altitude change is never steeper than half the flight angle
+            for leg in path[n - 1..].windows(2) {
+                assert!(angle(&leg[0], &leg[1]) <= MAX_FLIGHT_ANGLE / 2f32 + 1e-3);
+            }
+        }
+
+        // the target itself has to be within the altitude limits
+        pathfinder.set_altitude_limits(0f32, 121.92f32);
+        assert_eq!(
+            pathfinder.find_path_with_altitude_target(start, goal, 200f32),
+            Err(PathfinderError::AltitudeOutOfRange { altitude: 200f32 })
+        );
+    }
+
+    #[test]
+    fn path_graph_edges_test() {
+        let obstacles = [(150f32, 170f32), (250f32, 230f32)]
+            .iter()
+            .map(|&(x, y)| obstacle_from_meters(x, y, 40f32, 50f32))
+            .collect();
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let (path, edges) = pathfinder
+            .find_path_returning_graph_edges(start, goal)
+            .unwrap();
+        assert!(path.len() > 2);
+        assert_eq!(edges.len(), path.len() - 1);
+        assert_eq!(edges[0].0, START_VERTEX_INDEX);
+        assert_eq!(edges[edges.len() - 1].1, END_VERTEX_INDEX);
+        for pair in edges.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+        }
+
+        // edges between permanent vertices are connections, or arcs along a ring
+        let mut checked = 0;
+        for &(a, b) in &edges {
+            let (a, b) = match (
+                pathfinder.debug_vertex_by_index(a),
+                pathfinder.debug_vertex_by_index(b),
+            ) {
+                (Some(a), Some(b)) => (a, b),
+                _ => continue,
+            };
+            let connected = a
+                .borrow()
+                .connection
+                .iter()
+                .any(|edge| edge.neighbor.borrow().index == b.borrow().index);
+            let same_node = pathfinder.nodes.iter().any(|node| {
+                a.borrow().is_on_node(&node.borrow()) && b.borrow().is_on_node(&node.borrow())
+            });
+            assert!(connected || same_node);
+            checked += 1;
+        }
+        assert!(checked > 0);
+
+        let outside = Location::from_meters(500f32, 200f32, 20f32, &origin());
+        assert_eq!(
+            pathfinder.find_path_returning_graph_edges(start, outside),
+            Err(PathfinderError::OutsideFlyzone)
+        );
+    }
+
+    #[test]
+    fn obstacle_passable_test() {
+        let obstacle = |x, radius| obstacle_from_meters(x, 200f32, radius, 200f32);
+        // the pole gives the planner a node next to the straight line
+        let obstacles = vec![obstacle(200f32, 40f32), obstacle(300f32, 5f32)];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let full_path = |pathfinder: &mut Tanstar| {
+            let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+            Tanstar::assemble_path(start, &waypoints, goal)
+        };
+
+        let around = full_path(&mut pathfinder);
+        let straight = pathfinder.path_length(&[start, goal]);
+        assert!(pathfinder.path_length(&around) > straight + 1f32);
+
+        // passable obstacle is flown straight through, brushing past the pole
+        pathfinder.set_obstacle_passable(0, true).unwrap();
+        let path = full_path(&mut pathfinder);
+        let length = pathfinder.path_length(&path);
+        assert!(length < pathfinder.path_length(&around));
+        assert!(length < straight + 2f32);
+
+        pathfinder.set_obstacle_passable(0, false).unwrap();
+        let path = full_path(&mut pathfinder);
+        assert!((pathfinder.path_length(&path) - pathfinder.path_length(&around)).abs() < 1f32);
+
+        assert_eq!(
+            pathfinder.set_obstacle_passable(2, true),
+            Err(PathfinderError::InvalidObstacleIndex { index: 2 })
+        );
+    }
+
+    #[test]
+    fn path_with_fuel_constraint_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 40f32, 200f32)];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+        let shortest = Tanstar::assemble_path(start, &waypoints, goal);
+        let length = pathfinder.path_length(&shortest);
+
+        // range just over the shortest path still finds it
+        let path = pathfinder
+            .find_shortest_path_with_fuel_constraint(start, goal, length + 1f32)
+            .unwrap();
+        assert!((pathfinder.path_length(&path) - length).abs() < 0.1f32);
+
+        match pathfinder.find_shortest_path_with_fuel_constraint(start, goal, length - 5f32) {
+            Err(PathfinderError::PathExceedsFuelRange { best_distance }) => {
+                assert!((best_distance - length).abs() < 0.1f32)
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        // range limit is only applied to the constrained query
+        assert!(pathfinder.find_shortest_path::<()>(start, goal).is_ok());
+    }
+
+    #[test]
+    fn arrival_time_test() {
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), Vec::new());
+        let location = |x, y, z| Location::from_meters(x, y, z, &origin());
+        // 300m east then a 400m climb over 300m north, 800m in total
+        let waypoints = [
+            location(50f32, 50f32, 20f32),
+            location(350f32, 50f32, 20f32),
+            location(350f32, 350f32, 420f32),
+        ];
+        let time = pathfinder.estimate_flight_time(&waypoints);
+        assert!((time - 40f32).abs() < 0.1f32);
+
+        let departure = 1_700_000_000u64;
+        let arrival = pathfinder.estimate_arrival_time(&waypoints, departure);
+        assert!((arrival as i64 - (departure + 40) as i64).abs() <= 1);
+        assert_eq!(
+            pathfinder.estimate_arrival_time(&waypoints[..1], departure),
+            departure
+        );
+
+        // 5 m/s towards east speeds up the first leg and leaves the northbound climb alone
+        pathfinder.set_wind(5f32, 0f32);
+        let time = pathfinder.estimate_flight_time(&waypoints);
+        assert!((time - (300f32 / 25f32 + 500f32 / 20f32)).abs() < 0.1f32);
+        let back: Vec<Location> = waypoints.iter().rev().cloned().collect();
+        let time = pathfinder.estimate_flight_time(&back);
+        assert!((time - (500f32 / 20f32 + 300f32 / 15f32)).abs() < 0.1f32);
+
+        // at a lower airspeed the headwind leg takes longer still
+        pathfinder.set_airspeed(10f32);
+        let time = pathfinder.estimate_flight_time(&back);
+        assert!((time - (500f32 / 10f32 + 300f32 / 5f32)).abs() < 0.1f32);
+        let arrival = pathfinder.estimate_arrival_time(&back, departure);
+        assert!((arrival as i64 - (departure + 110) as i64).abs() <= 1);
+
+        // a headwind as fast as the airspeed never arrives
+        pathfinder.set_wind(10f32, 0f32);
+        assert_eq!(pathfinder.estimate_flight_time(&back), f32::INFINITY);
+        assert_eq!(pathfinder.estimate_arrival_time(&back, departure), u64::MAX);
+    }
+
+    #[test]
+    fn path_maximizing_altitude_test() {
+        // terrain rising to the north, encoded as a grid of obstacles
+        let mut obstacles = Vec::new();
+        for &(y, height) in &[(80f32, 20f32), (200f32, 50f32), (320f32, 100f32)] {
+            for &x in &[125f32, 200f32, 275f32] {
+                obstacles.push(obstacle_from_meters(x, y, 30f32, height));
+            }
+        }
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(30f32, 200f32, 60f32, &origin());
+        let goal = Location::from_meters(370f32, 200f32, 60f32, &origin());
+
+        let path = pathfinder
+            .find_path_maximizing_altitude(start, goal)
+            .unwrap();
+        assert_eq!(path[0], start);
+        assert_eq!(path[path.len() - 1], goal);
+        assert!(path.len() > 2);
+        // the buffer above the highest terrain
+        for location in &path[1..path.len() - 1] {
+            assert_eq!(location.alt(), 105f32);
+        }
+        // every leg clears what it flies over, climbing from the start and descending to the goal
+        for leg in path.windows(2) {
+            let altitude = leg[0].alt().max(leg[1].alt());
+            assert!(pathfinder.segment_flyover_altitude(&leg[0], &leg[1]) < altitude);
+        }
+        for leg in path[1..path.len() - 1].windows(2) {
+            let altitude = leg[0].alt().min(leg[1].alt());
+            assert!(pathfinder.segment_flyover_altitude(&leg[0], &leg[1]) < altitude);
+        }
+
+        // the shortest path stays at the start altitude, along the middle of the terrain
+        let shortest = pathfinder.adjust_path::<()>(start, goal).unwrap();
+        for waypoint in &shortest {
+            assert!(waypoint.location.alt() < 100f32);
+        }
+
+        // start and goal already above all of the terrain are joined directly
+        let high =
+            |location: Location| Location::from_radians(location.lat(), location.lon(), 150f32);
+        let path = pathfinder
+            .find_path_maximizing_altitude(high(start), high(goal))
+            .unwrap();
+        assert_eq!(path, vec![high(start), high(goal)]);
+    }
+
+    #[test]
+    fn path_with_max_turns_test() {
+        // weaving between the obstacles is shortest, going around the top of both turns once
+        let obstacles = vec![
+            obstacle_from_meters(150f32, 215f32, 30f32, 50f32),
+            obstacle_from_meters(250f32, 185f32, 30f32, 50f32),
+        ];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+
+        let shortest = pathfinder
+            .find_path_with_max_turns(start, goal, 10)
+            .unwrap();
+        assert_eq!(pathfinder.last_path_turns, 2);
+        assert_eq!(
+            pathfinder.find_path_with_max_turns(start, goal, 1),
+            Err(PathfinderError::TooManyTurns { turns: 2 })
+        );
+
+        pathfinder.set_turn_penalty(1000f32);
+        let path = pathfinder.find_path_with_max_turns(start, goal, 1).unwrap();
+        assert_eq!(pathfinder.last_path_turns, 1);
+        assert!(pathfinder.path_length(&path) > pathfinder.path_length(&shortest));
+        assert_eq!(path[0], start);
+        assert_eq!(path[path.len() - 1], goal);
+    }
+
+    #[test]
+    fn path_for_fixed_wing_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 40f32, 50f32)];
+        let mut pathfinder = Tanstar::new();
+        let config = TConfig {
+            turning_radius: 20f32,
+            ..TConfig::default()
+        };
+        pathfinder.init(config, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let point = |location: &Location| Point::from((location, &origin()));
+        let heading = |a: &Location, b: &Location| heading_degrees(&point(a), &point(b));
+        let radii = |pathfinder: &Tanstar| -> Vec<f32> {
+            pathfinder
+                .nodes
+                .iter()
+                .map(|node| node.borrow().radius)
+                .collect()
+        };
+
+        for &(start_heading, goal_heading) in &[(90f32, 90f32), (0f32, 180f32), (270f32, 0f32)] {
+            let path = pathfinder
+                .find_path_for_fixed_wing(start, start_heading, goal, goal_heading)
+                .unwrap();
+            assert_eq!(path[0], start);
+            assert_eq!(path[path.len() - 1], goal);
+            // turning to the goal heading may loop over the path, but never into obstacles
+            let report = pathfinder.path_feasibility_report(&path);
+            for violation in &report.violations {
+                match violation {
+                    FeasibilityViolation::PathCrossesItself { .. } => {}
+                    _ => panic!("unexpected violation {:?}", violation),
+                }
+            }
+
+            assert!(
+                angle_difference(
+                    heading(&path[0], &path[1]).to_radians(),
+                    start_heading.to_radians()
+                )
+                .abs()
+                    < 0.2
+            );
+            let n = path.len();
+            assert!(
+                angle_difference(
+                    heading(&path[n - 2], &path[n - 1]).to_radians(),
+                    goal_heading.to_radians()
+                )
+                .abs()
+                    < 0.2
+            );
+            // consecutive legs never turn tighter than the turning radius
+            for triple in path.windows(3) {
+                let change = angle_difference(
+                    heading(&triple[0], &triple[1]).to_radians(),
+                    heading(&triple[1], &triple[2]).to_radians(),
+                );
+                assert!(change.abs() <= DUBINS_STEP / 20f32 + 0.05);
+            }
+        }
+
+        // wider turns only clear the obstacle once it is planned around with a larger buffer,
+        // which is done separately, leaving the graph as it was
+        pathfinder.set_config(TConfig {
+            turning_radius: 30f32,
+            ..TConfig::default()
+        });
+        let graph = radii(&pathfinder);
+        let path = pathfinder
+            .find_path_for_fixed_wing(start, 90f32, goal, 90f32)
+            .unwrap();
+        let points: Vec<Point> = path.iter().map(point).collect();
+        assert!(pathfinder.validate_curve(&points).is_ok());
+        assert_eq!(pathfinder.config.buffer_size, DEFAULT_BUFFER_SIZE);
+        assert_eq!(radii(&pathfinder), graph);
+    }
+
+    #[test]
+    fn equidistant_waypoints_test() {
+        let obstacles = vec![obstacle_from_meters(95f32, 305f32, 30f32, 200f32)];
+        let pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let at = |x, y| Location::from_meters(x, y, 20f32, &origin());
+        let points = |path: &[Location]| -> Vec<Point> {
+            path.iter()
+                .map(|location| Point::from((location, &pathfinder.origin)))
+                .collect()
+        };
+
+        // a straight path is split into equal legs
+        let straight = [at(50f32, 50f32), at(350f32, 50f32)];
+        let path = pathfinder.find_equidistant_waypoints(&straight, 5);
+        assert_eq!(path.len(), 5);
+        for leg in points(&path).windows(2) {
+            assert!((leg[0].distance3d(&leg[1]) - 75f32).abs() < 0.1);
+        }
+
+        // the leg from 200m to 400m along the path cuts the corner through the obstacle, so
+        // its first waypoint is moved onto the corner
+        let corner = [at(50f32, 50f32), at(50f32, 350f32), at(350f32, 350f32)];
+        let path = pathfinder.find_equidistant_waypoints(&corner, 4);
+        assert_eq!(path.len(), 4);
+        let (resampled, original) = (points(&path), points(&corner));
+        assert_eq!(resampled[0].distance3d(&original[0]), 0f32);
+        assert!(resampled[1].distance3d(&original[1]) < 0.1);
+        assert!(resampled[2].distance3d(&Point::new(150f32, 350f32, 20f32)) < 0.1);
+        assert_eq!(resampled[3].distance3d(&original[2]), 0f32);
+        assert!(pathfinder.validate_curve(&resampled).is_ok());
+    }
+
+    #[test]
+    fn grid_path_test() {
+        let obstacles = vec![obstacle_from_meters(100f32, 100f32, 25f32, 200f32)];
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(200f32), obstacles);
+        let path = pathfinder.grid_path(30f32, 20f32).unwrap();
+        // 10 by 10 grid without the 4 points inside the obstacle
+        assert_eq!(path.len(), 96);
+        let points: Vec<Point> = path
+            .iter()
+            .map(|location| Point::from((location, &pathfinder.origin)))
+            .collect();
+        let center = Point::new(100f32, 100f32, 30f32);
+        for point in &points {
+            assert!(point.distance(&center) >= 25f32);
+            assert!((point.z - 30f32).abs() < 1e-3);
+        }
+        assert!(pathfinder.validate_curve(&points).is_ok());
+        // each point is visited once
+        for (i, a) in points.iter().enumerate() {
+            assert!(points[i + 1..].iter().all(|b| a.distance(b) > 1f32));
+        }
+
+        pathfinder.set_altitude_limits(0f32, 121.92f32);
+        assert_eq!(
+            pathfinder.grid_path(-10f32, 20f32),
+            Err(PathfinderError::AltitudeOutOfRange { altitude: -10f32 })
+        );
+    }
+
+    #[test]
+    fn segment_wind_component_test() {
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), Vec::new());
+        let at = |x, y| Location::from_meters(x, y, 20f32, &origin());
+        let (a, east, north) = (at(100f32, 100f32), at(300f32, 100f32), at(100f32, 300f32));
+        assert_eq!(pathfinder.segment_wind_component(&a, &east), 0f32);
+
+        // 10 m/s blowing towards east
+        pathfinder.set_wind(10f32, 0f32);
+        assert!((pathfinder.segment_wind_component(&a, &east) - 10f32).abs() < 1e-3);
+        assert!((pathfinder.segment_wind_component(&east, &a) + 10f32).abs() < 1e-3);
+        assert!(pathfinder.segment_wind_component(&a, &north).abs() < 1e-3);
+        let northeast = at(200f32, 200f32);
+        let component = 10f32 / 2f32.sqrt();
+        assert!((pathfinder.segment_wind_component(&a, &northeast) - component).abs() < 1e-2);
+        assert!((pathfinder.segment_wind_component(&northeast, &a) + component).abs() < 1e-2);
+    }
+
+    #[test]
+    fn dynamic_obstacle_test() {
+        let obstacles = vec![
+            obstacle_from_meters(200f32, 60f32, 30f32, 200f32),
+            obstacle_from_meters(200f32, 230f32, 5f32, 200f32),
+        ];
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let (a, b) = (
+            Point::from((&start, &pathfinder.origin)),
+            Point::from((&goal, &pathfinder.origin)),
+        );
+        let full_path = |pathfinder: &mut Tanstar| {
+            let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+            Tanstar::assemble_path(start, &waypoints, goal)
+        };
+        let still = full_path(&mut pathfinder);
+        assert!(matches!(pathfinder.valid_path(&a, &b), PathValidity::Flyover(t) if t == 0f32));
+
+        // flying north at the airspeed, the obstacle reaches the line as the plane is halfway
+        pathfinder.set_airspeed(20f32);
+        pathfinder
+            .set_obstacle_dynamic(0, (0f32, 20f32, 0f32))
+            .unwrap();
+        let predicted = Point::from((&pathfinder.predict_obstacle_position(0, 7f32), &origin()));
+        assert!(predicted.distance(&Point::new(200f32, 200f32, 0f32)) < 0.1);
+        assert!(matches!(pathfinder.valid_path(&a, &b), PathValidity::Flyover(t) if t == 200f32));
+        // flown later, the obstacle is already past
+        assert!(matches!(
+            pathfinder.valid_path_at(&a, &b, 10f32),
+            PathValidity::Flyover(t) if t == 0f32
+        ));
+
+        let path = full_path(&mut pathfinder);
+        assert!(pathfinder.path_length(&path) > pathfinder.path_length(&still) + 1f32);
+        let mut elapsed = 0f32;
+        for leg in path.windows(2) {
+            let (p, q) = (
+                Point::from((&leg[0], &pathfinder.origin)),
+                Point::from((&leg[1], &pathfinder.origin)),
+            );
+            match pathfinder.valid_path_at(&p, &q, elapsed) {
+                PathValidity::Flyover(t) => assert!(t < 20f32),
+                PathValidity::Valid => {}
+                PathValidity::Invalid => panic!(),
+            }
+            elapsed += p.distance3d(&q) / 20f32;
+        }
+
+        pathfinder
+            .set_obstacle_dynamic(0, (0f32, 0f32, 0f32))
+            .unwrap();
+        let path = full_path(&mut pathfinder);
+        assert!((pathfinder.path_length(&path) - pathfinder.path_length(&still)).abs() < 1f32);
+        assert_eq!(
+            pathfinder.set_obstacle_dynamic(2, (1f32, 0f32, 0f32)),
+            Err(PathfinderError::InvalidObstacleIndex { index: 2 })
+        );
+    }
+
+    #[test]
+    fn path_anytime_test() {
+        let obstacles = [
+            (371f32, 112f32, 32f32),
+            (244f32, 499f32, 34f32),
+            (495f32, 304f32, 10f32),
+            (321f32, 198f32, 24f32),
+            (263f32, 131f32, 18f32),
+            (178f32, 169f32, 14f32),
+            (189f32, 269f32, 37f32),
+            (104f32, 320f32, 21f32),
+            (397f32, 495f32, 18f32),
+            (473f32, 128f32, 25f32),
+            (422f32, 497f32, 18f32),
+            (134f32, 414f32, 16f32),
+        ]
+        .iter()
+        .map(|&(x, y, radius)| obstacle_from_meters(x, y, radius, 200f32))
+        .collect();
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(600f32), obstacles);
+        let start = Location::from_meters(30f32, 30f32, 20f32, &origin());
+        let goal = Location::from_meters(570f32, 570f32, 20f32, &origin());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let path = pathfinder.find_path_anytime(start, goal, deadline).unwrap();
+
+        // every search finds a path at most as long as the one before, and the inflated
+        // heuristic leaves room for at least three improvements
+        let costs = pathfinder.last_anytime_costs.clone();
+        assert!(costs.windows(2).all(|pair| pair[1] <= pair[0]));
+        let mut distinct = costs.clone();
+        distinct.dedup();
+        assert!(distinct.len() >= 3);
+        let optimal = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+        assert!((costs[costs.len() - 1] - pathfinder.last_path_cost).abs() < 1e-3);
+        assert_eq!(path.len(), optimal.len() + 2);
+
+        // a deadline in the past leaves no time to search
+        assert!(matches!(
+            pathfinder.find_path_anytime(start, goal, Instant::now()),
+            Err(PathfinderError::PlanningTimeout { .. })
+        ));
+    }
+
+    #[test]
+    fn path_multi_goal_test() {
+        // the closest goal sits inside a ring of obstacles
+        let mut obstacles: Vec<Obstacle> = (0..8)
+            .map(|i| {
+                let angle = i as f32 * PI / 4f32;
+                Obstacle::new(
+                    Location::from_meters(
+                        150f32 + 30f32 * angle.cos(),
+                        200f32 + 30f32 * angle.sin(),
+                        0f32,
+                        &origin(),
+                    ),
+                    15f32,
+                    200f32,
+                )
+            })
+            .collect();
+        obstacles.push(obstacle_from_meters(250f32, 200f32, 10f32, 200f32));
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let at = |x, y| Location::from_meters(x, y, 20f32, &origin());
+        let start = at(50f32, 200f32);
+        let goals = [at(350f32, 350f32), at(150f32, 200f32), at(300f32, 200f32)];
+
+        let (path, index) = pathfinder.find_path_multi_goal(start, &goals).unwrap();
+        assert_eq!(index, 2);
+        let end = Point::from((&path[path.len() - 1], &pathfinder.origin));
+        assert!(end.distance(&Point::new(300f32, 200f32, 20f32)) < 0.1);
+        let cost = pathfinder.last_path_cost;
+        let direct = pathfinder
+            .find_shortest_path::<()>(start, goals[2])
+            .unwrap();
+        assert_eq!(path.len(), direct.len() + 2);
+        assert!((cost - pathfinder.last_path_cost).abs() < 0.01);
+
+        // indices are those of the goals given, invalid ones included
+        let with_invalid = [at(500f32, 200f32), goals[0], goals[2]];
+        let (multi, index) = pathfinder
+            .find_path_multi_goal(start, &with_invalid)
+            .unwrap();
+        assert_eq!(index, 2);
+        assert_eq!(multi, path);
+
+        assert_eq!(
+            pathfinder.find_path_multi_goal(start, &goals[1..2]),
+            Err(PathfinderError::AllGoalsUnreachable)
+        );
+        assert_eq!(
+            pathfinder.find_path_multi_goal(start, &[]),
+            Err(PathfinderError::AllGoalsUnreachable)
+        );
+    }
+
+    #[test]
+    fn path_waypoint_types_test() {
+        let obstacle = |x, radius, height| obstacle_from_meters(x, 200f32, radius, height);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let full_path = |pathfinder: &mut Tanstar| {
+            let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+            Tanstar::assemble_path(start, &waypoints, goal)
+        };
+
+        // flying around a tall obstacle enters and leaves its ring
+        let obstacles = vec![obstacle(200f32, 40f32, 200f32)];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        let path = full_path(&mut pathfinder);
+        let types = pathfinder.path_waypoint_types(&path);
+        assert_eq!(types.len(), path.len());
+        assert_eq!(types[0], WaypointType::StartPoint);
+        assert_eq!(types[1], WaypointType::PathHugStart);
+        assert!(types[2..types.len() - 1]
+            .iter()
+            .all(|&kind| kind == WaypointType::PathHugEnd));
+        assert_eq!(types[types.len() - 1], WaypointType::GoalPoint);
+
+        // a low obstacle is flown over on the way to a pole
+        let obstacles = vec![
+            obstacle(150f32, 40f32, 10f32),
+            obstacle(300f32, 2f32, 200f32),
+        ];
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let path = full_path(&mut pathfinder);
+        let types = pathfinder.path_waypoint_types(&path);
+        assert!(types.contains(&WaypointType::FlyoverApex));
+
+        // other paths only have their ends told apart
+        let types = pathfinder.path_waypoint_types(&[start, path[1], goal]);
+        assert_eq!(
+            types,
+            [
+                WaypointType::StartPoint,
+                WaypointType::DirectSegment,
+                WaypointType::GoalPoint
+            ]
+        );
+    }
+
+    #[test]
+    fn path_with_loiter_at_goal_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 40f32, 200f32)];
+        let mut pathfinder = Tanstar::create(5f32, square_flyzone(400f32), obstacles);
+        pathfinder.set_airspeed(20f32);
+        // start south of the axis so the approach passes below the obstacle, outside the circle
+        let start = Location::from_meters(50f32, 190f32, 20f32, &origin());
+        let goal = Location::from_meters(330f32, 200f32, 20f32, &origin());
+        // 100s at 20m/s is a bit over five laps of a 60m circle
+        let path = pathfinder
+            .find_path_with_loiter_at_goal(start, goal, 60f32, 100f32)
+            .unwrap();
+        let approach = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+        assert_eq!(path.len(), 1 + approach.len() + 6 * LOITER_POINTS + 1);
+
+        let points: Vec<Point> = path
+            .iter()
+            .map(|location| Point::from((location, &pathfinder.origin)))
+            .collect();
+        let center = Point::new(330f32, 200f32, 20f32);
+        let loiter = &points[1 + approach.len()..];
+        for point in loiter {
+            assert!((point.distance(&center) - 60f32).abs() < 0.1);
+        }
+        // the loiter is flown counter-clockwise and closes on itself
+        let (a, b) = (&loiter[0], &loiter[1]);
+        let cross = (a.x - center.x) * (b.y - center.y) - (a.y - center.y) * (b.x - center.x);
+        assert!(cross > 0f32);
+        assert!(loiter[0].distance(&loiter[loiter.len() - 1]) < 0.1);
+        // joining the circle turns less than flying to its center and out again would
+        let last = points[approach.len()];
+        let join = (a.y - last.y).atan2(a.x - last.x);
+        let along = (b.y - a.y).atan2(b.x - a.x);
+        assert!(angle_difference(join, along).abs() < PI / 2f32);
+        assert!(pathfinder.validate_curve(&points).is_ok());
+    }
+
+    #[test]
+    fn path_at_time_test() {
+        let obstacles = vec![
+            obstacle_from_meters(200f32, 60f32, 30f32, 200f32),
+            obstacle_from_meters(200f32, 230f32, 5f32, 200f32),
+        ];
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let still = pathfinder.find_path_at_time(start, goal, 0f32).unwrap();
+        pathfinder.set_airspeed(20f32);
+        pathfinder
+            .set_obstacle_dynamic(0, (0f32, 20f32, 1f32))
+            .unwrap();
+
+        let environment = pathfinder.environment_at_time(10f32);
+        assert_eq!(environment.len(), 2);
+        let (location, radius, height) = environment[0];
+        let moved = Point::from((&location, &origin()));
+        assert!(moved.distance(&Point::new(200f32, 260f32, 0f32)) < 0.1);
+        assert_eq!((radius, height), (30f32, 210f32));
+        let (location, _, height) = environment[1];
+        let pole = Point::from((&location, &origin()));
+        assert!(pole.distance(&Point::new(200f32, 230f32, 0f32)) < 0.1);
+        assert_eq!(height, 200f32);
+
+        // leaving now, the obstacle crosses the way
+        let now = pathfinder.find_path_at_time(start, goal, 0f32).unwrap();
+        assert!(pathfinder.path_length(&now) > pathfinder.path_length(&still) + 1f32);
+        // leaving later, it is far north once the plane gets there
+        let later = pathfinder.find_path_at_time(start, goal, 20f32).unwrap();
+        assert!(pathfinder.path_length(&later) < pathfinder.path_length(&now));
+
+        // the obstacles of the planner itself did not move
+        let center = Point::from((&pathfinder.obstacles[0].location, &origin()));
+        assert!(center.distance(&Point::new(200f32, 60f32, 0f32)) < 0.1);
+    }
+
+    #[test]
+    fn deduplicate_waypoints_test() {
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), Vec::new());
+        pathfinder.set_waypoint_tolerance(0.1f32);
+        let path = vec![
+            Location::from_meters(10f32, 10f32, 20f32, &origin()),
+            Location::from_meters(100f32, 10f32, 20f32, &origin()),
+            Location::from_meters(100.05f32, 10f32, 20f32, &origin()),
+            Location::from_meters(200f32, 10f32, 20f32, &origin()),
+            Location::from_meters(200.15f32, 10f32, 20f32, &origin()),
+        ];
+        let merged = pathfinder.deduplicate_waypoints(path.clone());
+        assert_eq!(merged.len(), 4);
+        assert_eq!(merged[1], path[1]);
+        assert_eq!(merged[2], path[3]);
+        assert_eq!(merged[3], path[4]);
+
+        // the goal is kept over a close waypoint before it
+        let merged = pathfinder.deduplicate_waypoints(path[..3].to_vec());
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1], path[2]);
+    }
+
+    #[test]
+    fn count_path_hug_arcs_test() {
+        let obstacle = |x, y, radius| obstacle_from_meters(x, y, radius, 200f32);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let count = |obstacles| {
+            let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+            let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+            let path = Tanstar::assemble_path(start, &waypoints, goal);
+            pathfinder.count_path_hug_arcs(&path)
+        };
+
+        // a straight line has no arc
+        let pathfinder = Tanstar::create(1f32, square_flyzone(400f32), Vec::new());
+        assert_eq!(pathfinder.count_path_hug_arcs(&[start, goal]), 0);
+        assert_eq!(count(vec![obstacle(200f32, 200f32, 40f32)]), 1);
+        // each obstacle in the way adds an arc
+        let obstacles = vec![
+            obstacle(130f32, 200f32, 30f32),
+            obstacle(270f32, 200f32, 30f32),
+        ];
+        assert_eq!(count(obstacles), 2);
+    }
+
+    #[test]
+    fn obstacle_uncertainty_test() {
+        let center = Point::new(200f32, 200f32, 0f32);
+        let obstacles = vec![Obstacle::new(
+            Location::from((&center, &origin())),
+            30f32,
+            200f32,
+        )];
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let clearance = |pathfinder: &mut Tanstar| {
+            let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+            let path = Tanstar::assemble_path(start, &waypoints, goal);
+            path.windows(2)
+                .map(|leg| {
+                    let a = Point::from((&leg[0], &origin()));
+                    let b = Point::from((&leg[1], &origin()));
+                    point_to_segment_distance_2d(&center, &a, &b)
+                })
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let nominal = clearance(&mut pathfinder);
+        assert!(nominal >= 30f32);
+        // 3 sigma of a 5 m deviation keeps 15 m more clear
+        pathfinder.set_obstacle_uncertainty(0, 3f32, 4f32).unwrap();
+        pathfinder.build_graph();
+        let uncertain = clearance(&mut pathfinder);
+        assert!(uncertain > nominal + 10f32);
+        // segments between waypoints on the 46 m ring cut slightly inside it
+        assert!(uncertain > 44f32);
+        // a lower sigma takes less room
+        pathfinder.set_risk_sigma(1f32);
+        pathfinder.build_graph();
+        let lower = clearance(&mut pathfinder);
+        assert!(lower > nominal && lower < uncertain);
+
+        assert_eq!(
+            pathfinder.set_obstacle_uncertainty(1, 1f32, 1f32),
+            Err(PathfinderError::InvalidObstacleIndex { index: 1 })
+        );
+        for &(pos_std, radius_std) in &[(-1f32, 1f32), (1f32, f32::NAN), (f32::INFINITY, 0f32)] {
+            assert_eq!(
+                pathfinder.set_obstacle_uncertainty(0, pos_std, radius_std),
+                Err(PathfinderError::InvalidUncertainty)
+            );
+        }
+        // the previous deviations are kept
+        assert_eq!(pathfinder.obstacle_uncertainty.get(&0), Some(&(3f32, 4f32)));
+    }
+
+    #[test]
+    fn path_minimizing_exposure_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 40f32, 200f32)];
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 190f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 190f32, 20f32, &origin());
+        let threat = Location::from_meters(200f32, 150f32, 0f32, &origin());
+        let middle = |path: &[Location]| {
+            let (a, b) = (&path[path.len() / 2 - 1], &path[path.len() / 2]);
+            Point::from((a, &origin())).lerp(&Point::from((b, &origin())), 0.5f32)
+        };
+
+        // the shortest path passes south of the obstacle, in sight of the threat
+        let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+        let shortest = Tanstar::assemble_path(start, &waypoints, goal);
+        let threat_point = Point::from((&threat, &origin()));
+        let seen = middle(&shortest);
+        assert!(seen.y < 200f32);
+        assert!(pathfinder.line_of_sight(&threat_point, &Point::new(seen.x, seen.y, 20f32)));
+
+        // avoiding the threat goes around the north side, hidden behind the obstacle
+        let path = pathfinder
+            .find_path_minimizing_exposure(start, goal, &[threat])
+            .unwrap();
+        let hidden = middle(&path);
+        assert!(hidden.y > 200f32);
+        assert!(!pathfinder.line_of_sight(&threat_point, &Point::new(hidden.x, hidden.y, 20f32)));
+        assert!(pathfinder.path_length(&path) > pathfinder.path_length(&shortest));
+        assert!(pathfinder.threats.is_empty());
+
+        // without any weight the threat changes nothing
+        pathfinder.set_threat_weight(0f32);
+        let path = pathfinder
+            .find_path_minimizing_exposure(start, goal, &[threat])
+            .unwrap();
+        assert!(middle(&path).y < 200f32);
+    }
+
+    #[test]
+    fn path_maximizing_sensor_coverage_test() {
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 30f32, 200f32)];
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 190f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 190f32, 20f32, &origin());
+        // cluster north west of the obstacle, away from the shortest path
+        let cluster = Point::new(125f32, 215f32, 0f32);
+        let offsets = [
+            (0f32, 0f32),
+            (5f32, 0f32),
+            (-5f32, 0f32),
+            (0f32, 5f32),
+            (0f32, -5f32),
+        ];
+        let targets: Vec<Location> = offsets
+            .iter()
+            .map(|&(dx, dy)| {
+                Location::from((&Point::new(cluster.x + dx, cluster.y + dy, 0f32), &origin()))
+            })
+            .collect();
+        let closest = |path: &[Location]| {
+            path.windows(2)
+                .map(|leg| {
+                    let a = Point::from((&leg[0], &origin()));
+                    let b = Point::from((&leg[1], &origin()));
+                    point_to_segment_distance_2d(&cluster, &a, &b)
+                })
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        let waypoints = pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+        let shortest = Tanstar::assemble_path(start, &waypoints, goal);
+        assert!(closest(&shortest) > 30f32);
+
+        let path = pathfinder
+            .find_path_maximizing_sensor_coverage(start, goal, 20f32, &targets)
+            .unwrap();
+        assert!(closest(&path) < 20f32);
+        assert!(pathfinder.path_length(&path) > pathfinder.path_length(&shortest));
+        assert!(pathfinder.targets.is_empty());
+
+        // without a bonus the targets change nothing
+        pathfinder.set_coverage_bonus(0f32);
+        let path = pathfinder
+            .find_path_maximizing_sensor_coverage(start, goal, 20f32, &targets)
+            .unwrap();
+        assert!(closest(&path) > 30f32);
+    }
+
+    #[test]
+    fn sensor_coverage_optimal_test() {
+        // the obstacle sits north of the direct line, so the south side is shorter
+        let obstacles = vec![obstacle_from_meters(200f32, 215f32, 30f32, 200f32)];
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), obstacles);
+        let start = Location::from_meters(50f32, 190f32, 20f32, &origin());
+        let goal = Location::from_meters(350f32, 190f32, 20f32, &origin());
+        pathfinder.find_shortest_path::<()>(start, goal).unwrap();
+        let shortest_cost = pathfinder.last_path_cost;
+
+        // targets under the last leg of the north side make it far cheaper, but only once the
+        // search is past the obstacle, where the distance to goal no longer bounds the cost
+        let target = Location::from((&Point::new(275f32, 225f32, 0f32), &origin()));
+        let path = pathfinder
+            .find_path_maximizing_sensor_coverage(start, goal, 20f32, &[target; 3])
+            .unwrap();
+        let north = path
+            .iter()
+            .map(|location| Point::from((location, &origin())).y)
+            .fold(f32::MIN, f32::max);
+        assert!(north > 240f32);
+        let bonus = 3f32 * DEFAULT_COVERAGE_BONUS;
+        assert!(pathfinder.path_length(&path) > shortest_cost);
+        assert!((pathfinder.last_path_cost - (pathfinder.path_length(&path) - bonus)).abs() < 1f32);
+        assert!(pathfinder.last_path_cost < shortest_cost - 100f32);
+    }
+
+    #[test]
+    fn override_test() {
+        let mut pathfinder = Tanstar::create(1f32, square_flyzone(400f32), Vec::new());
+        {
+            let mut planner = pathfinder.with_override(|planner| &mut planner.max_range, None);
+            let planner = planner.with_override(|planner| &mut planner.heuristic_weight, 2f32);
+            assert_eq!(planner.heuristic_weight, 2f32);
+        }
+        assert_eq!(pathfinder.heuristic_weight, 1f32);
+
+        // the old value is put back while unwinding from a failed search
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let planner = pathfinder.with_override(|planner| &mut planner.no_flyover, true);
+            assert!(planner.no_flyover);
+            panic!("search failed");
+        }));
+        assert!(result.is_err());
+        assert!(!pathfinder.no_flyover);
+    }
+}
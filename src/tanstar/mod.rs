@@ -2,21 +2,27 @@
 // contains main functionality of the library
 use super::obj::*;
 use super::Algorithm;
+use super::PathfinderError;
 
 pub mod config;
+pub mod feasibility;
 
+mod export;
 mod graph;
+mod mission;
 mod queue;
 
 pub use self::config::*;
+pub use self::feasibility::*;
+pub use self::mission::WaypointType;
 
 use self::graph::*;
 use self::queue::Queue;
 use std::cell::RefCell;
-use std::collections::{BinaryHeap, HashSet, LinkedList};
+use std::collections::{BinaryHeap, HashMap, HashSet, LinkedList};
 use std::f32::consts::PI;
 use std::rc::Rc;
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 
 // const EQUATORIAL_RADIUS: f64 = 63781370.0;
 // const POLAR_RADIUS: f64 = 6356752.0;
@@ -30,6 +36,19 @@ const RADIUS: f64 = 6_371_000.0;
 const START_VERTEX_INDEX: i32 = -1;
 const END_VERTEX_INDEX: i32 = -2;
 const HEADER_VERTEX_INDEX: i32 = -3;
+const DEFAULT_TIMEOUT_CHECK_INTERVAL: u32 = 64;
+const DEFAULT_TURN_THRESHOLD_DEG: f32 = 10f32;
+const DEFAULT_AIRSPEED: f32 = 20f32; // In meters per second
+const DEFAULT_WAYPOINT_TOLERANCE: f32 = 0.1f32; // In meters
+const DEFAULT_RISK_SIGMA: f32 = 3f32;
+const DEFAULT_THREAT_WEIGHT: f32 = 100f32; // In meters per second seen by one threat
+const DEFAULT_COVERAGE_BONUS: f32 = 50f32; // In meters per target
+
+// Weight of the heuristic and whether it adds the arcs to ring exits
+type Heuristic = (f32, bool);
+
+// End vertices of a search and the index of the goal each one reaches
+type EndVertices = Vec<(Rc<RefCell<Vertex>>, usize)>;
 
 #[allow(non_snake_case)]
 pub struct Tanstar {
@@ -37,12 +56,52 @@ pub struct Tanstar {
     config: TConfig,
     flyzones: Vec<Vec<Location>>,
     obstacles: Vec<Obstacle>,
+    home_location: Option<Location>,
     // private
     initialized: bool,
     start_time: SystemTime,
     origin: Location, // Reference point defining each node
     nodes: Vec<Rc<RefCell<Node>>>,
     num_vertices: i32,
+    dirty_nodes: HashSet<usize>, // Nodes with edges to be recomputed
+    obstacle_sentinels: HashMap<(usize, usize), LinkedList<Rc<RefCell<Vertex>>>>,
+    forbidden_edges: HashSet<(usize, usize)>, // Node pairs whose edges are never taken
+    forbidden_nodes: HashSet<usize>,          // Nodes that are never entered
+    passable_obstacles: HashSet<usize>,       // Obstacles the planner ignores
+    last_path: Option<Vec<Location>>,         // Start, waypoints and end of last path found
+    last_path_vertices: Vec<i32>,             // Indices of the vertices along the last path found
+    last_path_types: Vec<WaypointType>,       // Type of each location of the last path found
+    keep_out_zones: Vec<Vec<Location>>,       // Areas no path may enter or cross
+    lazy_recheck: bool,                       // Recheck edges against obstacles during search
+    no_flyover: bool,                         // Skip edges that fly over any obstacle
+    max_range: Option<f32>,                   // Drop partial paths longer than this, in meters
+    last_search_explored: usize,              // Vertices popped from the open set in last search
+    planning_timeout: Option<Duration>,       // Abort searches running longer than this
+    timeout_check_interval: u32,              // Search iterations between deadline checks
+    min_vertex_separation: f32,               // Smallest angle between ring vertices, in radians
+    turn_penalty: f32,                        // Extra cost of each turn around a node, in meters
+    turn_threshold: f32,                      // Smallest heading change of a turn, in radians
+    max_turns: Option<usize>,                 // Reject paths with more turns than this
+    last_path_turns: usize,                   // Turns of the last path found
+    wind: (f32, f32),                         // Wind velocity towards east and north, in m/s
+    // Velocity of moving obstacles towards east, north and up, in m/s
+    obstacle_velocities: HashMap<usize, (f32, f32, f32)>,
+    airspeed: f32,                // Predicts where moving obstacles are met, in m/s
+    heuristic_weight: f32,        // Inflation of the heuristic, 1 for shortest paths
+    ring_heuristic: bool,         // Add the arc to the nearest exit of a ring to the heuristic
+    last_path_cost: f32,          // Search cost of the last path found, in meters
+    last_anytime_costs: Vec<f32>, // Cost of the path of each anytime search
+    waypoint_tolerance: f32,      // Consecutive waypoints closer than this are merged, in meters
+    // Standard deviations of the position and radius of uncertain obstacles, in meters
+    obstacle_uncertainty: HashMap<usize, (f32, f32)>,
+    risk_sigma: f32,     // Deviations of uncertain obstacles added to their buffer
+    threats: Vec<Point>, // Threats whose line of sight adds to the cost of edges
+    threat_weight: f32,  // Cost of each second seen by one threat, in meters
+    targets: Vec<Point>, // Targets whose coverage lowers the cost of edges
+    sensor_fov: f32,     // Distance from the middle of an edge a target is covered within
+    coverage_bonus: f32, // Cost removed for each target covered by an edge, in meters
+    flight_altitude: Option<f32>, // Altitude obstacles are flown around at, tapering their rings
+    altitude_limits: (f32, f32), // Lowest and highest altitude locations may be at, in meters
 }
 
 impl Default for Tanstar {
@@ -52,12 +111,50 @@ impl Default for Tanstar {
             config: TConfig::default(),
             flyzones: Vec::new(),
             obstacles: Vec::new(),
+            home_location: None,
             // private
             initialized: false,
             start_time: SystemTime::now(),
             origin: Location::from_degrees(0f64, 0f64, 0f32),
             nodes: Vec::new(),
             num_vertices: 0i32,
+            dirty_nodes: HashSet::new(),
+            obstacle_sentinels: HashMap::new(),
+            forbidden_edges: HashSet::new(),
+            forbidden_nodes: HashSet::new(),
+            passable_obstacles: HashSet::new(),
+            last_path: None,
+            last_path_vertices: Vec::new(),
+            last_path_types: Vec::new(),
+            keep_out_zones: Vec::new(),
+            lazy_recheck: false,
+            no_flyover: false,
+            max_range: None,
+            last_search_explored: 0,
+            planning_timeout: None,
+            timeout_check_interval: DEFAULT_TIMEOUT_CHECK_INTERVAL,
+            min_vertex_separation: 0f32,
+            turn_penalty: 0f32,
+            turn_threshold: DEFAULT_TURN_THRESHOLD_DEG.to_radians(),
+            max_turns: None,
+            last_path_turns: 0,
+            wind: (0f32, 0f32),
+            obstacle_velocities: HashMap::new(),
+            airspeed: DEFAULT_AIRSPEED,
+            heuristic_weight: 1f32,
+            ring_heuristic: true,
+            last_path_cost: 0f32,
+            last_anytime_costs: Vec::new(),
+            waypoint_tolerance: DEFAULT_WAYPOINT_TOLERANCE,
+            obstacle_uncertainty: HashMap::new(),
+            risk_sigma: DEFAULT_RISK_SIGMA,
+            threats: Vec::new(),
+            threat_weight: DEFAULT_THREAT_WEIGHT,
+            targets: Vec::new(),
+            sensor_fov: 0f32,
+            coverage_bonus: DEFAULT_COVERAGE_BONUS,
+            flight_altitude: None,
+            altitude_limits: (f32::NEG_INFINITY, f32::INFINITY),
         }
     }
 }
@@ -67,72 +164,309 @@ impl Tanstar {
         Tanstar::default()
     }
 
-    // determine if flyzone intersects itself (correct order)
-    // inputs (flyzones, origin), outputs true if invalid
-    #[allow(clippy::many_single_char_names)]
-    fn invalid_flyzone(flyzones: &[Vec<Location>], origin: &Location) -> bool {
-        for flyzone in flyzones {
-            let mut vertices = Vec::new();
-            for loc in flyzone {
-                let point = Point::from((loc, origin));
-                vertices.push(point);
+    // Reference location that points of the graph are measured from
+    pub fn origin(&self) -> Location {
+        self.origin
+    }
+
+    // Set the location missions are planned around
+    pub fn set_home_location(&mut self, home: Location) {
+        self.home_location = Some(home);
+    }
+
+    // Location missions are planned around, defaults to centroid of the first flyzone
+    pub fn home_location(&self) -> Location {
+        match self.home_location {
+            Some(home) => home,
+            None => {
+                let centroid = self
+                    .compute_flyzone_centroid(0)
+                    .expect("Require at least one flyzone");
+                Location::from((&centroid, &self.origin))
             }
-            let n = vertices.len();
-            // compares any side of flyzone, ab, with any non-adjacent side, cd
-            for ab in 0..n - 2 {
-                let a = vertices[ab];
-                let b = vertices[ab + 1];
-                for i in 2..n - 1 {
-                    let cd = ab + i;
-                    let c = vertices[cd];
-                    let d = vertices[(cd + 1) % n];
-                    if intersect(&a, &b, &c, &d) {
-                        return true;
-                    }
-                    if cd + 1 == n {
-                        break;
-                    }
+        }
+    }
+
+    // Number of locations in the last path found, including start and end
+    // 0 if no path has been found
+    pub fn waypoint_count(&self) -> usize {
+        match self.last_path {
+            Some(ref path) => path.len(),
+            None => 0,
+        }
+    }
+
+    // Number of vertices the last search expanded, a measure of how hard it was to plan
+    pub fn count_explored_vertices_last_search(&self) -> usize {
+        self.last_search_explored
+    }
+
+    // Number of segments flown in the last path found, 0 if no path has been found
+    pub fn segment_count(&self) -> usize {
+        self.waypoint_count().saturating_sub(1)
+    }
+
+    // Prevent paths from taking any edge between node a and node b
+    pub fn set_forbidden_edge(&mut self, node_a: usize, node_b: usize) {
+        self.forbidden_edges
+            .insert((node_a.min(node_b), node_a.max(node_b)));
+    }
+
+    pub fn clear_forbidden_edges(&mut self) {
+        self.forbidden_edges.clear();
+    }
+
+    // Add a rectangular keep out zone extending width / 2 on each side of the segment from to to,
+    // and rebuild the graph so paths are routed around it
+    pub fn add_no_fly_corridor(&mut self, from: Location, to: Location, width_m: f32) {
+        let a = Point::from((&from, &self.origin));
+        let b = Point::from((&to, &self.origin));
+        let length = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+        assert!(width_m > 0f32, "Corridor width must be positive");
+        assert!(length > 0f32, "Corridor requires two distinct locations");
+        // offset perpendicular to the corridor axis
+        let dx = -(b.y - a.y) / length * width_m / 2f32;
+        let dy = (b.x - a.x) / length * width_m / 2f32;
+        let corners = [
+            Point::new(a.x + dx, a.y + dy, 0f32),
+            Point::new(b.x + dx, b.y + dy, 0f32),
+            Point::new(b.x - dx, b.y - dy, 0f32),
+            Point::new(a.x - dx, a.y - dy, 0f32),
+        ];
+        self.keep_out_zones.push(
+            corners
+                .iter()
+                .map(|corner| Location::from((corner, &self.origin)))
+                .collect(),
+        );
+        self.build_graph();
+    }
+
+    // Recheck every edge against the current obstacles before it is taken
+    // useful when obstacles move without the graph being rebuilt, at the cost of search time
+    pub fn set_lazy_recheck(&mut self, lazy_recheck: bool) {
+        self.lazy_recheck = lazy_recheck;
+    }
+
+    // Add the arc around a ring to its nearest exit to the heuristic, on by default
+    // the straight line distance alone is kept for comparison, both give the shortest path
+    pub fn set_ring_heuristic(&mut self, ring_heuristic: bool) {
+        self.ring_heuristic = ring_heuristic;
+    }
+
+    // Abort searches running longer than timeout, searches are not bounded until it is set
+    // find_shortest_path returns the best partial path, adjust_path only reports no path
+    pub fn set_planning_timeout(&mut self, timeout: Duration) {
+        self.planning_timeout = Some(timeout);
+    }
+
+    pub fn clear_planning_timeout(&mut self) {
+        self.planning_timeout = None;
+    }
+
+    // Reject locations and flag path segments outside of min to max, in meters
+    // altitudes are not limited until it is set
+    pub fn set_altitude_limits(&mut self, min: f32, max: f32) {
+        assert!(min <= max);
+        self.altitude_limits = (min, max);
+    }
+
+    // Number of search iterations between checks of the planning deadline
+    pub fn set_timeout_check_interval(&mut self, interval: u32) {
+        assert!(interval > 0);
+        self.timeout_check_interval = interval;
+    }
+
+    // Merge new ring vertices into an existing vertex within degrees of their angle
+    // takes effect the next time the graph is built, 0 keeps every vertex
+    pub fn set_min_vertex_angle_separation(&mut self, degrees: f32) {
+        assert!(degrees >= 0f32);
+        self.min_vertex_separation = degrees.to_radians();
+    }
+
+    // Cost in meters added to a path for each turn, 0 to search for the shortest path
+    // a turn is flying around a node with a heading change over the turn threshold
+    pub fn set_turn_penalty(&mut self, penalty: f32) {
+        assert!(penalty >= 0f32);
+        self.turn_penalty = penalty;
+    }
+
+    // Smallest heading change in degrees around a node that is counted as a turn
+    pub fn set_turn_threshold(&mut self, degrees: f32) {
+        assert!(degrees >= 0f32);
+        self.turn_threshold = degrees.to_radians();
+    }
+
+    // Wind velocity in m/s, wind_x blowing towards east and wind_y towards north
+    pub fn set_wind(&mut self, wind_x: f32, wind_y: f32) {
+        self.wind = (wind_x, wind_y);
+    }
+
+    // Airspeed in m/s used to predict when each segment is flown past moving obstacles
+    pub fn set_airspeed(&mut self, airspeed_m_s: f32) {
+        assert!(airspeed_m_s > 0f32);
+        self.airspeed = airspeed_m_s;
+    }
+
+    // Distance in meters under which consecutive waypoints of stitched paths are merged
+    pub fn set_waypoint_tolerance(&mut self, tolerance_m: f32) {
+        assert!(tolerance_m >= 0f32);
+        self.waypoint_tolerance = tolerance_m;
+    }
+
+    // Number of standard deviations of uncertain obstacles kept clear of
+    // takes effect the next time the graph is built
+    pub fn set_risk_sigma(&mut self, sigma: f32) {
+        assert!(sigma >= 0f32);
+        self.risk_sigma = sigma;
+    }
+
+    // Altitude in meters obstacles are flown around at, modelling each obstacle as a cone rather
+    // than a cylinder so rings shrink towards its top, unset by default
+    // takes effect the next time the graph is built
+    pub fn set_flight_altitude(&mut self, altitude: f32) {
+        assert!(altitude >= 0f32);
+        self.flight_altitude = Some(altitude);
+    }
+
+    // Cost in meters of each second a path is seen by one threat, see
+    // find_path_minimizing_exposure
+    pub fn set_threat_weight(&mut self, weight: f32) {
+        assert!(weight >= 0f32);
+        self.threat_weight = weight;
+    }
+
+    // Cost in meters removed from an edge for each target it covers, see
+    // find_path_maximizing_sensor_coverage
+    pub fn set_coverage_bonus(&mut self, bonus: f32) {
+        assert!(bonus >= 0f32);
+        self.coverage_bonus = bonus;
+    }
+
+    // Cost of flying distance from a to b at altitude, the distance plus the weighted time seen
+    // by the threats in sight of the middle of the segment, less the coverage bonus of every
+    // target within the sensor field of view of the middle. Never below 0, and never below the
+    // distance without targets, so the heuristic only needs to be dropped for coverage
+    fn edge_cost(&self, a: &Point, b: &Point, distance: f32, altitude: f32) -> f32 {
+        if self.threats.is_empty() && self.targets.is_empty() {
+            return distance;
+        }
+        let middle = Point::new((a.x + b.x) / 2f32, (a.y + b.y) / 2f32, altitude);
+        let visible = self
+            .threats
+            .iter()
+            .filter(|threat| self.line_of_sight(threat, &middle))
+            .count();
+        let covered = self
+            .targets
+            .iter()
+            .filter(|target| target.distance(&middle) <= self.sensor_fov)
+            .count();
+        let cost = distance + self.threat_weight * distance / self.airspeed * visible as f32;
+        (cost - self.coverage_bonus * covered as f32).max(0f32)
+    }
+
+    // Heading change flying around the node of last, from the vertex the path entered the node
+    // returns the angle and the entry vertex. The angle is 0 if last is not attached to a node
+    fn swept_angle(
+        last: &Rc<RefCell<Vertex>>,
+        vertex_nodes: &HashMap<i32, usize>,
+    ) -> (f32, Rc<RefCell<Vertex>>) {
+        let mut angle = 0f32;
+        let mut cur_vertex = last.clone();
+        let node = match vertex_nodes.get(&last.borrow().index) {
+            Some(node) => node,
+            None => return (angle, cur_vertex),
+        };
+        loop {
+            let parent = match cur_vertex.borrow().parent {
+                Some(ref parent) if vertex_nodes.get(&parent.borrow().index) == Some(node) => {
+                    parent.clone()
                 }
+                _ => break,
+            };
+            angle += angle_difference(parent.borrow().angle, cur_vertex.borrow().angle).abs();
+            cur_vertex = parent;
+        }
+        (angle, cur_vertex)
+    }
+
+    // Number of turns along the path ending at last
+    fn count_turns(&self, last: &Rc<RefCell<Vertex>>, vertex_nodes: &HashMap<i32, usize>) -> usize {
+        let mut turns = 0;
+        let mut cur_vertex = last.clone();
+        loop {
+            let (angle, entry) = Self::swept_angle(&cur_vertex, vertex_nodes);
+            if angle > self.turn_threshold {
+                turns += 1;
             }
+            cur_vertex = match entry.borrow().parent {
+                Some(ref parent) => parent.clone(),
+                None => break,
+            };
         }
-        false
+        turns
     }
-}
 
-impl Algorithm for Tanstar {
-    type Config = TConfig;
+    // Whether paths may not use node, because it is forbidden or its obstacle is passable
+    fn is_skipped_node(&self, node: usize) -> bool {
+        self.forbidden_nodes.contains(&node) || self.passable_obstacles.contains(&node)
+    }
 
-    fn init(
-        &mut self,
-        config: Self::Config,
-        flyzones: Vec<Vec<Location>>,
-        obstacles: Vec<Obstacle>,
-    ) {
-        // Flyzone validation
-        assert!(!flyzones.is_empty());
-        for flyzone in &flyzones {
-            assert!(flyzone.len() >= 3);
-        }
-        assert!(!Self::invalid_flyzone(
-            &flyzones,
-            &Self::find_origin(&flyzones)
-        ));
+    fn is_forbidden_edge(&self, node_a: usize, node_b: usize) -> bool {
+        self.forbidden_edges
+            .contains(&(node_a.min(node_b), node_a.max(node_b)))
+    }
 
-        self.config = config;
-        self.flyzones = flyzones;
-        self.obstacles = obstacles;
-        self.build_graph();
-        self.initialized = true;
+    // Map the index of every vertex in the graph to the index of the node it is attached to
+    fn vertex_nodes(&self) -> HashMap<i32, usize> {
+        let mut vertex_nodes = HashMap::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            for vertex in node.borrow().vertices() {
+                vertex_nodes.insert(vertex.borrow().index, i);
+            }
+        }
+        vertex_nodes
     }
 
     // Find best path using the a* algorithm
-    // Return path if found and none if any error occured or no path found
-    fn adjust_path<T>(
+    // Search is aborted with the best partial path once planning_timeout is exceeded
+    // config.max_process_time is not used, searches are unbounded until set_planning_timeout
+    pub fn find_shortest_path<T>(
         &mut self,
         start: Location,
         end: Location,
-    ) -> Option<LinkedList<Waypoint<T>>> {
-        let mut path = None;
+    ) -> Result<LinkedList<Waypoint<T>>, PathfinderError> {
+        self.search_goals(start, &[end])
+            .map(|(waypoints, _)| waypoints)
+    }
+
+    // Search from start until the first of goals is reached, returning the waypoints to it and
+    // its index in goals. Edges are flown at the lowest altitude of start and every goal
+    pub(crate) fn search_goals<T>(
+        &mut self,
+        start: Location,
+        goals: &[Location],
+    ) -> Result<(LinkedList<Waypoint<T>>, usize), PathfinderError> {
+        self.last_search_explored = 0;
+        self.last_path_vertices.clear();
+        self.last_path_types.clear();
+        self.last_path_turns = 0;
+        if let Err(error) = self.validate_location(&start).and_then(|_| {
+            goals
+                .iter()
+                .try_for_each(|goal| self.validate_location(goal))
+        }) {
+            self.last_path = None;
+            return Err(error);
+        }
+        let deadline = self
+            .planning_timeout
+            .map(|timeout| Instant::now() + timeout);
+        let mut iterations = 0u32;
+        let mut closest: Option<Rc<RefCell<Vertex>>> = None; // explored vertex nearest to end
+        self.update_dirty_nodes();
+        let mut path = Err(PathfinderError::NoPathFound);
         let mut open_set = Queue::new(); // candidate vertices
         let mut close_set: HashSet<i32> = HashSet::new(); // set of vertex already visited
 
@@ -141,23 +475,21 @@ impl Algorithm for Tanstar {
             &self.origin,
             self.config.turning_radius,
         ))));
-        let end_node = Rc::new(RefCell::new(Node::from((
-            &end,
-            &self.origin,
-            self.config.turning_radius,
-        ))));
+        let end_nodes: Vec<Node> = goals
+            .iter()
+            .map(|goal| Node::from((goal, &self.origin, self.config.turning_radius)))
+            .collect();
 
-        let end_point = Point::from((&end, &self.origin));
-        let min_height = if start.alt() > end.alt() {
-            end.alt()
-        } else {
-            start.alt()
-        };
+        // the heuristic is only meaningful towards a single goal
+        let end_point = Point::from((&goals[0], &self.origin));
+        let min_height = goals
+            .iter()
+            .fold(start.alt(), |height, goal| height.min(goal.alt()));
         println!("Height threshold {}", min_height);
 
-        let temp_vertices = self.add_temp_vertices(
+        let (temp_vertices, end_vertices) = self.add_temp_vertices(
             &start_node.borrow(),
-            &end_node.borrow(),
+            &end_nodes,
             min_height,
             &end_point,
             &mut open_set,
@@ -170,29 +502,140 @@ impl Algorithm for Tanstar {
             println!("{}, {}", v_loc.lat_degree(), v_loc.lon_degree());
         }
 
+        let vertex_nodes = if self.forbidden_edges.is_empty()
+            && self.forbidden_nodes.is_empty()
+            && self.passable_obstacles.is_empty()
+            && self.turn_penalty == 0f32
+            && self.max_turns.is_none()
+        {
+            HashMap::new()
+        } else {
+            self.vertex_nodes()
+        };
+
         //A* algorithm - find shortest path from plane to destination
         while let Some(cur) = open_set.pop() {
+            self.last_search_explored += 1;
             assert!(cur.borrow().index != HEADER_VERTEX_INDEX);
+            if let Some(range) = self.max_range {
+                if cur.borrow().g_cost > range {
+                    continue;
+                }
+            }
             println!("current vertex {}", cur.borrow());
             if cur.borrow().index == END_VERTEX_INDEX {
-                path = Some(self.generate_waypoint::<T>(cur, start.alt.into(), end.alt.into()));
+                let goal = end_vertices
+                    .iter()
+                    .find(|(vertex, _)| Rc::ptr_eq(vertex, &cur))
+                    .map_or(0, |&(_, goal)| goal);
+                self.last_path_vertices = Self::vertex_chain(&cur);
+                self.last_path_types = self.waypoint_types(&cur);
+                self.last_path_cost = cur.borrow().g_cost;
+                if !vertex_nodes.is_empty() {
+                    self.last_path_turns = self.count_turns(&cur, &vertex_nodes);
+                }
+                path = match self.max_turns {
+                    Some(max_turns) if self.last_path_turns > max_turns => {
+                        Err(PathfinderError::TooManyTurns {
+                            turns: self.last_path_turns,
+                        })
+                    }
+                    _ => Ok((
+                        self.generate_waypoint::<T>(cur, start.alt.into(), goals[goal].alt.into()),
+                        goal,
+                    )),
+                };
                 break;
             }
             close_set.insert(cur.borrow().index);
+            let remaining = |vertex: &Rc<RefCell<Vertex>>| {
+                let vertex = vertex.borrow();
+                vertex.f_cost - vertex.g_cost
+            };
+            let is_closer = match closest {
+                Some(ref best) => remaining(&cur) < remaining(best),
+                None => true,
+            };
+            if is_closer {
+                closest = Some(cur.clone());
+            }
+            iterations += 1;
+            if iterations == self.timeout_check_interval {
+                iterations = 0;
+            }
+            if iterations == 0 && deadline.is_some_and(|deadline| Instant::now() > deadline) {
+                path = Err(PathfinderError::PlanningTimeout {
+                    partial_path: closest.as_ref().map(|best| self.partial_path(best, start)),
+                });
+                break;
+            }
 
             let cur_vertex = cur.borrow();
-            let state = &mut (&mut open_set, &close_set, &cur, &end_point);
+            let cur_node = vertex_nodes.get(&cur_vertex.index).cloned();
+            let state = &mut (
+                &mut open_set,
+                &close_set,
+                &cur,
+                &end_point,
+                (self.search_heuristic_weight(), self.ring_heuristic),
+            );
             let g_cost = cur_vertex.g_cost;
+            // leaving the node after turning around it
+            let turn_cost = if self.turn_penalty > 0f32
+                && Self::swept_angle(&cur, &vertex_nodes).0 > self.turn_threshold
+            {
+                self.turn_penalty
+            } else {
+                0f32
+            };
             for connection in &cur_vertex.connection {
                 // println!(
                 //     "Adding connection {} to queue",
                 //     connection.neighbor.borrow().index
                 // );
                 // Only add vertex if height meets threshold requirement
-                if min_height > connection.threshold {
+                let next_node = vertex_nodes.get(&connection.neighbor.borrow().index);
+                if let Some(&b) = next_node {
+                    if self.is_skipped_node(b) {
+                        continue;
+                    }
+                }
+                if let (Some(a), Some(&b)) = (cur_node, next_node) {
+                    if self.is_forbidden_edge(a, b) {
+                        continue;
+                    }
+                }
+                if self.no_flyover && connection.threshold > 0f32 {
+                    continue;
+                }
+                if self.lazy_recheck
+                    && connection.crosses_any_obstacle(
+                        &cur_vertex.location,
+                        &self.obstacles,
+                        &self.origin,
+                    )
+                {
+                    continue;
+                }
+                // moving obstacles are checked when the edge would be reached
+                let threshold = if self.obstacle_velocities.is_empty() {
+                    connection.threshold
+                } else {
+                    connection.threshold.max(self.moving_obstacle_threshold(
+                        &cur_vertex.location,
+                        &connection.neighbor.borrow().location,
+                        g_cost / self.airspeed,
+                    ))
+                };
+                if min_height > threshold {
                     // println!("Met threshold requirement of {}", connection.threshold);
                     let mut next = connection.neighbor.clone();
-                    let dist = connection.distance;
+                    let dist = self.edge_cost(
+                        &cur_vertex.location,
+                        &next.borrow().location,
+                        connection.distance,
+                        min_height,
+                    ) + turn_cost;
                     Self::update_vertex(state, g_cost, next, dist);
                 }
             }
@@ -210,14 +653,187 @@ impl Algorithm for Tanstar {
                     }
                 }
 
+                let weight = self.edge_cost(
+                    &cur_vertex.location,
+                    &next.borrow().location,
+                    weight,
+                    min_height,
+                );
                 Self::update_vertex(state, g_cost, next, weight);
             }
         }
 
         Node::prune_vertices(temp_vertices);
+        self.last_path = path
+            .as_ref()
+            .ok()
+            .map(|(waypoints, goal)| Self::assemble_path(start, waypoints, goals[*goal]));
         path
     }
 
+    // Path from start through the waypoints of a search to goal
+    fn assemble_path<T>(
+        start: Location,
+        waypoints: &LinkedList<Waypoint<T>>,
+        goal: Location,
+    ) -> Vec<Location> {
+        let mut path = vec![start];
+        path.extend(waypoints.iter().map(|wp| wp.location));
+        path.push(goal);
+        path
+    }
+
+    // Indices of the vertices from the start vertex to last, following the parents of last
+    fn vertex_chain(last: &Rc<RefCell<Vertex>>) -> Vec<i32> {
+        let mut indices = vec![last.borrow().index];
+        let mut cur_vertex = last.clone();
+        loop {
+            let parent = match cur_vertex.borrow().parent {
+                Some(ref parent) => parent.clone(),
+                None => break,
+            };
+            indices.push(parent.borrow().index);
+            if parent.borrow().index == START_VERTEX_INDEX {
+                break;
+            }
+            cur_vertex = parent;
+        }
+        indices.reverse();
+        indices
+    }
+
+    // Type of the start, every waypoint and the end of the path ending at last
+    // a waypoint is on the ring of a node when its neighbor along the path is on the same node
+    fn waypoint_types(&self, last: &Rc<RefCell<Vertex>>) -> Vec<WaypointType> {
+        let mut chain = vec![last.clone()];
+        while chain[chain.len() - 1].borrow().index != START_VERTEX_INDEX {
+            let parent = match chain[chain.len() - 1].borrow().parent {
+                Some(ref parent) => parent.clone(),
+                None => break,
+            };
+            chain.push(parent);
+        }
+        chain.reverse();
+
+        let vertex_nodes = self.vertex_nodes();
+        let node = |vertex: &Rc<RefCell<Vertex>>| vertex_nodes.get(&vertex.borrow().index);
+        let mut types = vec![WaypointType::StartPoint];
+        for i in 1..chain.len() - 1 {
+            let (prev, cur, next) = (&chain[i - 1], &chain[i], &chain[i + 1]);
+            let on_ring =
+                |other: &Rc<RefCell<Vertex>>| node(cur).is_some() && node(other) == node(cur);
+            let location = |vertex: &Rc<RefCell<Vertex>>| vertex.borrow().location;
+            let threshold = match self.valid_path(&location(prev), &location(cur)) {
+                PathValidity::Flyover(threshold) => threshold,
+                _ => 0f32,
+            };
+            types.push(if on_ring(prev) {
+                WaypointType::PathHugEnd
+            } else if threshold > 0f32 {
+                WaypointType::FlyoverApex
+            } else if on_ring(next) {
+                WaypointType::PathHugStart
+            } else {
+                WaypointType::DirectSegment
+            });
+        }
+        types.push(WaypointType::GoalPoint);
+        types
+    }
+
+    // Locations from start to an explored vertex, following its parents
+    fn partial_path(&self, last: &Rc<RefCell<Vertex>>, start: Location) -> Vec<Location> {
+        let mut locations = Vec::new();
+        let mut cur_vertex = last.clone();
+        while cur_vertex.borrow().index != START_VERTEX_INDEX {
+            let mut loc = Location::from((&cur_vertex.borrow().location, &self.origin));
+            loc.alt = start.alt;
+            locations.push(loc);
+            let parent = match cur_vertex.borrow().parent {
+                Some(ref parent) => parent.clone(),
+                None => break,
+            };
+            cur_vertex = parent;
+        }
+        locations.push(start);
+        locations.reverse();
+        locations
+    }
+
+    // determine if flyzone intersects itself (correct order)
+    // inputs (flyzones, origin), outputs true if invalid
+    #[allow(clippy::many_single_char_names)]
+    fn invalid_flyzone(flyzones: &[Vec<Location>], origin: &Location) -> bool {
+        for flyzone in flyzones {
+            let mut vertices = Vec::new();
+            for loc in flyzone {
+                let point = Point::from((loc, origin));
+                vertices.push(point);
+            }
+            let n = vertices.len();
+            // compares any side of flyzone, ab, with any non-adjacent side, cd
+            for ab in 0..n - 2 {
+                let a = vertices[ab];
+                let b = vertices[ab + 1];
+                for i in 2..n - 1 {
+                    let cd = ab + i;
+                    let c = vertices[cd];
+                    let d = vertices[(cd + 1) % n];
+                    if intersect(&a, &b, &c, &d) {
+                        return true;
+                    }
+                    if cd + 1 == n {
+                        break;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+impl Algorithm for Tanstar {
+    type Config = TConfig;
+
+    fn init(
+        &mut self,
+        config: Self::Config,
+        flyzones: Vec<Vec<Location>>,
+        obstacles: Vec<Obstacle>,
+    ) {
+        // Flyzone validation
+        assert!(!flyzones.is_empty());
+        for flyzone in &flyzones {
+            assert!(flyzone.len() >= 3);
+        }
+        assert!(!Self::invalid_flyzone(
+            &flyzones,
+            &Self::find_origin(&flyzones)
+        ));
+
+        self.config = config;
+        self.flyzones = flyzones;
+        self.obstacles = obstacles;
+        self.build_graph();
+        self.initialized = true;
+    }
+
+    // Find best path using the a* algorithm
+    // Return path if found and none if any error occured or no path found
+    fn adjust_path<T>(
+        &mut self,
+        start: Location,
+        end: Location,
+    ) -> Option<LinkedList<Waypoint<T>>> {
+        match self.find_shortest_path(start, end) {
+            Ok(path) => Some(path),
+            Err(error) => {
+                println!("no path: {}", error);
+                None
+            }
+        }
+    }
+
     fn get_config(&self) -> &Self::Config {
         &self.config
     }
@@ -247,16 +863,17 @@ impl Algorithm for Tanstar {
 }
 
 impl Tanstar {
-    // Helper function to add temp vertices connecting start and end
+    // Helper function to add temp vertices connecting start and every end
     fn add_temp_vertices(
         &mut self,
         start_node: &Node,
-        end_node: &Node,
+        end_nodes: &[Node],
         min_height: f32,
         end_point: &Point,
         open_set: &mut Queue,
-    ) -> LinkedList<Rc<RefCell<Vertex>>> {
+    ) -> (LinkedList<Rc<RefCell<Vertex>>>, EndVertices) {
         let mut temp_vertices = LinkedList::new();
+        let mut end_vertices = Vec::new();
         let start_vertex = Rc::new(RefCell::new(Vertex::new(
             &mut START_VERTEX_INDEX,
             &start_node,
@@ -267,59 +884,101 @@ impl Tanstar {
         //Prepare graph for A*
         println!("\n[ Inserting temp vertices ]");
         for i in 0..self.nodes.len() {
+            if self.is_skipped_node(i) {
+                continue;
+            }
             let temp_node = &self.nodes[i];
+            let start_radii = self.pair_radii(start_node, &temp_node.borrow());
             let (temp_paths, _) = self.find_path(&start_node, &temp_node.borrow());
             println!("[start {}]: path count -> {}", i, temp_paths.len());
 
             for (_, b, dist, threshold) in temp_paths {
-                if min_height < threshold {
+                if min_height < threshold || (self.no_flyover && threshold > 0f32) {
                     continue;
                 }
 
                 println!("Inserting start vertex {}", self.num_vertices);
-                let mut vertex =
-                    Vertex::new(&mut self.num_vertices, &temp_node.borrow(), b, vec![]);
+                let mut vertex = Vertex::with_radius(
+                    &mut self.num_vertices,
+                    &temp_node.borrow(),
+                    b,
+                    start_radii.1,
+                    vec![],
+                );
                 vertex.parent = Some(start_vertex.clone());
+                let dist = self.edge_cost(&start_node.origin, &vertex.location, dist, min_height);
                 vertex.g_cost = dist;
-                vertex.f_cost = dist + vertex.location.distance(end_point);
                 let vertex_p = Rc::new(RefCell::new(vertex));
                 temp_node.borrow_mut().insert_vertex(vertex_p.clone());
+                let heuristic = Self::heuristic(&vertex_p.borrow(), end_point, self.ring_heuristic);
+                vertex_p.borrow_mut().f_cost = dist + self.search_heuristic_weight() * heuristic;
                 open_set.push(vertex_p.clone());
                 temp_vertices.push_front(vertex_p.clone());
             }
 
-            let (temp_paths, _) = self.find_path(&temp_node.borrow(), &end_node);
-            println!("[end {}]: path count -> {}", i, temp_paths.len());
+            for (goal, end_node) in end_nodes.iter().enumerate() {
+                let end_radii = self.pair_radii(&temp_node.borrow(), end_node);
+                let (temp_paths, _) = self.find_path(&temp_node.borrow(), end_node);
+                println!("[end {}]: path count -> {}", i, temp_paths.len());
 
-            for (a, b, dist, threshold) in temp_paths {
-                println!("Inserting end vertex {}", self.num_vertices);
-                let end_vertex = Rc::new(RefCell::new(Vertex::new(
-                    &mut END_VERTEX_INDEX,
-                    &end_node,
-                    b,
-                    vec![],
-                )));
-                let connection = Connection::new(end_vertex.clone(), dist, threshold);
-                let vertex = Rc::new(RefCell::new(Vertex::new(
-                    &mut self.num_vertices,
-                    &temp_node.borrow(),
-                    a,
-                    vec![connection],
-                )));
-                temp_node.borrow_mut().insert_vertex(vertex.clone());
-                temp_vertices.push_front(vertex.clone());
+                for (a, b, dist, threshold) in temp_paths {
+                    println!("Inserting end vertex {}", self.num_vertices);
+                    let mut end_vertex = Vertex::with_radius(
+                        &mut END_VERTEX_INDEX,
+                        end_node,
+                        b,
+                        end_radii.1,
+                        vec![],
+                    );
+                    // end vertices share an index, so each one must start out unreached for the
+                    // cheapest of them to be queued
+                    end_vertex.g_cost = f32::INFINITY;
+                    let end_vertex = Rc::new(RefCell::new(end_vertex));
+                    end_vertices.push((end_vertex.clone(), goal));
+                    let connection = Connection::new(end_vertex, dist, threshold);
+                    let vertex = Rc::new(RefCell::new(Vertex::with_radius(
+                        &mut self.num_vertices,
+                        &temp_node.borrow(),
+                        a,
+                        end_radii.0,
+                        vec![connection],
+                    )));
+                    temp_node.borrow_mut().insert_vertex(vertex.clone());
+                    temp_vertices.push_front(vertex.clone());
+                }
             }
         }
 
-        temp_vertices
+        (temp_vertices, end_vertices)
+    }
+
+    // Inflation of the heuristic for the current search. Covering targets lowers edges below
+    // their length, so the distance to goal could overestimate and the search falls back to
+    // dijkstra to keep finding the cheapest path
+    fn search_heuristic_weight(&self) -> f32 {
+        if self.targets.is_empty() || self.coverage_bonus == 0f32 {
+            self.heuristic_weight
+        } else {
+            0f32
+        }
+    }
+
+    // Estimate of the cost from vertex to goal, the straight line distance unless ring is set
+    fn heuristic(vertex: &Vertex, goal: &Point, ring: bool) -> f32 {
+        if ring {
+            vertex.heuristic_to(goal)
+        } else {
+            vertex.location.distance(goal)
+        }
     }
 
     fn update_vertex(
-        (open_set, close_set, cur, end_point): &mut (
+        (open_set, close_set, cur, end_point, (heuristic_weight, ring_heuristic)): &mut (
             &mut Queue,
             &HashSet<i32>,
             &Rc<RefCell<Vertex>>,
             &Point,
+            Heuristic,
         ),
         cur_g_cost: f32,
         next: Rc<RefCell<Vertex>>,
@@ -343,7 +1002,8 @@ impl Tanstar {
                 return;
             }
             let mut next_mut = next.borrow_mut();
-            let new_f_cost = new_g_cost + next_mut.location.distance(end_point);
+            let heuristic = Self::heuristic(&next_mut, end_point, *ring_heuristic);
+            let new_f_cost = new_g_cost + *heuristic_weight * heuristic;
             next_mut.g_cost = new_g_cost;
             next_mut.f_cost = new_f_cost;
             next_mut.parent = Some(cur.clone());
@@ -390,6 +1050,7 @@ impl Tanstar {
 
 #[cfg(test)]
 mod test {
+    use super::graph::util::test_helpers::*;
     use super::*;
 
     #[test]
@@ -402,4 +1063,262 @@ mod test {
         let test_flyzone = vec![vec![a, b, d, c]];
         assert!(Tanstar::invalid_flyzone(&test_flyzone, &origin));
     }
+
+    #[test]
+    fn forbidden_edge_test() {
+        let flyzones = square_flyzone(400f32);
+        let centers = [
+            Point::new(150f32, 200f32, 0f32),
+            Point::new(250f32, 200f32, 0f32),
+        ];
+        let obstacles = centers
+            .iter()
+            .map(|c| obstacle_from_meters(c.x, c.y, 30f32, 50f32))
+            .collect();
+        let mut pathfinder = Tanstar::create(5f32, flyzones, obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let end = Location::from_meters(350f32, 200f32, 20f32, &origin());
+
+        // true if the path flies directly from a vertex on the first obstacle to the second
+        let uses_edge = |pathfinder: &Tanstar, path: &LinkedList<Waypoint<()>>| {
+            let nodes: Vec<Option<usize>> = path
+                .iter()
+                .map(|wp| {
+                    let point = Point::from((&wp.location, &pathfinder.origin));
+                    centers
+                        .iter()
+                        .position(|c| (c.distance(&point) - 35f32).abs() < 0.01)
+                })
+                .collect();
+            nodes
+                .windows(2)
+                .any(|leg| leg[0].is_some() && leg[1].is_some() && leg[0] != leg[1])
+        };
+
+        let path = pathfinder.adjust_path::<()>(start, end).unwrap();
+        assert!(uses_edge(&pathfinder, &path));
+
+        pathfinder.set_forbidden_edge(1, 0);
+        let path = pathfinder.adjust_path::<()>(start, end).unwrap();
+        assert!(!uses_edge(&pathfinder, &path));
+
+        pathfinder.clear_forbidden_edges();
+        let path = pathfinder.adjust_path::<()>(start, end).unwrap();
+        assert!(uses_edge(&pathfinder, &path));
+    }
+
+    #[test]
+    fn origin_test() {
+        let flyzones = vec![vec![
+            Location::from_degrees(30.32469, -97.60466, 0f32),
+            Location::from_degrees(30.32437, -97.60367, 0f32),
+            Location::from_degrees(30.32356, -97.60410, 0f32),
+            Location::from_degrees(30.32392, -97.60513, 0f32),
+        ]];
+        let pathfinder = Tanstar::create(1f32, flyzones, vec![]);
+        let origin = pathfinder.origin();
+        assert!((origin.lat_degree() - 30.32356).abs() < 1e-6);
+        assert!((origin.lon_degree() - -97.60513).abs() < 1e-6);
+    }
+
+    #[test]
+    fn path_count_test() {
+        let flyzones = square_flyzone(400f32);
+        let mut pathfinder = Tanstar::create(5f32, flyzones, vec![]);
+        assert_eq!(pathfinder.waypoint_count(), 0);
+        assert_eq!(pathfinder.segment_count(), 0);
+
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let end = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        let path = pathfinder.adjust_path::<()>(start, end).unwrap();
+        assert_eq!(pathfinder.waypoint_count(), path.len() + 2);
+        assert_eq!(pathfinder.segment_count(), path.len() + 1);
+    }
+
+    #[test]
+    fn no_fly_corridor_test() {
+        let flyzones = square_flyzone(400f32);
+        let mut pathfinder = Tanstar::create(5f32, flyzones, vec![]);
+        let start = Location::from_meters(100f32, 200f32, 20f32, &origin());
+        let end = Location::from_meters(300f32, 200f32, 20f32, &origin());
+        let nodes = pathfinder.nodes.len();
+
+        pathfinder.add_no_fly_corridor(
+            Location::from_meters(200f32, 100f32, 0f32, &origin()),
+            Location::from_meters(200f32, 300f32, 0f32, &origin()),
+            20f32,
+        );
+        // one virtual node outside each corner
+        assert_eq!(pathfinder.nodes.len(), nodes + 4);
+        let a = Point::from((&start, &pathfinder.origin));
+        let b = Point::from((&end, &pathfinder.origin));
+        match pathfinder.valid_path(&a, &b) {
+            PathValidity::Invalid => {}
+            _ => panic!(),
+        }
+        let inside = Point::from((
+            &Location::from_meters(200f32, 200f32, 20f32, &origin()),
+            &pathfinder.origin,
+        ));
+        match pathfinder.valid_path(&a, &inside) {
+            PathValidity::Invalid => {}
+            _ => panic!(),
+        }
+
+        pathfinder.adjust_path::<()>(start, end).unwrap();
+        let path = pathfinder.last_path.clone().unwrap();
+        assert!(path.len() > 2);
+        for leg in path.windows(2) {
+            let a = Point::from((&leg[0], &pathfinder.origin));
+            let b = Point::from((&leg[1], &pathfinder.origin));
+            match pathfinder.valid_path(&a, &b) {
+                PathValidity::Flyover(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn lazy_recheck_test() {
+        let flyzones = square_flyzone(400f32);
+        let obstacle = |x, y, radius| obstacle_from_meters(x, y, radius, 50f32);
+        let obstacles = vec![
+            obstacle(150f32, 200f32, 30f32),
+            obstacle(250f32, 200f32, 30f32),
+            obstacle(350f32, 350f32, 10f32),
+        ];
+        let mut pathfinder = Tanstar::create(5f32, flyzones, obstacles);
+        // start off the axis so one tangent between the large obstacles is strictly shorter
+        let start = Location::from_meters(50f32, 190f32, 20f32, &origin());
+        let end = Location::from_meters(350f32, 200f32, 20f32, &origin());
+
+        // highest obstacle crossed by any leg of the last path
+        let max_crossed = |pathfinder: &Tanstar| {
+            pathfinder
+                .last_path
+                .as_ref()
+                .unwrap()
+                .windows(2)
+                .map(|leg| pathfinder.segment_flyover_altitude(&leg[0], &leg[1]))
+                .fold(0f32, f32::max)
+        };
+
+        // move the small obstacle onto the tangent between the two large obstacles
+        pathfinder.adjust_path::<()>(start, end).unwrap();
+        let side = Point::from((
+            &pathfinder.last_path.as_ref().unwrap()[1],
+            &pathfinder.origin,
+        ))
+        .y;
+        let y = if side > 200f32 { 235f32 } else { 165f32 };
+        let moved = Location::from_meters(200f32, y, 50f32, &origin());
+        pathfinder
+            .set_obstacle_position(2, moved.lat_degree(), moved.lon_degree(), 50f32)
+            .unwrap();
+
+        // stale edges still lead through the moved obstacle
+        pathfinder.adjust_path::<()>(start, end).unwrap();
+        assert!(max_crossed(&pathfinder) >= 50f32);
+
+        pathfinder.set_lazy_recheck(true);
+        pathfinder.adjust_path::<()>(start, end).unwrap();
+        assert!(max_crossed(&pathfinder) < 50f32);
+    }
+
+    #[test]
+    fn planning_timeout_test() {
+        let flyzones = square_flyzone(400f32);
+        let obstacles = vec![obstacle_from_meters(200f32, 200f32, 30f32, 50f32)];
+        let mut pathfinder = Tanstar::create(5f32, flyzones, obstacles);
+        let start = Location::from_meters(50f32, 200f32, 20f32, &origin());
+        let end = Location::from_meters(350f32, 200f32, 20f32, &origin());
+        assert!(pathfinder.find_shortest_path::<()>(start, end).is_ok());
+
+        pathfinder.set_planning_timeout(Duration::from_nanos(0));
+        pathfinder.set_timeout_check_interval(1);
+        match pathfinder.find_shortest_path::<()>(start, end) {
+            Err(PathfinderError::PlanningTimeout {
+                partial_path: Some(partial),
+            }) => assert_eq!(partial[0], start),
+            result => panic!("expected timeout, got {:?}", result.err()),
+        }
+        assert!(pathfinder.last_path.is_none());
+        assert!(pathfinder.adjust_path::<()>(start, end).is_none());
+
+        pathfinder.set_planning_timeout(Duration::from_secs(10));
+        assert!(pathfinder.find_shortest_path::<()>(start, end).is_ok());
+
+        // unbounded once cleared, whatever the config says
+        pathfinder.set_planning_timeout(Duration::from_nanos(0));
+        pathfinder.clear_planning_timeout();
+        pathfinder.config.max_process_time = Duration::from_nanos(0);
+        assert!(pathfinder.find_shortest_path::<()>(start, end).is_ok());
+    }
+
+    #[test]
+    fn explored_vertices_test() {
+        let flyzones = square_flyzone(400f32);
+        let obstacles = [100f32, 200f32, 300f32]
+            .iter()
+            .map(|&x| obstacle_from_meters(x, 200f32, 20f32, 50f32))
+            .collect();
+        let mut pathfinder = Tanstar::create(5f32, flyzones, obstacles);
+        assert_eq!(pathfinder.count_explored_vertices_last_search(), 0);
+
+        let start = Location::from_meters(30f32, 200f32, 20f32, &origin());
+        let far = Location::from_meters(370f32, 200f32, 20f32, &origin());
+        let near = Location::from_meters(30f32, 230f32, 20f32, &origin());
+        pathfinder.adjust_path::<()>(start, far).unwrap();
+        let far_explored = pathfinder.count_explored_vertices_last_search();
+        assert!(far_explored > 0);
+
+        // counts are reset on every search
+        pathfinder.adjust_path::<()>(start, near).unwrap();
+        let near_explored = pathfinder.count_explored_vertices_last_search();
+        assert!(near_explored > 0);
+        assert!(near_explored < far_explored);
+
+        // without a heuristic the search expands vertices in every direction
+        pathfinder.heuristic_weight = 0f32;
+        pathfinder.adjust_path::<()>(start, far).unwrap();
+        let zero_explored = pathfinder.count_explored_vertices_last_search();
+        pathfinder.heuristic_weight = 1f32;
+        assert!(far_explored < zero_explored);
+
+        let outside = Location::from_meters(500f32, 200f32, 20f32, &origin());
+        assert!(pathfinder.adjust_path::<()>(start, outside).is_none());
+        assert_eq!(pathfinder.count_explored_vertices_last_search(), 0);
+    }
+
+    #[test]
+    fn ring_heuristic_test() {
+        let flyzones = square_flyzone(400f32);
+        let obstacles = [
+            (100f32, 100f32),
+            (200f32, 200f32),
+            (300f32, 300f32),
+            (100f32, 300f32),
+            (300f32, 100f32),
+        ]
+        .iter()
+        .map(|&(x, y)| obstacle_from_meters(x, y, 30f32, 50f32))
+        .collect();
+        let mut pathfinder = Tanstar::create(5f32, flyzones, obstacles);
+        let start = Location::from_meters(30f32, 30f32, 20f32, &origin());
+        let goal = Location::from_meters(370f32, 370f32, 20f32, &origin());
+        let search = |pathfinder: &mut Tanstar, ring_heuristic| {
+            pathfinder.set_ring_heuristic(ring_heuristic);
+            pathfinder.adjust_path::<()>(start, goal).unwrap();
+            (
+                pathfinder.count_explored_vertices_last_search(),
+                pathfinder.last_path_cost,
+            )
+        };
+
+        // arcs to the ring exits prune vertices the straight line distance keeps
+        let (straight_explored, straight_cost) = search(&mut pathfinder, false);
+        let (ring_explored, ring_cost) = search(&mut pathfinder, true);
+        assert!(ring_explored < straight_explored);
+        assert!((ring_cost - straight_cost).abs() < 0.01);
+    }
 }